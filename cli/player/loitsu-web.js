@@ -40,12 +40,25 @@ export function add_error(message) {
     add_log("ERROR", "#FF0000", message);
 }
 
+export function set_profiler_overlay(text) {
+    let overlay = document.getElementById("profiler_overlay");
+    if (!overlay) {
+        overlay = document.createElement("pre");
+        overlay.id = "profiler_overlay";
+        overlay.style.cssText = "position: fixed; top: 0; right: 0; margin: 0; padding: 8px; background: rgba(0, 0, 0, 0.6); color: #FFFFFF; font-size: 12px; pointer-events: none; z-index: 10;";
+        document.body.appendChild(overlay);
+    }
+    overlay.textContent = text;
+    overlay.style.display = text ? "block" : "none";
+}
+
 window.addEventListener("resize", () => resize());
 
 window.set_status = set_status;
 window.add_log = add_log;
 window.add_warning = add_warning;
 window.add_error = add_error;
+window.set_profiler_overlay = set_profiler_overlay;
 
 async function run() {
 	await init();
@@ -1,5 +1,6 @@
 use crate::info;
 use crate::shard_gen;
+use colored::*;
 use loitsu::scripting::ScriptingSource;
 use loitsu::Preferences;
 use std::fs::File;
@@ -29,57 +30,22 @@ pub async fn build_assets(out_dir: &PathBuf, force: bool, release: bool) {
 
     info!("Building assets...");
 
-    let files = read_files("assets");
-
-    let mut scenes = Vec::new();
-    {
-        let files = files.clone();
-        for file in files {
-            if file.name.ends_with(".scene.json") {
-                let path = file.path.strip_prefix(asset_path.clone()).unwrap();
-                let name = path
-                    .to_str()
-                    .unwrap()
-                    .replace(".scene.json", "")
-                    .replace("\\", "/");
-                scenes.push((name.to_owned(), String::from_utf8(file.data).unwrap()));
-                info!("Found scene: {}", name);
-            }
-        }
-    }
-
-    let mut scripts = Vec::new();
-    for file in files {
-        if file.name.ends_with(".rn") {
-            scripts.push(ScriptingSource {
-                name: file.name.clone(),
-                source: String::from_utf8(file.data.clone()).unwrap(),
-            });
-        }
-    }
-
-    // lets load our Preferences
-    let preferences = {
-        let mut path = asset_path.clone();
-        path.push("preferences.json");
-        if path.exists() {
-            let mut file = File::open(path).unwrap();
-            let mut data = String::new();
-            file.read_to_string(&mut data).unwrap();
-            serde_json::from_str::<Preferences>(&data).unwrap()
-        } else {
-            panic!("Couldn't find preferences.json! Please create one in the assets directory with the required fields.")
-        }
-    };
+    let (scenes, scripts, preferences) = read_project(&asset_path);
 
     info!(
         "Building {} scenes and {} scripts...",
         scenes.len(),
         scripts.len()
     );
+    // the diagnostics have already been logged by the time this fails
+    let validated_scripts = loitsu::validate_scripts(scripts.clone()).unwrap_or_else(|e| {
+        println!("{} Build failed: {}", "[ERROR]".bright_red(), e);
+        std::process::exit(1);
+    });
     let scenes = loitsu::build_scenes(scenes, scripts.clone());
     info!("Generating shards...");
-    let (shards, static_shard) = shard_gen::generate_shards(scenes, scripts, &preferences);
+    let (shards, static_shard) =
+        shard_gen::generate_shards(scenes, scripts, validated_scripts, &preferences);
 
     // lets make sure the shard dir exists
     let s_path = Path::new(&shard_dir);
@@ -129,6 +95,56 @@ pub async fn build_assets(out_dir: &PathBuf, force: bool, release: bool) {
     );
 }
 
+/// Reads the scenes, scripts and preferences of the project in the assets directory
+pub fn read_project(
+    asset_path: &Path,
+) -> (Vec<(String, String)>, Vec<ScriptingSource>, Preferences) {
+    let files = read_files("assets");
+
+    let mut scenes = Vec::new();
+    {
+        let files = files.clone();
+        for file in files {
+            if file.name.ends_with(".scene.json") {
+                let path = file.path.strip_prefix(asset_path).unwrap();
+                let name = path
+                    .to_str()
+                    .unwrap()
+                    .replace(".scene.json", "")
+                    .replace("\\", "/");
+                scenes.push((name.to_owned(), String::from_utf8(file.data).unwrap()));
+                info!("Found scene: {}", name);
+            }
+        }
+    }
+
+    let mut scripts = Vec::new();
+    for file in files {
+        if file.name.ends_with(".rn") {
+            scripts.push(ScriptingSource {
+                name: file.name.clone(),
+                source: String::from_utf8(file.data.clone()).unwrap(),
+            });
+        }
+    }
+
+    // lets load our Preferences
+    let preferences = {
+        let mut path = asset_path.to_path_buf();
+        path.push("preferences.json");
+        if path.exists() {
+            let mut file = File::open(path).unwrap();
+            let mut data = String::new();
+            file.read_to_string(&mut data).unwrap();
+            serde_json::from_str::<Preferences>(&data).unwrap()
+        } else {
+            panic!("Couldn't find preferences.json! Please create one in the assets directory with the required fields.")
+        }
+    };
+
+    (scenes, scripts, preferences)
+}
+
 fn get_assets_checksum(path: &PathBuf) -> String {
     checksumdir::checksumdir(path.to_str().unwrap()).unwrap()
 }
@@ -0,0 +1,556 @@
+use cargo_toml::{Dependency, Manifest};
+use clap::{Parser, Subcommand};
+use colored::*;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::path::PathBuf;
+use warp::Filter;
+mod asset_builder;
+mod scene_diff;
+mod shard_gen;
+
+#[derive(Debug, Parser)]
+#[command(name = "loitsu")]
+#[command(about = "Tools useful for development with the Loitsu engine", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Build {
+        #[arg(short, long, default_value = "")]
+        target: String,
+        #[arg(short, long, default_value = "false")]
+        release: bool,
+        #[arg(short, long, default_value = "false")]
+        force: bool,
+    },
+    Run {
+        #[arg(short, long, default_value = "")]
+        target: String,
+        #[arg(short, long, default_value = "false")]
+        release: bool,
+        #[arg(short, long, default_value = "false")]
+        force: bool,
+    },
+    Edit {
+        #[arg(short, long, default_value = "")]
+        scene: String,
+    },
+    Clean,
+    /// Replays an input recording made with `LOITSU_RECORD` without opening a window
+    Replay {
+        recording: PathBuf,
+        /// The scene to replay in, defaults to the default scene of the project
+        #[arg(short, long)]
+        scene: Option<String>,
+        /// Prints how long the component methods took during the replay
+        #[arg(short, long)]
+        profile: bool,
+    },
+    /// Runs the `#[test]` functions in the scripts of the project without opening a window
+    Test {
+        /// Only runs the tests whose name contains this
+        filter: Option<String>,
+    },
+    /// Tools for working with scene files
+    Scene {
+        #[command(subcommand)]
+        command: SceneCommands,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SceneCommands {
+    /// Lists the entities, components and properties that differ between two scenes
+    Diff { a: PathBuf, b: PathBuf },
+    /// Three-way merges two versions of a scene. Can be used as a git merge driver:
+    /// `loitsu scene merge %O %A %B`
+    Merge {
+        base: PathBuf,
+        ours: PathBuf,
+        theirs: PathBuf,
+        /// Where to write the merged scene, defaults to overwriting `ours`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Rewrites scenes in the canonical format. Formats every scene in `assets` if no files are
+    /// given
+    Fmt {
+        files: Vec<PathBuf>,
+        /// Only check whether the scenes are formatted, exiting with an error if they aren't
+        #[arg(long, default_value = "false")]
+        check: bool,
+    },
+}
+/// Runs the command given on the command line. Games that register their own script modules can
+/// call this from a binary of their own after registering them, which then replaces the `loitsu`
+/// command for the project.
+pub async fn run() {
+    let args = Cli::parse();
+
+    match args.command {
+        Commands::Build {
+            target,
+            release,
+            force,
+        } => build(&target, release, false, force).await,
+        Commands::Run {
+            target,
+            release,
+            force,
+        } => build(&target, release, true, force).await,
+        Commands::Edit { scene } => run_editor(scene),
+        Commands::Clean => clean(),
+        Commands::Replay {
+            recording,
+            scene,
+            profile,
+        } => replay(&recording, scene, profile),
+        Commands::Test { filter } => test(filter),
+        Commands::Scene { command } => match command {
+            SceneCommands::Diff { a, b } => scene_diff(&a, &b),
+            SceneCommands::Merge {
+                base,
+                ours,
+                theirs,
+                output,
+            } => scene_merge(&base, &ours, &theirs, output.as_ref()),
+            SceneCommands::Fmt { files, check } => scene_fmt(files, check),
+        },
+    }
+}
+
+fn read_scene(path: &PathBuf) -> loitsu::scene_management::Scene {
+    let data = fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Couldn't read scene {}", path.display()));
+    let name = path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .replace(".scene.json", "");
+    loitsu::scene_management::Scene::from_json(name, data)
+}
+
+fn scene_diff(a: &PathBuf, b: &PathBuf) {
+    let changes = scene_diff::diff(&read_scene(a), &read_scene(b));
+    if changes.is_empty() {
+        done("Scenes are identical");
+        return;
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+    info!("{} change(s)", changes.len());
+}
+
+fn scene_fmt(files: Vec<PathBuf>, check: bool) {
+    let files = if files.is_empty() {
+        walkdir::WalkDir::new(std::env::current_dir().unwrap().join("assets"))
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.to_str().unwrap().ends_with(".scene.json"))
+            .collect()
+    } else {
+        files
+    };
+    let mut unformatted = 0;
+    for path in &files {
+        let original = fs::read_to_string(path).unwrap();
+        let formatted = read_scene(path).to_json();
+        if original == formatted {
+            continue;
+        }
+        unformatted += 1;
+        if check {
+            println!("{} {}", "[UNFORMATTED]".yellow(), path.display());
+        } else {
+            fs::write(path, formatted).expect("Unable to write file");
+            info!("Formatted {}", path.display());
+        }
+    }
+    if check && unformatted > 0 {
+        std::process::exit(1);
+    }
+    done(&format!(
+        "{} scene(s) checked, {} {}",
+        files.len(),
+        unformatted,
+        if check { "need formatting" } else { "formatted" }
+    ));
+}
+
+fn scene_merge(base: &PathBuf, ours: &PathBuf, theirs: &PathBuf, output: Option<&PathBuf>) {
+    let (merged, conflicts) =
+        scene_diff::merge(&read_scene(base), &read_scene(ours), &read_scene(theirs));
+    fs::write(output.unwrap_or(ours), merged.to_json()).expect("Unable to write file");
+    if conflicts.is_empty() {
+        done("Scenes merged without conflicts");
+        return;
+    }
+    for conflict in &conflicts {
+        println!("{}", conflict);
+    }
+    println!(
+        "{} {} conflict(s), our version was kept for each of them",
+        "[CONFLICT]".bright_red(),
+        conflicts.len()
+    );
+    std::process::exit(1);
+}
+
+fn replay(recording: &Path, scene: Option<String>, profile: bool) {
+    use loitsu::headless::HeadlessRunner;
+    use loitsu::input::recording::InputRecording;
+    use loitsu::input::InputState;
+    use loitsu::scripting::profiler::{format_report, PROFILER};
+    use loitsu::scripting::rune_runtime::RuneInstance;
+    use loitsu::scripting::ScriptingInstance;
+
+    let recording = InputRecording::load(recording).unwrap_or_else(|e| panic!("{}", e));
+    let asset_path = std::env::current_dir().unwrap().join("assets");
+    let (scenes, scripts, preferences) = asset_builder::read_project(&asset_path);
+    let scene_name = scene.unwrap_or(preferences.default_scene);
+    let scene = scenes
+        .into_iter()
+        .find(|(name, _)| *name == scene_name)
+        .unwrap_or_else(|| panic!("Couldn't find scene '{}'", scene_name));
+    // the recording was made in a built scene, so we'll have to build it as well
+    let scene = loitsu::build_scenes(vec![scene], scripts.clone()).remove(0);
+    let mut input_state = InputState::new();
+    input_state.actions = loitsu::input::actions::ActionMap::new(preferences.input);
+    input_state.start_replay(recording);
+    if profile {
+        PROFILER.lock().unwrap().set_enabled(true);
+    }
+    let mut runner = HeadlessRunner::<RuneInstance>::with_input_state(scripts, scene, input_state)
+        .unwrap_or_else(|e| panic!("Couldn't start scripting: {}", e));
+    runner.scripting.set_budget(preferences.script_budget);
+    let frames = runner.replay();
+    if profile {
+        print!("{}", format_report(&PROFILER.lock().unwrap().report()));
+    }
+    done(&format!("Replayed {} frame(s)", frames));
+}
+
+fn test(filter: Option<String>) {
+    use loitsu::scripting::rune_runtime::{run_tests, TestProject};
+
+    let asset_path = std::env::current_dir().unwrap().join("assets");
+    let (scenes, scripts, preferences) = asset_builder::read_project(&asset_path);
+    let scenes = loitsu::build_scenes(scenes, scripts.clone());
+    let project = TestProject {
+        scripts,
+        scenes,
+        preferences,
+    };
+    let results = run_tests(project, filter.as_deref()).unwrap_or_else(|e| {
+        println!("{} {}", "[ERROR]".bright_red(), e);
+        std::process::exit(1);
+    });
+    let mut failures = 0;
+    for result in &results {
+        let location = match &result.location {
+            Some(location) => format!(" ({})", location),
+            None => String::new(),
+        };
+        match &result.error {
+            None => println!("{} {}{}", "[PASS]".bright_green(), result.name, location),
+            Some(error) => {
+                failures += 1;
+                println!(
+                    "{} {}{}\n{}",
+                    "[FAIL]".bright_red(),
+                    result.name,
+                    location,
+                    error
+                );
+            }
+        }
+    }
+    if failures > 0 {
+        println!(
+            "{} {} of {} test(s) failed",
+            "[FAILED]".bright_red(),
+            failures,
+            results.len()
+        );
+        std::process::exit(1);
+    }
+    done(&format!("{} test(s) passed", results.len()));
+}
+
+fn run_editor(scene: String) {
+    let mut command = std::process::Command::new("loitsu-editor");
+    if scene != "" {
+        command.arg(format!("--scene={}", scene));
+    }
+    // now lets run the command and wait for it to finish
+    let mut child = command.spawn().unwrap();
+    child.wait().unwrap();
+}
+
+fn clean() {
+    let mut path = std::env::current_dir().unwrap();
+    path.push(".loitsu");
+    path.push("asset_cache");
+    if path.exists() {
+        fs::remove_dir_all(path.clone()).unwrap();
+        println!("loitsu cache has been cleared!");
+    } else {
+        println!("loitsu cache is already clean!");
+    }
+}
+
+async fn build(target: &str, release: bool, run: bool, force: bool) {
+    if target == "web" {
+        info!("Building for web");
+        // Now we can build the target
+        build_with_args(
+            vec!["--target=wasm32-unknown-unknown".to_string()],
+            release,
+            false,
+        );
+        let mut out_path = std::env::current_dir().unwrap();
+        out_path.push("target");
+        out_path.push("wasm32-unknown-unknown");
+        if release {
+            out_path.push("release");
+        } else {
+            out_path.push("debug");
+        }
+        // Lets read the name of the app we just built, this can be determined from the Cargo.toml file
+        let mut path = std::env::current_dir().unwrap();
+        path.push("Cargo.toml");
+        let manifest = Manifest::from_path(path.clone()).unwrap();
+        let package_name = manifest.package.unwrap().name;
+
+        let loitsu_version = match &manifest.dependencies["loitsu"] {
+            Dependency::Simple(ver) => ver.to_owned(),
+            Dependency::Inherited(_inherited) => "Unknown (CUSTOM/DEV)".to_string(),
+            Dependency::Detailed(detail) => {
+                let ver = &detail.version;
+                match ver {
+                    Some(version) => version.to_owned(),
+                    None => {
+                        if let Some(path) = &detail.path {
+                            let path = Path::new(path);
+                            let mut path = path.to_path_buf();
+                            path.push("Cargo.toml");
+                            let manifest = Manifest::from_path(path.clone()).unwrap();
+                            let package_version = manifest.package.unwrap().version;
+                            match package_version {
+                                cargo_toml::Inheritable::Set(version) => {
+                                    format!("{}dev", version)
+                                }
+                                cargo_toml::Inheritable::Inherited { .. } => {
+                                    "Unknown (CUSTOM/DEV)".to_string()
+                                }
+                            }
+                        } else {
+                            "Unknown (CUSTOM/DEV)".to_string()
+                        }
+                    }
+                }
+            }
+        };
+        info!("Running wasm-bindgen...");
+        wasm_bindgen(&out_path, package_name.as_str());
+        info!("Creating player...");
+        // Lets copy the web player files
+        generate_player_files(&out_path, &package_name, &loitsu_version, release);
+
+        asset_builder::build_assets(&out_path.join("out"), force, release).await;
+        if run {
+            start_webserver(&out_path).await;
+        } else {
+            done("Build Done!");
+        }
+    } else if target == "" {
+        // Now we can build the target
+        info!("Building for native");
+        let mut out_path = std::env::current_dir().unwrap();
+        out_path.push("target");
+        if release {
+            out_path.push("release");
+        } else {
+            out_path.push("debug");
+        }
+        asset_builder::build_assets(&out_path, force, release).await;
+        info!("Building native target...");
+        build_with_args(vec![], release, run);
+    } else {
+        panic!("Unsupported target: {}", target);
+    }
+}
+
+fn generate_player_files(path: &PathBuf, app_name: &str, loitsu_version: &str, is_release: bool) {
+    // First lets load the player html file located in /player
+    let build_path = path.join("out");
+    let raw_player_html = include_str!("../player/index.html");
+    let build_date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let release_value = if is_release { "true" } else { "false" };
+    let player_html = raw_player_html
+        .replace("{APP_NAME}", &app_name)
+        .replace("{LOITSU_VERSION}", &loitsu_version)
+        .replace("{BUILD_DATE}", &build_date)
+        .replace("{IS_RELEASE}", release_value);
+
+    // Now lets write the html file to the output directory
+    let out_str = build_path.to_str().unwrap();
+    let out_dir = Path::new(&out_str);
+    let dest_path = &out_dir.join("index.html");
+    let mut f = File::create(&dest_path).unwrap();
+    f.write_all(player_html.as_bytes()).unwrap();
+
+    // Finally lets copy the loitsu logo & js to the output directory
+    let logo_bytes = include_bytes!("../player/loitsu.png");
+    let dest_path = Path::new(&out_dir).join("loitsu.png");
+    let loitsu_js = include_str!("../player/loitsu-web.js")
+        .replace("{APP_NAME}", &app_name)
+        .replace("{LOITSU_VERSION}", &loitsu_version)
+        .replace("{BUILD_DATE}", &build_date)
+        .replace("{IS_RELEASE}", release_value);
+    let dest_path_js = Path::new(&out_dir).join("loitsu-web.js");
+
+    fs::write(&dest_path, logo_bytes).expect("Unable to write file");
+    fs::write(&dest_path_js, loitsu_js).expect("Unable to write file");
+}
+
+fn build_with_args(args: Vec<String>, release: bool, run: bool) {
+    // Lets run cargo build with the given args
+    let mut command = std::process::Command::new("cargo");
+    if run {
+        command.arg("run");
+    } else {
+        command.arg("build");
+    }
+    if release {
+        command.arg("--release");
+    }
+    command.args(args);
+    let mut child = command.spawn().expect("failed to build");
+    // lets wait for the build to complete
+    child.wait().unwrap();
+}
+
+async fn start_webserver(path: &PathBuf) {
+    // Let's start a webserver in the given directory
+    let out_path = path.join("out");
+    let directory = out_path.to_str().unwrap();
+    // Add middleware to set Cache-Control header
+    let add_no_cache_header = warp::any().map(|| {
+        warp::reply::with_header(
+            warp::reply(),
+            "Cache-Control",
+            "no-store, no-cache, must-revalidate, proxy-revalidate",
+        )
+    });
+
+    let route = warp::get()
+        .and(warp::fs::dir(str_static(directory.to_string())))
+        .and(add_no_cache_header)
+        .map(|reply, _| reply);
+
+    done("Build done! Project live at http://localhost:5959");
+    warp::serve(route).run(([127, 0, 0, 1], 5959)).await;
+}
+
+fn wasm_bindgen(path: &PathBuf, bin_name: &str) {
+    let directory = Path::new(&path);
+    let bin_path = directory.join(bin_name);
+    let out_path = directory.join("out");
+    let mut command = std::process::Command::new("wasm-bindgen");
+    command.arg(format!("{}.wasm", bin_path.to_str().unwrap()));
+    command.args(vec![
+        "--target",
+        "web",
+        "--out-dir",
+        out_path.to_str().unwrap(),
+    ]);
+    let mut child = command.spawn().expect("failed to build");
+    // lets wait for the build to complete
+    child.wait().unwrap();
+}
+
+fn str_static(s: String) -> &'static str {
+    s.leak()
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($t:tt)*) => ($crate::info(&format_args!($($t)*).to_string()))
+}
+
+pub fn info(msg: &str) {
+    println!("{} {}", "[INFO]".bright_blue(), msg);
+}
+
+pub fn done(msg: &str) {
+    println!("{} {}", "[DONE]".bright_green(), msg);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_fmt() {
+        let scene = r#"{"name": "main", "required_assets": [], "id_space": 3, "entities": [
+            {"transform": {"static": false, "scale": [1.0, 1.0],
+                "rotation": 0.10000000149011612, "position": [0.5, -2]},
+             "name": "Player", "id": "EID0", "children": [],
+             "components": [{"properties": {"speed": 1.5, "health": null, "cooldown": 0.2},
+                 "name": "Player", "id": "CID1"}]}
+        ]}"#;
+        let path = std::env::temp_dir().join(format!("fmt-{}.scene.json", std::process::id()));
+        fs::write(&path, scene).unwrap();
+        scene_fmt(vec![path.clone()], false);
+        let formatted = fs::read_to_string(&path).unwrap();
+        scene_fmt(vec![path.clone()], false);
+        let reformatted = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(formatted, reformatted);
+
+        // the keys of every object are sorted
+        let keys: Vec<&str> = formatted
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"')?.split_once("\":"))
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "entities",
+                "children",
+                "components",
+                "id",
+                "name",
+                "properties",
+                "cooldown",
+                "health",
+                "speed",
+                "id",
+                "name",
+                "transform",
+                "position",
+                "rotation",
+                "scale",
+                "static",
+                "id_space",
+                "name",
+                "required_assets",
+            ]
+        );
+        assert!(formatted.contains("\"cooldown\": 0.2,"));
+        assert!(formatted.contains("\"rotation\": 0.1,"));
+        // NaN is kept
+        assert!(formatted.contains("\"health\": null,"));
+    }
+}
@@ -0,0 +1,764 @@
+use colored::*;
+use loitsu::ecs::Transform;
+use loitsu::scene_management::{Component, Entity, Property, Scene};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// A flattened view of an entity, keyed by its ID in a `FlatScene`
+#[derive(Clone)]
+struct FlatEntity {
+    parent: Option<u32>,
+    index: usize,
+    name: String,
+    transform: Value,
+    components: BTreeMap<u32, FlatComponent>,
+}
+
+#[derive(Clone)]
+struct FlatComponent {
+    index: usize,
+    name: String,
+    properties: BTreeMap<String, Property>,
+}
+
+// the sibling index isn't compared, as it changes whenever a sibling is added or removed
+impl PartialEq for FlatEntity {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent
+            && self.name == other.name
+            && self.transform == other.transform
+            && self.components == other.components
+    }
+}
+
+impl PartialEq for FlatComponent {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.properties == other.properties
+    }
+}
+
+type FlatScene = BTreeMap<u32, FlatEntity>;
+
+fn flatten(entities: &[Entity], parent: Option<u32>, out: &mut FlatScene) {
+    for (index, entity) in entities.iter().enumerate() {
+        let components = entity
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| {
+                (
+                    component.id,
+                    FlatComponent {
+                        index,
+                        name: component.name.clone(),
+                        properties: component.properties.clone(),
+                    },
+                )
+            })
+            .collect();
+        out.insert(
+            entity.id,
+            FlatEntity {
+                parent,
+                index,
+                name: entity.name.clone(),
+                transform: entity.transform.clone().to_json(),
+                components,
+            },
+        );
+        flatten(&entity.children, Some(entity.id), out);
+    }
+}
+
+fn flatten_scene(scene: &Scene) -> FlatScene {
+    let mut out = BTreeMap::new();
+    flatten(&scene.entities, None, &mut out);
+    out
+}
+
+pub enum Change {
+    EntityAdded(u32, String),
+    EntityRemoved(u32, String),
+    EntityMoved {
+        id: u32,
+        name: String,
+        from: Option<u32>,
+        to: Option<u32>,
+    },
+    EntityRenamed {
+        id: u32,
+        from: String,
+        to: String,
+    },
+    TransformChanged {
+        id: u32,
+        name: String,
+        from: Value,
+        to: Value,
+    },
+    ComponentAdded(u32, u32, String),
+    ComponentRemoved(u32, u32, String),
+    PropertyChanged {
+        entity: u32,
+        component: String,
+        property: String,
+        from: Option<Property>,
+        to: Option<Property>,
+    },
+}
+
+fn parent_name(parent: &Option<u32>) -> String {
+    match parent {
+        Some(id) => format!("EID{}", id),
+        None => "scene root".to_string(),
+    }
+}
+
+fn property_name(property: &Option<Property>) -> String {
+    match property {
+        Some(property) => format!("{:?}", property),
+        None => "(unset)".to_string(),
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::EntityAdded(id, name) => {
+                write!(f, "{} entity '{}' (EID{})", "+".green(), name, id)
+            }
+            Change::EntityRemoved(id, name) => {
+                write!(f, "{} entity '{}' (EID{})", "-".red(), name, id)
+            }
+            Change::EntityMoved { id, name, from, to } => write!(
+                f,
+                "{} entity '{}' (EID{}) moved from {} to {}",
+                "~".yellow(),
+                name,
+                id,
+                parent_name(from),
+                parent_name(to)
+            ),
+            Change::EntityRenamed { id, from, to } => write!(
+                f,
+                "{} entity '{}' (EID{}) renamed to '{}'",
+                "~".yellow(),
+                from,
+                id,
+                to
+            ),
+            Change::TransformChanged { id, name, from, to } => write!(
+                f,
+                "{} entity '{}' (EID{}) transform: {} -> {}",
+                "~".yellow(),
+                name,
+                id,
+                from,
+                to
+            ),
+            Change::ComponentAdded(entity, id, name) => write!(
+                f,
+                "{} component '{}' (CID{}) on EID{}",
+                "+".green(),
+                name,
+                id,
+                entity
+            ),
+            Change::ComponentRemoved(entity, id, name) => write!(
+                f,
+                "{} component '{}' (CID{}) on EID{}",
+                "-".red(),
+                name,
+                id,
+                entity
+            ),
+            Change::PropertyChanged {
+                entity,
+                component,
+                property,
+                from,
+                to,
+            } => write!(
+                f,
+                "{} EID{} {}.{}: {} -> {}",
+                "~".yellow(),
+                entity,
+                component,
+                property,
+                property_name(from),
+                property_name(to)
+            ),
+        }
+    }
+}
+
+/// Compares two scenes by entity and component ID
+pub fn diff(a: &Scene, b: &Scene) -> Vec<Change> {
+    let a = flatten_scene(a);
+    let b = flatten_scene(b);
+    let mut changes = Vec::new();
+    for (id, entity) in &a {
+        if !b.contains_key(id) {
+            changes.push(Change::EntityRemoved(*id, entity.name.clone()));
+        }
+    }
+    for (id, new) in &b {
+        let old = match a.get(id) {
+            Some(old) => old,
+            None => {
+                changes.push(Change::EntityAdded(*id, new.name.clone()));
+                continue;
+            }
+        };
+        if old.name != new.name {
+            changes.push(Change::EntityRenamed {
+                id: *id,
+                from: old.name.clone(),
+                to: new.name.clone(),
+            });
+        }
+        if old.parent != new.parent {
+            changes.push(Change::EntityMoved {
+                id: *id,
+                name: new.name.clone(),
+                from: old.parent,
+                to: new.parent,
+            });
+        }
+        if old.transform != new.transform {
+            changes.push(Change::TransformChanged {
+                id: *id,
+                name: new.name.clone(),
+                from: old.transform.clone(),
+                to: new.transform.clone(),
+            });
+        }
+        for (cid, component) in &old.components {
+            if !new.components.contains_key(cid) {
+                changes.push(Change::ComponentRemoved(*id, *cid, component.name.clone()));
+            }
+        }
+        for (cid, component) in &new.components {
+            let old_component = match old.components.get(cid) {
+                Some(old_component) => old_component,
+                None => {
+                    changes.push(Change::ComponentAdded(*id, *cid, component.name.clone()));
+                    continue;
+                }
+            };
+            let keys: BTreeSet<&String> = old_component
+                .properties
+                .keys()
+                .chain(component.properties.keys())
+                .collect();
+            for key in keys {
+                let from = old_component.properties.get(key);
+                let to = component.properties.get(key);
+                if from != to {
+                    changes.push(Change::PropertyChanged {
+                        entity: *id,
+                        component: component.name.clone(),
+                        property: key.clone(),
+                        from: from.cloned(),
+                        to: to.cloned(),
+                    });
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// A field that was changed differently on both sides of a merge. The merged scene keeps our
+/// version of the field.
+pub struct Conflict {
+    pub path: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", "!".red(), self.path)
+    }
+}
+
+/// Three way merge of a single value. Returns our value on conflict
+fn merge_value<T: PartialEq + Clone>(
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    path: impl FnOnce() -> String,
+    conflicts: &mut Vec<Conflict>,
+) -> T {
+    if ours == theirs || theirs == base {
+        ours.clone()
+    } else if ours == base {
+        theirs.clone()
+    } else {
+        conflicts.push(Conflict { path: path() });
+        ours.clone()
+    }
+}
+
+fn merge_component(
+    entity: u32,
+    cid: u32,
+    base: &FlatComponent,
+    ours: &FlatComponent,
+    theirs: &FlatComponent,
+    conflicts: &mut Vec<Conflict>,
+) -> FlatComponent {
+    let mut merged = ours.clone();
+    let keys: BTreeSet<&String> = base
+        .properties
+        .keys()
+        .chain(ours.properties.keys())
+        .chain(theirs.properties.keys())
+        .collect();
+    for key in keys {
+        let value = merge_value(
+            &base.properties.get(key).cloned(),
+            &ours.properties.get(key).cloned(),
+            &theirs.properties.get(key).cloned(),
+            || format!("EID{} CID{} ({}).{}", entity, cid, ours.name, key),
+            conflicts,
+        );
+        match value {
+            Some(value) => merged.properties.insert(key.clone(), value),
+            None => merged.properties.remove(key),
+        };
+    }
+    merged
+}
+
+fn merge_entity(
+    id: u32,
+    base: &FlatEntity,
+    ours: &FlatEntity,
+    theirs: &FlatEntity,
+    conflicts: &mut Vec<Conflict>,
+) -> FlatEntity {
+    let name = merge_value(
+        &base.name,
+        &ours.name,
+        &theirs.name,
+        || format!("EID{} name", id),
+        conflicts,
+    );
+    let parent = merge_value(
+        &base.parent,
+        &ours.parent,
+        &theirs.parent,
+        || format!("EID{} ({}) parent", id, name),
+        conflicts,
+    );
+    // the index only orders siblings, it's taken from the side that moved the entity
+    let index = if parent != ours.parent || (parent == theirs.parent && ours.index == base.index) {
+        theirs.index
+    } else {
+        ours.index
+    };
+    let transform = merge_value(
+        &base.transform,
+        &ours.transform,
+        &theirs.transform,
+        || format!("EID{} ({}) transform", id, name),
+        conflicts,
+    );
+    let mut components = BTreeMap::new();
+    let cids: BTreeSet<&u32> = base
+        .components
+        .keys()
+        .chain(ours.components.keys())
+        .chain(theirs.components.keys())
+        .collect();
+    for cid in cids {
+        let component = match (
+            base.components.get(cid),
+            ours.components.get(cid),
+            theirs.components.get(cid),
+        ) {
+            (Some(b), Some(o), Some(t)) => Some(merge_component(id, *cid, b, o, t, conflicts)),
+            (b, o, t) => merge_value(
+                &b.cloned(),
+                &o.cloned(),
+                &t.cloned(),
+                || format!("EID{} CID{} added or removed on both sides", id, cid),
+                conflicts,
+            ),
+        };
+        if let Some(component) = component {
+            components.insert(*cid, component);
+        }
+    }
+    FlatEntity {
+        parent,
+        index,
+        name,
+        transform,
+        components,
+    }
+}
+
+/// Gives the entities and components added on their side whose IDs are also used by something
+/// else on our side a fresh ID, so that both additions are kept. References to them and the
+/// parents of their children are updated to match.
+fn renumber_clashes(base: &FlatScene, ours: &FlatScene, theirs: &FlatScene) -> FlatScene {
+    let ids = |scene: &FlatScene| -> BTreeSet<u32> {
+        scene
+            .iter()
+            .flat_map(|(id, entity)| std::iter::once(*id).chain(entity.components.keys().copied()))
+            .collect()
+    };
+    let base_ids = ids(base);
+    let ours_ids = ids(ours);
+    let mut next_id = base_ids
+        .iter()
+        .chain(ours_ids.iter())
+        .chain(ids(theirs).iter())
+        .max()
+        .map_or(0, |id| id + 1);
+    let mut fresh_id = |id_map: &mut HashMap<u32, u32>, id: u32| {
+        id_map.insert(id, next_id);
+        next_id += 1;
+    };
+
+    let mut id_map = HashMap::new();
+    for (id, entity) in theirs {
+        let ours_entity = ours.get(id);
+        // the same entity added on both sides is merged as one
+        if !base_ids.contains(id) && ours_entity != Some(entity) && ours_ids.contains(id) {
+            fresh_id(&mut id_map, *id);
+        }
+        for (cid, component) in &entity.components {
+            let same = ours_entity.and_then(|e| e.components.get(cid)) == Some(component);
+            if !base_ids.contains(cid) && !same && ours_ids.contains(cid) {
+                fresh_id(&mut id_map, *cid);
+            }
+        }
+    }
+
+    let remap = |id: u32| *id_map.get(&id).unwrap_or(&id);
+    theirs
+        .iter()
+        .map(|(id, entity)| {
+            let mut entity = entity.clone();
+            entity.parent = entity.parent.map(remap);
+            entity.components = entity
+                .components
+                .into_iter()
+                .map(|(cid, mut component)| {
+                    for property in component.properties.values_mut() {
+                        property.remap_references(&id_map);
+                    }
+                    (remap(cid), component)
+                })
+                .collect();
+            (remap(*id), entity)
+        })
+        .collect()
+}
+
+/// Merges the edits made in `ours` and `theirs` on top of their common ancestor `base`.
+/// Conflicting fields keep our value and are returned alongside the merged scene.
+pub fn merge(base: &Scene, ours: &Scene, theirs: &Scene) -> (Scene, Vec<Conflict>) {
+    let base_flat = flatten_scene(base);
+    let ours_flat = flatten_scene(ours);
+    let theirs_flat = renumber_clashes(&base_flat, &ours_flat, &flatten_scene(theirs));
+    let mut conflicts = Vec::new();
+    let mut merged = FlatScene::new();
+
+    let ids: BTreeSet<&u32> = base_flat
+        .keys()
+        .chain(ours_flat.keys())
+        .chain(theirs_flat.keys())
+        .collect();
+    for id in ids {
+        match (base_flat.get(id), ours_flat.get(id), theirs_flat.get(id)) {
+            (Some(b), Some(o), Some(t)) => {
+                merged.insert(*id, merge_entity(*id, b, o, t, &mut conflicts));
+            }
+            (Some(b), o, t) => {
+                // removed on at least one side
+                let entity = merge_value(
+                    &Some(b.clone()),
+                    &o.cloned(),
+                    &t.cloned(),
+                    || format!("EID{} ({}) removed on one side, edited on the other", id, b.name),
+                    &mut conflicts,
+                );
+                if let Some(entity) = entity {
+                    merged.insert(*id, entity);
+                }
+            }
+            (None, o, t) => {
+                // added on one side, or the same entity added on both
+                if let Some(entity) = o.or(t) {
+                    merged.insert(*id, entity.clone());
+                }
+            }
+        }
+    }
+    // entities whose parent was removed are moved to the scene root
+    let orphans: Vec<u32> = merged
+        .iter()
+        .filter(|(_, entity)| matches!(entity.parent, Some(parent) if !merged.contains_key(&parent)))
+        .map(|(id, _)| *id)
+        .collect();
+    for id in orphans {
+        let entity = merged.get_mut(&id).unwrap();
+        conflicts.push(Conflict {
+            path: format!(
+                "EID{} ({}) lost its parent EID{}, moved to the scene root",
+                id,
+                entity.name,
+                entity.parent.unwrap()
+            ),
+        });
+        entity.parent = None;
+    }
+
+    let mut scene = Scene::new(ours.name.clone());
+    scene.entities = unflatten(&merged, None);
+    // IDs of entities removed on either side must not be handed out again
+    scene.id_space = base.id_space.max(ours.id_space).max(theirs.id_space);
+    // assets are kept unless one side removed them, ours first
+    scene.required_assets = ours
+        .required_assets
+        .iter()
+        .chain(&theirs.required_assets)
+        .filter(|asset| {
+            !base.required_assets.contains(asset)
+                || (ours.required_assets.contains(asset) && theirs.required_assets.contains(asset))
+        })
+        .fold(Vec::new(), |mut assets, asset| {
+            if !assets.contains(asset) {
+                assets.push(asset.clone());
+            }
+            assets
+        });
+    (scene, conflicts)
+}
+
+fn unflatten(scene: &FlatScene, parent: Option<u32>) -> Vec<Entity> {
+    let mut children: Vec<(&u32, &FlatEntity)> = scene
+        .iter()
+        .filter(|(_, entity)| entity.parent == parent)
+        .collect();
+    children.sort_by_key(|(id, entity)| (entity.index, **id));
+    children
+        .into_iter()
+        .map(|(id, flat)| {
+            let mut entity = Entity::new(flat.name.clone(), *id);
+            entity.transform = Transform::from_json(flat.transform.as_object().unwrap());
+            let mut components: Vec<(&u32, &FlatComponent)> = flat.components.iter().collect();
+            components.sort_by_key(|(cid, component)| (component.index, **cid));
+            for (cid, flat_component) in components {
+                let mut component = Component::new(flat_component.name.clone(), *cid);
+                for (key, value) in &flat_component.properties {
+                    component.add_property(key.clone(), value.clone());
+                }
+                entity.add_component(component);
+            }
+            entity.children = unflatten(scene, Some(*id));
+            entity
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(entities: Vec<Entity>) -> Scene {
+        let mut scene = Scene::new("test".to_string());
+        scene.entities = entities;
+        scene
+    }
+
+    fn entity(name: &str, id: u32, components: Vec<Component>) -> Entity {
+        let mut entity = Entity::new(name.to_string(), id);
+        entity.components = components;
+        entity
+    }
+
+    fn component(id: u32, properties: &[(&str, f32)]) -> Component {
+        let mut component = Component::new("Health".to_string(), id);
+        for (key, value) in properties {
+            component.add_property(key.to_string(), Property::Number(*value));
+        }
+        component
+    }
+
+    fn property(scene: &Scene, entity: usize, key: &str) -> Option<Property> {
+        scene.entities[entity].components[0]
+            .properties
+            .get(key)
+            .cloned()
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = scene(vec![
+            entity("Player", 0, vec![component(1, &[("hp", 10.0)])]),
+            entity("Enemy", 2, vec![]),
+        ]);
+        let b = scene(vec![
+            entity("Player", 0, vec![component(1, &[("hp", 5.0)])]),
+            entity("Tree", 3, vec![]),
+        ]);
+        let changes = diff(&a, &b);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], Change::EntityRemoved(2, _)));
+        assert!(matches!(
+            changes[1],
+            Change::PropertyChanged {
+                entity: 0,
+                from: Some(Property::Number(10.0)),
+                to: Some(Property::Number(5.0)),
+                ..
+            }
+        ));
+        assert!(matches!(changes[2], Change::EntityAdded(3, _)));
+    }
+
+    #[test]
+    fn test_merge_non_conflicting() {
+        let base = scene(vec![entity(
+            "Player",
+            0,
+            vec![component(1, &[("hp", 10.0), ("speed", 1.0)])],
+        )]);
+        let ours = scene(vec![entity(
+            "Player",
+            0,
+            vec![component(1, &[("hp", 20.0), ("speed", 1.0)])],
+        )]);
+        let theirs = scene(vec![entity(
+            "Player",
+            0,
+            vec![component(1, &[("hp", 10.0), ("speed", 2.0)])],
+        )]);
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(property(&merged, 0, "hp"), Some(Property::Number(20.0)));
+        assert_eq!(property(&merged, 0, "speed"), Some(Property::Number(2.0)));
+    }
+
+    #[test]
+    fn test_merge_conflict_keeps_ours() {
+        let base = scene(vec![entity("Player", 0, vec![component(1, &[("hp", 10.0)])])]);
+        let ours = scene(vec![entity("Player", 0, vec![component(1, &[("hp", 20.0)])])]);
+        let theirs = scene(vec![entity("Player", 0, vec![component(1, &[("hp", 30.0)])])]);
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(property(&merged, 0, "hp"), Some(Property::Number(20.0)));
+    }
+
+    #[test]
+    fn test_merge_keeps_id_space_and_assets() {
+        let mut base = scene(vec![entity("A", 0, vec![]), entity("B", 1, vec![])]);
+        base.id_space = 2;
+        base.required_assets = vec!["a.png".to_string(), "b.png".to_string()];
+        // ours removes the entity with the highest ID and one of the assets
+        let mut ours = scene(vec![entity("A", 0, vec![])]);
+        ours.id_space = 2;
+        ours.required_assets = vec!["a.png".to_string()];
+        let mut theirs = scene(vec![entity("A", 0, vec![]), entity("B", 1, vec![])]);
+        theirs.id_space = 2;
+        theirs.required_assets = vec![
+            "a.png".to_string(),
+            "b.png".to_string(),
+            "c.png".to_string(),
+        ];
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.entities.len(), 1);
+        assert_eq!(merged.id_space, 2);
+        assert_eq!(merged.required_assets, vec!["a.png", "c.png"]);
+    }
+
+    #[test]
+    fn test_merge_both_added_same_id() {
+        let base = scene(vec![entity("Player", 0, vec![])]);
+        let ours = scene(vec![entity("Player", 0, vec![]), entity("Tree", 1, vec![])]);
+        let theirs = scene(vec![entity("Player", 0, vec![]), entity("Rock", 1, vec![])]);
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        let names: Vec<&str> = merged.entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Player", "Tree", "Rock"]);
+        assert_ne!(merged.entities[1].id, merged.entities[2].id);
+    }
+
+    #[test]
+    fn test_merge_sibling_added_and_removed() {
+        let base = scene(vec![
+            entity("A", 0, vec![]),
+            entity("B", 1, vec![]),
+            entity("C", 2, vec![]),
+        ]);
+        let ours = scene(vec![
+            entity("D", 3, vec![]),
+            entity("A", 0, vec![]),
+            entity("B", 1, vec![]),
+            entity("C", 2, vec![]),
+        ]);
+        let theirs = scene(vec![entity("A", 0, vec![]), entity("C", 2, vec![])]);
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        let names: Vec<&str> = merged.entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["D", "A", "C"]);
+    }
+
+    #[test]
+    fn test_merge_component_id_clash() {
+        let base = scene(vec![entity("Player", 0, vec![])]);
+        let ours = scene(vec![
+            entity("Player", 0, vec![]),
+            entity("Tree", 1, vec![component(2, &[("hp", 1.0)])]),
+        ]);
+        let mut rock = component(2, &[("hp", 2.0)]);
+        rock.add_property("self".to_string(), Property::ComponentReference(2));
+        let mut watcher = component(4, &[]);
+        watcher.add_property("target".to_string(), Property::EntityReference(3));
+        let theirs = scene(vec![
+            entity("Player", 0, vec![watcher]),
+            entity("Rock", 3, vec![rock]),
+        ]);
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        let tree = &merged.entities[1];
+        let rock = &merged.entities[2];
+        assert_eq!((tree.name.as_str(), rock.name.as_str()), ("Tree", "Rock"));
+        assert_eq!(tree.components[0].id, 2);
+        let rock_cid = rock.components[0].id;
+        assert_ne!(rock_cid, 2);
+        assert_eq!(
+            rock.components[0].properties.get("self"),
+            Some(&Property::ComponentReference(rock_cid))
+        );
+        assert_eq!(
+            property(&merged, 0, "target"),
+            Some(Property::EntityReference(rock.id))
+        );
+    }
+
+    #[test]
+    fn test_ids_stable_on_save() {
+        let mut before = scene(vec![
+            entity("A", 0, vec![component(1, &[])]),
+            entity("B", 2, vec![component(3, &[])]),
+            entity("C", 4, vec![component(5, &[])]),
+        ]);
+        before.id_space = 6;
+        let mut after = before.clone();
+        after.entities.remove(1);
+        let after = Scene::from_json("test".to_string(), after.to_json());
+        let ids: Vec<u32> = after.entities.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![0, 4]);
+        assert_eq!(after.id_space, 6);
+        let changes = diff(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], Change::EntityRemoved(2, _)));
+    }
+}
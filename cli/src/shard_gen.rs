@@ -1,6 +1,6 @@
 use loitsu::asset_management::static_shard::StaticShard;
 use loitsu::scene_management::Scene;
-use loitsu::scripting::ScriptingSource;
+use loitsu::scripting::{ScriptingSource, ValidatedScripts};
 use loitsu::Preferences;
 use loitsu_asset_gen::resolve_asset;
 use std::collections::hash_map::DefaultHasher;
@@ -128,6 +128,7 @@ impl Shard {
 pub fn generate_shards(
     scenes: Vec<Scene>,
     scripts: Vec<ScriptingSource>,
+    validated_scripts: ValidatedScripts,
     preferences: &Preferences,
 ) -> (Vec<Shard>, StaticShard) {
     let mut initial_shards = Vec::new();
@@ -177,7 +178,13 @@ pub fn generate_shards(
         scene.required_assets = Vec::new();
         scene.shards = map.get(&scene.name).unwrap_or(&Vec::new()).clone();
     }
-    let static_shard = StaticShard::new(map, scripts, scenes, preferences.clone());
+    let static_shard = StaticShard::new(
+        map,
+        scripts,
+        scenes,
+        preferences.clone(),
+        Some(validated_scripts),
+    );
 
     (shards, static_shard)
 }
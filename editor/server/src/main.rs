@@ -54,7 +54,10 @@ async fn main() {
             let mut scripts = Vec::new();
             for file in files {
                 if file.name.ends_with(".rn") {
-                    scripts.push(String::from_utf8(file.data.clone()).unwrap());
+                    scripts.push((
+                        file.name.clone(),
+                        String::from_utf8(file.data.clone()).unwrap(),
+                    ));
                 }
             }
             Response::builder()
@@ -63,6 +63,8 @@ if (window.set_status === undefined) {
     // @ts-ignore
     window.add_error = (message) => {};
     // @ts-ignore
+    window.set_profiler_overlay = (text) => {};
+    // @ts-ignore
     window.request_select_entity = request_select_entity;
     // @ts-ignore
     window.set_component_property = set_component_property;
@@ -0,0 +1,50 @@
+//! Batches sprites on the CPU and reports how many draw calls each camera would make for them.
+//! Before batching every sprite was its own draw call. Run with `cargo bench -p loitsu`.
+//!
+//! Only the grouping done by `SpriteBatches` is measured. No GPU device is created, so the time
+//! spent uploading instances and drawing, and the draw calls of the real render path, aren't part
+//! of the results. The draw call counts are the batches the render path would submit per camera.
+use loitsu::rendering::drawable::batch::{SpriteBatches, SpriteInstance};
+use std::time::Instant;
+
+const FRAMES: u32 = 200;
+
+fn instance(i: usize) -> SpriteInstance {
+    SpriteInstance {
+        transform: [
+            [1.0, 0.0, 0.0, i as f32],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        uv: [0.0, 0.0, 1.0, 1.0],
+        color: [1.0; 4],
+    }
+}
+
+fn run(name: &str, sprites: usize, texture: impl Fn(usize) -> usize) {
+    let mut batches = SpriteBatches::new();
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        batches.clear();
+        for i in 0..sprites {
+            batches.push(texture(i), instance(i));
+        }
+    }
+    let per_frame = start.elapsed() / FRAMES;
+    println!(
+        "{:<40} {:>6} sprites {:>6} draw calls {:>10.1?} per frame",
+        name,
+        sprites,
+        batches.draw_calls(),
+        per_frame
+    );
+}
+
+fn main() {
+    for sprites in [1_000, 10_000] {
+        run("one texture", sprites, |_| 0);
+        run("4 textures, grouped", sprites, |i| i * 4 / sprites);
+        run("4 textures, interleaved (worst case)", sprites, |i| i % 4);
+    }
+}
@@ -0,0 +1,39 @@
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    // compiled script units can only be loaded by the rune version that serialized them, so the
+    // resolved version is baked into the engine
+    let version = find_lockfile()
+        .and_then(|path| {
+            println!("cargo:rerun-if-changed={}", path.display());
+            std::fs::read_to_string(path).ok()
+        })
+        .and_then(|lockfile| locked_version(&lockfile, "rune"))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LOITSU_RUNE_VERSION={}", version);
+}
+
+/// Looks for the lockfile of the workspace that is being built. The out dir is inside its target
+/// directory, which is usually next to the lockfile.
+fn find_lockfile() -> Option<PathBuf> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").ok()?);
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").ok()?);
+    out_dir
+        .ancestors()
+        .chain(manifest_dir.ancestors())
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists())
+}
+
+fn locked_version(lockfile: &str, package: &str) -> Option<String> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lockfile.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name {
+            let version = lines.next()?.trim().strip_prefix("version = ")?;
+            return Some(version.trim_matches('"').to_string());
+        }
+    }
+    None
+}
@@ -3,7 +3,7 @@ use std::io::Read;
 use std::io::Write;
 
 use crate::scene_management::Scene;
-use crate::scripting::ScriptingSource;
+use crate::scripting::{ScriptingSource, ValidatedScripts};
 use crate::Preferences;
 
 #[derive(Clone, bitcode::Encode, bitcode::Decode)]
@@ -12,6 +12,7 @@ pub struct StaticShard {
     scripts: Vec<ScriptingSource>,
     scenes: Vec<Scene>,
     preferences: Preferences,
+    validated_scripts: Option<ValidatedScripts>,
 }
 
 #[derive(Clone, bitcode::Encode, bitcode::Decode)]
@@ -26,12 +27,14 @@ impl StaticShard {
         scripts: Vec<ScriptingSource>,
         scenes: Vec<Scene>,
         preferences: Preferences,
+        validated_scripts: Option<ValidatedScripts>,
     ) -> StaticShard {
         StaticShard {
             shard_map,
             scripts,
             scenes,
             preferences,
+            validated_scripts,
         }
     }
 
@@ -68,4 +71,9 @@ impl StaticShard {
     pub fn get_scripts(&self) -> &Vec<ScriptingSource> {
         &self.scripts
     }
+
+    /// The scripts as compiled by `loitsu build`
+    pub fn get_validated_scripts(&self) -> Option<&ValidatedScripts> {
+        self.validated_scripts.as_ref()
+    }
 }
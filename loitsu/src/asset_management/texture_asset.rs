@@ -142,6 +142,11 @@ impl TextureMeta {
         return texture.get_version();
     }
 
+    /// The name of the texture asset the sprite is in
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+
     pub fn get_uv(&self) -> (f32, f32, f32, f32) {
         self.uv
     }
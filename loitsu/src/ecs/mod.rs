@@ -4,10 +4,13 @@ use std::collections::HashMap;
 use crate::asset_management::ASSET_MANAGER;
 
 use crate::scene_management::{Component, Entity, Property, Scene};
-use crate::scripting::{EntityUpdate, ScriptingData, ScriptingInstance};
+use crate::scripting::{EntityUpdate, ScriptingData, ScriptingInstance, ScriptingSource};
+use crate::error;
 use bitflags::bitflags;
 #[cfg(feature = "scene_generation")]
-use serde_json::{Map, Number, Value};
+use crate::scene_management::{float_to_json, json_to_float};
+#[cfg(feature = "scene_generation")]
+use serde_json::{Map, Value};
 use std::sync::{Arc, Mutex};
 
 pub struct ECS<T>
@@ -18,6 +21,8 @@ where
     pub static_scene: Option<Scene>,
     runtime_entities: Vec<Arc<Mutex<RuntimeEntity<T>>>>,
     entity_lookup: HashMap<u32, Arc<Mutex<RuntimeEntity<T>>>>,
+    // where the IDs of the active scene were moved to by `Scene::reserve_ids`
+    id_space_begin: u32,
     #[cfg(feature = "editor")]
     event_handler: Arc<Mutex<crate::editor::EventHandler<T>>>,
 }
@@ -115,19 +120,19 @@ impl Transform {
                 map.insert(
                     "position".to_string(),
                     Value::Array(vec![
-                        Value::Number(Number::from_f64(position.0 as f64).unwrap()),
-                        Value::Number(Number::from_f64(position.1 as f64).unwrap()),
+                        float_to_json(position.0),
+                        float_to_json(position.1),
                     ]),
                 );
                 map.insert(
                     "rotation".to_string(),
-                    Value::Number(Number::from_f64(rotation as f64).unwrap()),
+                    float_to_json(rotation),
                 );
                 map.insert(
                     "scale".to_string(),
                     Value::Array(vec![
-                        Value::Number(Number::from_f64(scale.0 as f64).unwrap()),
-                        Value::Number(Number::from_f64(scale.1 as f64).unwrap()),
+                        float_to_json(scale.0),
+                        float_to_json(scale.1),
                     ]),
                 );
                 map.insert("static".to_string(), Value::Bool(r#static));
@@ -138,8 +143,8 @@ impl Transform {
                 map.insert(
                     "position".to_string(),
                     Value::Array(vec![
-                        Value::Number(Number::from_f64(position.0 as f64).unwrap()),
-                        Value::Number(Number::from_f64(position.1 as f64).unwrap()),
+                        float_to_json(position.0),
+                        float_to_json(position.1),
                     ]),
                 );
                 Value::Object(map)
@@ -149,16 +154,10 @@ impl Transform {
     #[cfg(feature = "scene_generation")]
     pub fn from_json(json: &Map<String, Value>) -> Transform {
         let position = json["position"].as_array().unwrap();
-        let position = (
-            position[0].as_f64().unwrap() as f32,
-            position[1].as_f64().unwrap() as f32,
-        );
-        let rotation = json["rotation"].as_f64().unwrap() as f32;
+        let position = (json_to_float(&position[0]), json_to_float(&position[1]));
+        let rotation = json_to_float(&json["rotation"]);
         let scale = json["scale"].as_array().unwrap();
-        let scale = (
-            scale[0].as_f64().unwrap() as f32,
-            scale[1].as_f64().unwrap() as f32,
-        );
+        let scale = (json_to_float(&scale[0]), json_to_float(&scale[1]));
         let r#static = json["static"].as_bool().unwrap();
         Transform::Transform2D {
             position,
@@ -317,6 +316,7 @@ impl<T: ScriptingInstance> ECS<T> {
             static_scene: None,
             runtime_entities: Vec::new(),
             entity_lookup: HashMap::new(),
+            id_space_begin: 0,
         }
     }
 
@@ -327,13 +327,14 @@ impl<T: ScriptingInstance> ECS<T> {
             static_scene: None,
             runtime_entities: Vec::new(),
             entity_lookup: HashMap::new(),
+            id_space_begin: 0,
             event_handler,
         }
     }
 
     pub fn load_scene(&mut self, scene: Scene, scripting: &mut T) {
         let mut scene = scene.clone();
-        scene.reserve_ids();
+        self.id_space_begin = scene.reserve_ids();
         self.active_scene = scene;
 
         (self.runtime_entities, self.entity_lookup) =
@@ -390,6 +391,32 @@ impl<T: ScriptingInstance> ECS<T> {
         )
     }
 
+    /// Recompiles the scripts and rebinds the data of every component to the new scripts. If the
+    /// scripts fail to compile nothing is changed.
+    pub fn reload_scripts(
+        &self,
+        scripting: &mut T,
+        sources: Vec<ScriptingSource>,
+    ) -> crate::scripting::Result<()> {
+        scripting.reload(sources)?;
+        for entity in self.get_all_runtime_entities_flat() {
+            let mut entity = entity.lock().unwrap();
+            let mut component_flags = ComponentFlags::EMPTY;
+            for component in entity.components.iter_mut() {
+                component.flags = scripting.get_component_flags(&component.component_proto.name);
+                component_flags |= component.flags;
+                if let Err(e) = component.data.rebind(&component.component_proto, scripting) {
+                    error!(
+                        "Couldn't reload component {}: {}",
+                        component.component_proto.name, e
+                    );
+                }
+            }
+            entity.component_flags = component_flags;
+        }
+        Ok(())
+    }
+
     pub fn get_runtime_entities(&self) -> Vec<Arc<Mutex<RuntimeEntity<T>>>> {
         self.runtime_entities.clone()
     }
@@ -398,10 +425,12 @@ impl<T: ScriptingInstance> ECS<T> {
         self.active_scene = Scene::new("INITIAL_SCENE".to_string());
         self.static_scene = None;
         self.runtime_entities = Vec::new();
+        self.id_space_begin = 0;
     }
 
+    /// Returns the active scene as it currently is, with the IDs it was loaded with
     pub fn as_scene(&self) -> Scene {
-        Scene {
+        let mut scene = Scene {
             name: self.active_scene.name.clone(),
             entities: self
                 .runtime_entities
@@ -410,8 +439,10 @@ impl<T: ScriptingInstance> ECS<T> {
                 .collect(),
             required_assets: Vec::new(),
             shards: Vec::new(),
-            id_space: 0, // calculated later
-        }
+            id_space: self.active_scene.id_space,
+        };
+        scene.release_ids(self.id_space_begin);
+        scene
     }
 
     /// Returns a flat list of all entities in the scene
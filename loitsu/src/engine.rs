@@ -0,0 +1,177 @@
+use crate::ecs::ECS;
+use crate::scripting::rune_runtime::{ModuleFactory, RuneInstance};
+use crate::scripting::ScriptingInstance;
+use crate::Preferences;
+
+/// Extends the engine loop. Every hook is optional, they are called from
+/// `rendering::core::run` in the order the plugins were added.
+pub trait Plugin<T: ScriptingInstance = RuneInstance> {
+    /// Called once the renderer has been set up, before the first frame
+    fn on_start(&mut self) {}
+
+    /// Called after a scene has been loaded, before any of its components have run
+    fn on_scene_loaded(&mut self, _ecs: &mut ECS<T>) {}
+
+    /// Called on every frame the scripts run on, after the component methods
+    fn on_frame(&mut self, _ecs: &mut ECS<T>) {}
+
+    /// Called after the drawables have been drawn, before the frame is presented. Anything
+    /// recorded into the encoder is drawn on top of the scene.
+    fn on_render(&mut self, _context: &mut RenderContext) {}
+}
+
+/// What a plugin needs to draw into the current frame
+pub struct RenderContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    /// The view of the frame that is being rendered
+    pub view: &'a wgpu::TextureView,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub frame: u64,
+}
+
+pub struct WindowOptions {
+    pub title: String,
+    /// The logical size of the window, picked by the platform if `None`. Ignored on the web, where
+    /// the canvas fills the page.
+    pub size: Option<(u32, u32)>,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        WindowOptions {
+            title: "loitsu".to_string(),
+            size: None,
+            resizable: true,
+            fullscreen: false,
+        }
+    }
+}
+
+/// Changes the preferences after they have been loaded from the static shard
+pub type PreferencesOverride = Box<dyn Fn(&mut Preferences)>;
+
+/// Everything the engine was built with
+pub struct EngineOptions<T: ScriptingInstance> {
+    pub window: WindowOptions,
+    pub plugins: Vec<Box<dyn Plugin<T>>>,
+    pub preferences: Option<PreferencesOverride>,
+}
+
+impl<T: ScriptingInstance> EngineOptions<T> {
+    /// Returns the preferences with the override applied
+    pub fn apply_preferences(&self, preferences: &Preferences) -> Preferences {
+        let mut preferences = preferences.clone();
+        if let Some(preferences_override) = &self.preferences {
+            preferences_override(&mut preferences);
+        }
+        preferences
+    }
+}
+
+impl<T: ScriptingInstance> Default for EngineOptions<T> {
+    fn default() -> Self {
+        EngineOptions {
+            window: WindowOptions::default(),
+            plugins: Vec::new(),
+            preferences: None,
+        }
+    }
+}
+
+pub struct Engine;
+
+impl Engine {
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder {
+            options: EngineOptions::default(),
+            modules: Vec::new(),
+        }
+    }
+}
+
+pub struct EngineBuilder {
+    options: EngineOptions<RuneInstance>,
+    // registered when the engine starts, so that a builder that is never run changes nothing
+    modules: Vec<ModuleFactory>,
+}
+
+impl EngineBuilder {
+    pub fn window(mut self, window: WindowOptions) -> Self {
+        self.options.window = window;
+        self
+    }
+
+    /// Overrides preferences of the project, for example to use a different default scene
+    pub fn preferences(mut self, preferences: impl Fn(&mut Preferences) + 'static) -> Self {
+        self.options.preferences = Some(Box::new(preferences));
+        self
+    }
+
+    /// Makes a module of Rust functions and types available to scripts, see
+    /// `rune_runtime::register_module`
+    pub fn module<F>(mut self, module: F) -> Self
+    where
+        F: Fn() -> Result<rune::Module, rune::ContextError> + Send + Sync + 'static,
+    {
+        self.modules.push(Box::new(module));
+        self
+    }
+
+    pub fn plugin(mut self, plugin: impl Plugin + 'static) -> Self {
+        self.options.plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// Starts the engine. This should be called before any other loitsu functions, and on
+    /// desktop it only returns once the window has been closed.
+    #[cfg(not(feature = "editor"))]
+    pub fn run(self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+            console_log::init().expect("could not initialize logger");
+        }
+
+        crate::log!("Loitsu core v{} starting up...", env!("CARGO_PKG_VERSION"));
+        for module in self.modules {
+            crate::scripting::rune_runtime::register_module(module);
+        }
+        let ecs = std::sync::Arc::new(std::sync::RwLock::new(ECS::<RuneInstance>::new()));
+        let rune = RuneInstance::new_uninitialized().unwrap();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::rendering::desktop::init_window(rune, ecs, self.options);
+
+        #[cfg(target_arch = "wasm32")]
+        crate::rendering::web::init_view(rune, ecs, self.options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Empty;
+
+    impl Plugin for Empty {}
+
+    #[test]
+    fn test_builder() {
+        let builder = Engine::builder()
+            .window(WindowOptions {
+                title: "game".to_string(),
+                ..Default::default()
+            })
+            .preferences(|preferences| preferences.default_scene = "menu".to_string())
+            .module(|| Ok(rune::Module::new()))
+            .plugin(Empty);
+        assert_eq!(builder.options.window.title, "game");
+        assert_eq!(builder.options.plugins.len(), 1);
+        assert_eq!(builder.modules.len(), 1);
+        let preferences = builder.options.apply_preferences(&Preferences::default());
+        assert_eq!(preferences.default_scene, "menu");
+    }
+}
@@ -0,0 +1,114 @@
+use crate::ecs::ECS;
+use crate::input::InputState;
+use crate::scene_management::Scene;
+use crate::scripting::{ScriptingInstance, ScriptingSource};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Runs a scene and its scripts without a window or renderer. Frames are stepped manually, which
+/// makes it useful for replaying input recordings and for tests.
+pub struct HeadlessRunner<T: ScriptingInstance> {
+    pub ecs: Arc<RwLock<ECS<T>>>,
+    pub scripting: T,
+    pub input_state: Arc<Mutex<InputState>>,
+    pub frame_count: u64,
+    // shard requests made while loading the scene are spawned on the current runtime, or on our
+    // own if there isn't one
+    _runtime: Option<tokio::runtime::Runtime>,
+    handle: tokio::runtime::Handle,
+}
+
+impl<T: ScriptingInstance + 'static> HeadlessRunner<T> {
+    pub fn new(
+        scripts: Vec<ScriptingSource>,
+        scene: Scene,
+    ) -> crate::scripting::Result<HeadlessRunner<T>> {
+        Self::with_input_state(scripts, scene, InputState::new())
+    }
+
+    /// Like `new`, but starts from the given input state. Replays and seeds have to be set up
+    /// before the scene is loaded, as components may already use them when they start.
+    pub fn with_input_state(
+        scripts: Vec<ScriptingSource>,
+        scene: Scene,
+        input_state: InputState,
+    ) -> crate::scripting::Result<HeadlessRunner<T>> {
+        Self::with_initializer(scene, input_state, |scripting, input_state, ecs| {
+            scripting.initialize(scripts, input_state, ecs)
+        })
+    }
+
+    /// Like `with_input_state`, but scripting is set up by `initialize`, which is given the input
+    /// state and the ECS to initialize it with
+    pub fn with_initializer(
+        scene: Scene,
+        input_state: InputState,
+        initialize: impl FnOnce(
+            &mut T,
+            Arc<Mutex<InputState>>,
+            Arc<RwLock<ECS<T>>>,
+        ) -> crate::scripting::Result<()>,
+    ) -> crate::scripting::Result<HeadlessRunner<T>> {
+        let (runtime, handle) = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => (None, handle),
+            Err(_) => {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| {
+                        crate::scripting::ScriptingError::new(&format!(
+                            "Couldn't start the async runtime: {}",
+                            e
+                        ))
+                    })?;
+                let handle = runtime.handle().clone();
+                (Some(runtime), handle)
+            }
+        };
+        #[cfg(not(feature = "editor"))]
+        let ecs = Arc::new(RwLock::new(ECS::<T>::new()));
+        #[cfg(feature = "editor")]
+        let ecs = Arc::new(RwLock::new(ECS::<T>::new(Arc::new(Mutex::new(
+            crate::editor::EventHandler::new(),
+        )))));
+        let input_state = Arc::new(Mutex::new(input_state));
+        let mut scripting = T::new_uninitialized()?;
+        initialize(&mut scripting, input_state.clone(), ecs.clone())?;
+        {
+            let _guard = handle.enter();
+            ecs.write().unwrap().load_scene(scene, &mut scripting);
+        }
+        Ok(HeadlessRunner {
+            ecs,
+            scripting,
+            input_state,
+            frame_count: 0,
+            _runtime: runtime,
+            handle,
+        })
+    }
+
+    /// Runs a single frame that took `delta` seconds
+    pub fn step(&mut self, delta: f32) {
+        let _guard = self.handle.enter();
+        self.input_state.lock().unwrap().begin_frame(delta);
+        self.scripting.begin_frame();
+        self.ecs.read().unwrap().run_frame(&mut self.scripting);
+        self.input_state.lock().unwrap().end_frame();
+        self.frame_count += 1;
+    }
+
+    /// Steps through the rest of the replay in progress and returns the amount of frames that
+    /// were run
+    pub fn replay(&mut self) -> usize {
+        let frames = match &self.input_state.lock().unwrap().replay {
+            Some(replay) => replay.remaining_frames(),
+            None => 0,
+        };
+        for _ in 0..frames {
+            // the delta comes from the recording
+            self.step(0.0);
+        }
+        self.input_state.lock().unwrap().replay = None;
+        frames
+    }
+}
@@ -0,0 +1,457 @@
+use super::keyboard::{parse_key, KeyInput};
+use super::{gamepad, mouse, InputState};
+use crate::warn;
+use std::collections::HashMap;
+use winit::event::MouseButton;
+
+/// The named actions and axes of a game, as written in `preferences.json`. Each binding is a
+/// combination of inputs separated by `+`, for example `"Space"`, `"Control+S"`, `"Mouse:Left"`
+/// or `"Gamepad:South"`.
+#[cfg_attr(feature = "json_preference_parse", derive(serde::Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub struct InputPreferences {
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub actions: HashMap<String, Vec<String>>,
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub axes: HashMap<String, AxisPreferences>,
+}
+
+#[cfg_attr(feature = "json_preference_parse", derive(serde::Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub struct AxisPreferences {
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub negative: Vec<String>,
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub positive: Vec<String>,
+    /// A gamepad axis that drives this axis in addition to the bindings
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub gamepad: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    Key(KeyInput),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad
+    Gamepad(gamepad::GamepadButton),
+}
+
+impl InputSource {
+    fn parse(source: &str) -> Result<InputSource, String> {
+        if let Some(button) = source.strip_prefix("Mouse:") {
+            return mouse::str_to_button(button)
+                .map(InputSource::Mouse)
+                .ok_or_else(|| format!("Unknown mouse button '{}'", button));
+        }
+        if let Some(button) = source.strip_prefix("Gamepad:") {
+            return gamepad::str_to_button(button)
+                .map(InputSource::Gamepad)
+                .ok_or_else(|| format!("Unknown gamepad button '{}'", button));
+        }
+        parse_key(source)
+            .map(InputSource::Key)
+            .ok_or_else(|| format!("Unknown key '{}'", source))
+    }
+
+    fn pressed(&self, input: &InputState) -> bool {
+        match self {
+            InputSource::Key(key) => input.get_key(key),
+            InputSource::Mouse(button) => input.mouse.get_button(*button),
+            InputSource::Gamepad(button) => input
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.get_button(*button)),
+        }
+    }
+
+    fn down(&self, input: &InputState) -> bool {
+        match self {
+            InputSource::Key(key) => input.get_key_down(key),
+            InputSource::Mouse(button) => input.mouse.get_button_down(*button),
+            InputSource::Gamepad(button) => input
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.get_button_down(*button)),
+        }
+    }
+
+    fn up(&self, input: &InputState) -> bool {
+        match self {
+            InputSource::Key(key) => input.get_key_up(key),
+            InputSource::Mouse(button) => input.mouse.get_button_up(*button),
+            InputSource::Gamepad(button) => input
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.get_button_up(*button)),
+        }
+    }
+}
+
+/// A combination of inputs that all have to be held at the same time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub inputs: Vec<InputSource>,
+}
+
+impl Binding {
+    pub fn parse(binding: &str) -> Result<Binding, String> {
+        let inputs = binding
+            .split('+')
+            .map(|source| source.trim())
+            .map(|source| {
+                if source.is_empty() {
+                    Err(format!("Invalid binding '{}'", binding))
+                } else {
+                    InputSource::parse(source)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Binding { inputs })
+    }
+
+    pub fn pressed(&self, input: &InputState) -> bool {
+        self.inputs.iter().all(|source| source.pressed(input))
+    }
+
+    /// The combination was completed this frame
+    pub fn down(&self, input: &InputState) -> bool {
+        self.pressed(input) && self.inputs.iter().any(|source| source.down(input))
+    }
+
+    /// The combination was broken this frame
+    pub fn up(&self, input: &InputState) -> bool {
+        self.inputs.iter().any(|source| source.up(input))
+            && self
+                .inputs
+                .iter()
+                .all(|source| source.pressed(input) || source.up(input))
+    }
+}
+
+struct Axis {
+    negative: Vec<Binding>,
+    positive: Vec<Binding>,
+    gamepad: Option<gamepad::GamepadAxis>,
+}
+
+/// Resolves named actions and axes to their bindings. The bindings come from the preferences and
+/// can be overridden by the player at runtime.
+#[derive(Default)]
+pub struct ActionMap {
+    defaults: InputPreferences,
+    overrides: InputPreferences,
+    actions: HashMap<String, Vec<Binding>>,
+    axes: HashMap<String, Axis>,
+}
+
+impl ActionMap {
+    pub fn new(defaults: InputPreferences) -> ActionMap {
+        let mut map = ActionMap {
+            defaults,
+            ..Default::default()
+        };
+        map.rebuild();
+        map
+    }
+
+    pub fn has_action(&self, name: &str) -> bool {
+        self.actions.contains_key(name)
+    }
+
+    pub fn has_axis(&self, name: &str) -> bool {
+        self.axes.contains_key(name)
+    }
+
+    pub fn action_pressed(&self, name: &str, input: &InputState) -> bool {
+        self.actions
+            .get(name)
+            .is_some_and(|bindings| bindings.iter().any(|binding| binding.pressed(input)))
+    }
+
+    pub fn action_down(&self, name: &str, input: &InputState) -> bool {
+        self.actions
+            .get(name)
+            .is_some_and(|bindings| bindings.iter().any(|binding| binding.down(input)))
+    }
+
+    pub fn action_up(&self, name: &str, input: &InputState) -> bool {
+        // another binding might still be keeping the action pressed
+        !self.action_pressed(name, input)
+            && self
+                .actions
+                .get(name)
+                .is_some_and(|bindings| bindings.iter().any(|binding| binding.up(input)))
+    }
+
+    /// Returns the value of the axis between -1 and 1
+    pub fn axis(&self, name: &str, input: &InputState) -> f32 {
+        let Some(axis) = self.axes.get(name) else {
+            return 0.0;
+        };
+        let mut value = 0.0;
+        if axis.negative.iter().any(|binding| binding.pressed(input)) {
+            value -= 1.0;
+        }
+        if axis.positive.iter().any(|binding| binding.pressed(input)) {
+            value += 1.0;
+        }
+        if let Some(gamepad_axis) = axis.gamepad {
+            for gamepad in &input.gamepads {
+                value += gamepad.get_axis(gamepad_axis, input.dead_zone);
+            }
+        }
+        value.clamp(-1.0, 1.0)
+    }
+
+    /// Returns the bindings currently in use for the action
+    pub fn action_bindings(&self, name: &str) -> Option<&Vec<String>> {
+        self.overrides
+            .actions
+            .get(name)
+            .or_else(|| self.defaults.actions.get(name))
+    }
+
+    pub fn axis_bindings(&self, name: &str) -> Option<&AxisPreferences> {
+        self.overrides
+            .axes
+            .get(name)
+            .or_else(|| self.defaults.axes.get(name))
+    }
+
+    /// Replaces the bindings of an action. Nothing is changed if any of the bindings are invalid.
+    pub fn rebind_action(&mut self, name: &str, bindings: Vec<String>) -> Result<(), String> {
+        if !self.has_action(name) {
+            return Err(format!("Unknown action '{}'", name));
+        }
+        for binding in &bindings {
+            Binding::parse(binding)?;
+        }
+        self.overrides.actions.insert(name.to_string(), bindings);
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Replaces the bindings of an axis. Nothing is changed if any of the bindings are invalid.
+    pub fn rebind_axis(&mut self, name: &str, bindings: AxisPreferences) -> Result<(), String> {
+        if !self.has_axis(name) {
+            return Err(format!("Unknown axis '{}'", name));
+        }
+        parse_axis(&bindings)?;
+        self.overrides.axes.insert(name.to_string(), bindings);
+        self.rebuild();
+        Ok(())
+    }
+
+    pub fn overrides(&self) -> &InputPreferences {
+        &self.overrides
+    }
+
+    /// Applies previously saved overrides. Overrides for actions or axes that no longer exist
+    /// are dropped.
+    pub fn set_overrides(&mut self, overrides: InputPreferences) {
+        self.overrides = overrides;
+        let defaults = &self.defaults;
+        self.overrides
+            .actions
+            .retain(|name, _| defaults.actions.contains_key(name));
+        self.overrides
+            .axes
+            .retain(|name, _| defaults.axes.contains_key(name));
+        self.rebuild();
+    }
+
+    pub fn reset(&mut self) {
+        self.set_overrides(InputPreferences::default());
+    }
+
+    fn rebuild(&mut self) {
+        self.actions.clear();
+        self.axes.clear();
+        for (name, defaults) in &self.defaults.actions {
+            let bindings = self.overrides.actions.get(name).unwrap_or(defaults);
+            let bindings = bindings
+                .iter()
+                .filter_map(|binding| match Binding::parse(binding) {
+                    Ok(binding) => Some(binding),
+                    Err(e) => {
+                        warn!("Ignoring binding of action '{}': {}", name, e);
+                        None
+                    }
+                })
+                .collect();
+            self.actions.insert(name.clone(), bindings);
+        }
+        for (name, defaults) in &self.defaults.axes {
+            let bindings = self.overrides.axes.get(name).unwrap_or(defaults);
+            let axis = match parse_axis(bindings) {
+                Ok(axis) => axis,
+                Err(e) => {
+                    warn!("Ignoring bindings of axis '{}': {}", name, e);
+                    Axis {
+                        negative: Vec::new(),
+                        positive: Vec::new(),
+                        gamepad: None,
+                    }
+                }
+            };
+            self.axes.insert(name.clone(), axis);
+        }
+    }
+}
+
+fn parse_axis(bindings: &AxisPreferences) -> Result<Axis, String> {
+    let parse_all = |bindings: &Vec<String>| {
+        bindings
+            .iter()
+            .map(|binding| Binding::parse(binding))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let gamepad = match &bindings.gamepad {
+        Some(axis) => Some(
+            gamepad::str_to_axis(axis).ok_or_else(|| format!("Unknown gamepad axis '{}'", axis))?,
+        ),
+        None => None,
+    };
+    Ok(Axis {
+        negative: parse_all(&bindings.negative)?,
+        positive: parse_all(&bindings.positive)?,
+        gamepad,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const OVERRIDES_FILE: &str = "input_bindings.bin";
+#[cfg(target_arch = "wasm32")]
+const OVERRIDES_KEY: &str = "loitsu_input_bindings";
+
+/// Loads the bindings the player has saved, if there are any
+pub fn load_overrides() -> Option<InputPreferences> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let data = std::fs::read(crate::util::paths::user_data_dir()?.join(OVERRIDES_FILE)).ok()?;
+    #[cfg(target_arch = "wasm32")]
+    let data = {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        let hex = storage.get_item(OVERRIDES_KEY).ok()??;
+        crate::storage::web::from_hex(&hex)?
+    };
+    match bitcode::decode(&data) {
+        Ok(overrides) => Some(overrides),
+        Err(e) => {
+            warn!("Couldn't read the saved input bindings: {}", e);
+            None
+        }
+    }
+}
+
+/// Saves the bindings the player has changed so that they persist between sessions
+pub fn save_overrides(overrides: &InputPreferences) {
+    let data = match bitcode::encode(overrides) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Couldn't save the input bindings: {}", e);
+            return;
+        }
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let Some(dir) = crate::util::paths::user_data_dir() else {
+            warn!("Couldn't save the input bindings: no user data directory");
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(dir.join(OVERRIDES_FILE), data))
+        {
+            warn!("Couldn't save the input bindings: {}", e);
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let hex = crate::storage::web::to_hex(&data);
+        let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+        if storage
+            .and_then(|storage| storage.set_item(OVERRIDES_KEY, &hex).ok())
+            .is_none()
+        {
+            warn!("Couldn't save the input bindings: local storage isn't available");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::keyboard::{Key, NamedKey};
+
+    fn map() -> ActionMap {
+        let mut preferences = InputPreferences::default();
+        preferences.actions.insert(
+            "save".to_string(),
+            vec!["Control+s".to_string(), "Mouse:Right".to_string()],
+        );
+        preferences.axes.insert(
+            "move_x".to_string(),
+            AxisPreferences {
+                negative: vec!["a".to_string(), "Left".to_string()],
+                positive: vec!["d".to_string(), "Right".to_string()],
+                gamepad: Some("LeftStickX".to_string()),
+            },
+        );
+        ActionMap::new(preferences)
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut map = map();
+        let mut input = InputState::new();
+        let control = Key::Named(NamedKey::Control);
+        let s = Key::Character("s".into());
+
+        input.down_keys.push(s.clone());
+        input.new_keys.push(s.clone());
+        assert!(!map.action_pressed("save", &input));
+
+        input.down_keys.push(control.clone());
+        assert!(map.action_pressed("save", &input));
+        assert!(map.action_down("save", &input));
+
+        input.end_frame();
+        input.down_keys.retain(|key| *key != s);
+        input.up_keys.push(s.clone());
+        assert!(map.action_up("save", &input));
+
+        input.mouse.set_button(MouseButton::Right, true);
+        assert!(map.action_pressed("save", &input));
+
+        assert!(map
+            .rebind_action("save", vec!["Shift+x".to_string()])
+            .is_ok());
+        assert!(!map.action_pressed("save", &input));
+        assert!(map
+            .rebind_action("save", vec!["NotAKey".to_string()])
+            .is_err());
+        assert!(map.rebind_action("load", vec!["l".to_string()]).is_err());
+        assert_eq!(
+            map.action_bindings("save"),
+            Some(&vec!["Shift+x".to_string()])
+        );
+
+        map.reset();
+        assert!(map.action_pressed("save", &input));
+    }
+
+    #[test]
+    fn test_axes() {
+        let map = map();
+        let mut input = InputState::new();
+        assert_eq!(map.axis("move_x", &input), 0.0);
+        input.down_keys.push(Key::Named(NamedKey::ArrowLeft));
+        assert_eq!(map.axis("move_x", &input), -1.0);
+        input.down_keys.push(Key::Character("d".into()));
+        assert_eq!(map.axis("move_x", &input), 0.0);
+        input.down_keys.clear();
+        input.process_event(crate::input::InputEvent::Gamepad(
+            gamepad::GamepadEvent::AxisChanged(0, gamepad::GamepadAxis::LeftStickX, 1.0),
+        ));
+        assert_eq!(map.axis("move_x", &input), 1.0);
+    }
+}
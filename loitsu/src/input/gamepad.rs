@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+/// The default size of the dead zone applied to all axes
+pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+pub type GamepadId = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, bitcode::Encode, bitcode::Decode)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, bitcode::Encode, bitcode::Decode)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Debug, Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    ButtonPressed(GamepadId, GamepadButton),
+    ButtonReleased(GamepadId, GamepadButton),
+    /// Raw axis values range from -1 to 1, triggers from 0 to 1
+    AxisChanged(GamepadId, GamepadAxis, f32),
+}
+
+/// A source of gamepad events, implemented for each platform
+pub trait GamepadBackend {
+    /// Returns all events that have happened since the last poll
+    fn poll(&mut self) -> Vec<GamepadEvent>;
+}
+
+/// A backend without any gamepads, used when gamepad support isn't enabled
+pub struct NoGamepadBackend;
+
+impl GamepadBackend for NoGamepadBackend {
+    fn poll(&mut self) -> Vec<GamepadEvent> {
+        Vec::new()
+    }
+}
+
+/// A gamepad driven from code, useful for tests and input replays
+#[derive(Default)]
+pub struct VirtualGamepadBackend {
+    pending: Vec<GamepadEvent>,
+}
+
+impl VirtualGamepadBackend {
+    pub fn new() -> VirtualGamepadBackend {
+        VirtualGamepadBackend {
+            pending: Vec::new(),
+        }
+    }
+
+    /// Queues an event to be returned on the next poll
+    pub fn push(&mut self, event: GamepadEvent) {
+        self.pending.push(event);
+    }
+}
+
+impl GamepadBackend for VirtualGamepadBackend {
+    fn poll(&mut self) -> Vec<GamepadEvent> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+pub struct GamepadState {
+    pub id: GamepadId,
+    pub down_buttons: Vec<GamepadButton>,
+    pub new_buttons: Vec<GamepadButton>,
+    pub up_buttons: Vec<GamepadButton>,
+    axes: HashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    pub fn new(id: GamepadId) -> GamepadState {
+        GamepadState {
+            id,
+            down_buttons: Vec::new(),
+            new_buttons: Vec::new(),
+            up_buttons: Vec::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    pub fn get_button(&self, button: GamepadButton) -> bool {
+        self.down_buttons.contains(&button)
+    }
+
+    pub fn get_button_down(&self, button: GamepadButton) -> bool {
+        self.new_buttons.contains(&button)
+    }
+
+    pub fn get_button_up(&self, button: GamepadButton) -> bool {
+        self.up_buttons.contains(&button)
+    }
+
+    /// Returns the value of the axis with the dead zone applied. Values outside the dead zone are
+    /// rescaled so that the output still covers the whole range.
+    pub fn get_axis(&self, axis: GamepadAxis, dead_zone: f32) -> f32 {
+        let value = self.axes.get(&axis).copied().unwrap_or(0.0);
+        if value.abs() <= dead_zone {
+            return 0.0;
+        }
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+    }
+
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        self.axes.insert(axis, value.clamp(-1.0, 1.0));
+    }
+}
+
+pub fn str_to_button(button: &str) -> Option<GamepadButton> {
+    match button {
+        "South" | "A" => Some(GamepadButton::South),
+        "East" | "B" => Some(GamepadButton::East),
+        "North" | "Y" => Some(GamepadButton::North),
+        "West" | "X" => Some(GamepadButton::West),
+        "LeftBumper" => Some(GamepadButton::LeftBumper),
+        "RightBumper" => Some(GamepadButton::RightBumper),
+        "LeftTrigger" => Some(GamepadButton::LeftTrigger),
+        "RightTrigger" => Some(GamepadButton::RightTrigger),
+        "Select" => Some(GamepadButton::Select),
+        "Start" => Some(GamepadButton::Start),
+        "Mode" => Some(GamepadButton::Mode),
+        "LeftStick" => Some(GamepadButton::LeftStick),
+        "RightStick" => Some(GamepadButton::RightStick),
+        "DPadUp" => Some(GamepadButton::DPadUp),
+        "DPadDown" => Some(GamepadButton::DPadDown),
+        "DPadLeft" => Some(GamepadButton::DPadLeft),
+        "DPadRight" => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+pub fn str_to_axis(axis: &str) -> Option<GamepadAxis> {
+    match axis {
+        "LeftStickX" => Some(GamepadAxis::LeftStickX),
+        "LeftStickY" => Some(GamepadAxis::LeftStickY),
+        "RightStickX" => Some(GamepadAxis::RightStickX),
+        "RightStickY" => Some(GamepadAxis::RightStickY),
+        "LeftTrigger" => Some(GamepadAxis::LeftTrigger),
+        "RightTrigger" => Some(GamepadAxis::RightTrigger),
+        _ => None,
+    }
+}
+
+/// Returns the gamepad backend for the current platform
+pub fn default_backend() -> Box<dyn GamepadBackend> {
+    #[cfg(feature = "gamepad")]
+    {
+        match gilrs_backend::GilrsBackend::new() {
+            Ok(backend) => return Box::new(backend),
+            Err(e) => crate::warn!("Gamepads aren't available: {}", e),
+        }
+    }
+    Box::new(NoGamepadBackend)
+}
+
+#[cfg(feature = "gamepad")]
+mod gilrs_backend {
+    use super::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent};
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    pub struct GilrsBackend {
+        gilrs: Gilrs,
+        initial_events: Vec<GamepadEvent>,
+    }
+
+    impl GilrsBackend {
+        pub fn new() -> Result<GilrsBackend, gilrs::Error> {
+            let gilrs = Gilrs::new()?;
+            // gamepads that are already plugged in don't get a connect event
+            let initial_events = gilrs
+                .gamepads()
+                .map(|(id, _)| GamepadEvent::Connected(usize::from(id) as u32))
+                .collect();
+            Ok(GilrsBackend {
+                gilrs,
+                initial_events,
+            })
+        }
+    }
+
+    impl GamepadBackend for GilrsBackend {
+        fn poll(&mut self) -> Vec<GamepadEvent> {
+            let mut events = std::mem::take(&mut self.initial_events);
+            while let Some(event) = self.gilrs.next_event() {
+                let id = usize::from(event.id) as u32;
+                let event = match event.event {
+                    EventType::Connected => Some(GamepadEvent::Connected(id)),
+                    EventType::Disconnected => Some(GamepadEvent::Disconnected(id)),
+                    EventType::ButtonPressed(button, _) => {
+                        convert_button(button).map(|b| GamepadEvent::ButtonPressed(id, b))
+                    }
+                    EventType::ButtonReleased(button, _) => {
+                        convert_button(button).map(|b| GamepadEvent::ButtonReleased(id, b))
+                    }
+                    // analog triggers are reported as buttons with a value
+                    EventType::ButtonChanged(Button::LeftTrigger2, value, _) => Some(
+                        GamepadEvent::AxisChanged(id, GamepadAxis::LeftTrigger, value),
+                    ),
+                    EventType::ButtonChanged(Button::RightTrigger2, value, _) => Some(
+                        GamepadEvent::AxisChanged(id, GamepadAxis::RightTrigger, value),
+                    ),
+                    EventType::AxisChanged(axis, value, _) => {
+                        convert_axis(axis).map(|a| GamepadEvent::AxisChanged(id, a, value))
+                    }
+                    _ => None,
+                };
+                events.extend(event);
+            }
+            events
+        }
+    }
+
+    fn convert_button(button: Button) -> Option<GamepadButton> {
+        match button {
+            Button::South => Some(GamepadButton::South),
+            Button::East => Some(GamepadButton::East),
+            Button::North => Some(GamepadButton::North),
+            Button::West => Some(GamepadButton::West),
+            Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+            Button::RightTrigger => Some(GamepadButton::RightBumper),
+            Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger),
+            Button::RightTrigger2 => Some(GamepadButton::RightTrigger),
+            Button::Select => Some(GamepadButton::Select),
+            Button::Start => Some(GamepadButton::Start),
+            Button::Mode => Some(GamepadButton::Mode),
+            Button::LeftThumb => Some(GamepadButton::LeftStick),
+            Button::RightThumb => Some(GamepadButton::RightStick),
+            Button::DPadUp => Some(GamepadButton::DPadUp),
+            Button::DPadDown => Some(GamepadButton::DPadDown),
+            Button::DPadLeft => Some(GamepadButton::DPadLeft),
+            Button::DPadRight => Some(GamepadButton::DPadRight),
+            _ => None,
+        }
+    }
+
+    fn convert_axis(axis: Axis) -> Option<GamepadAxis> {
+        match axis {
+            Axis::LeftStickX => Some(GamepadAxis::LeftStickX),
+            Axis::LeftStickY => Some(GamepadAxis::LeftStickY),
+            Axis::RightStickX => Some(GamepadAxis::RightStickX),
+            Axis::RightStickY => Some(GamepadAxis::RightStickY),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputState;
+
+    #[test]
+    fn test_virtual_gamepad() {
+        let mut backend = VirtualGamepadBackend::new();
+        let mut input_state = InputState::new();
+        backend.push(GamepadEvent::Connected(0));
+        backend.push(GamepadEvent::ButtonPressed(0, GamepadButton::South));
+        backend.push(GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickX, 0.1));
+        backend.push(GamepadEvent::AxisChanged(0, GamepadAxis::LeftStickY, -1.0));
+        input_state.poll_gamepads(&mut backend);
+
+        assert_eq!(input_state.connected_gamepads, vec![0]);
+        let gamepad = input_state.get_gamepad(0).unwrap();
+        assert!(gamepad.get_button(GamepadButton::South));
+        assert!(gamepad.get_button_down(GamepadButton::South));
+        // inside the dead zone
+        assert_eq!(gamepad.get_axis(GamepadAxis::LeftStickX, 0.15), 0.0);
+        assert_eq!(gamepad.get_axis(GamepadAxis::LeftStickY, 0.15), -1.0);
+
+        input_state.end_frame();
+        backend.push(GamepadEvent::ButtonReleased(0, GamepadButton::South));
+        input_state.poll_gamepads(&mut backend);
+        let gamepad = input_state.get_gamepad(0).unwrap();
+        assert!(!gamepad.get_button(GamepadButton::South));
+        assert!(!gamepad.get_button_down(GamepadButton::South));
+        assert!(gamepad.get_button_up(GamepadButton::South));
+
+        backend.push(GamepadEvent::Disconnected(0));
+        input_state.poll_gamepads(&mut backend);
+        assert!(input_state.get_gamepad(0).is_none());
+        assert_eq!(input_state.disconnected_gamepads, vec![0]);
+    }
+}
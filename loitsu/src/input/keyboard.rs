@@ -0,0 +1,620 @@
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
+
+/// A key as scripts refer to it. Logical keys depend on the keyboard layout, physical keys are
+/// identified by their position on the keyboard.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyInput {
+    Logical(Key),
+    Physical(KeyCode),
+}
+
+/// Text typed by the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextInput {
+    /// Text that was typed or committed by an IME
+    Commit(String),
+    /// Text that is still being composed in an IME, replacing the previous composition. An
+    /// empty string means the composition was cleared.
+    Preedit(String),
+}
+
+macro_rules! key_names {
+    ($from_str:ident, $to_str:ident, $ty:ident, [$($variant:ident),* $(,)?]) => {
+        pub fn $from_str(name: &str) -> Option<$ty> {
+            match name {
+                $(stringify!($variant) => Some($ty::$variant),)*
+                _ => None,
+            }
+        }
+
+        pub fn $to_str(key: $ty) -> Option<&'static str> {
+            match key {
+                $($ty::$variant => Some(stringify!($variant)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_names!(
+    str_to_named_key,
+    named_key_to_str,
+    NamedKey,
+    [
+        Alt,
+        AltGraph,
+        CapsLock,
+        Control,
+        Fn,
+        FnLock,
+        NumLock,
+        ScrollLock,
+        Shift,
+        Symbol,
+        SymbolLock,
+        Meta,
+        Hyper,
+        Super,
+        Enter,
+        Tab,
+        Space,
+        ArrowDown,
+        ArrowLeft,
+        ArrowRight,
+        ArrowUp,
+        End,
+        Home,
+        PageDown,
+        PageUp,
+        Backspace,
+        Clear,
+        Copy,
+        CrSel,
+        Cut,
+        Delete,
+        EraseEof,
+        ExSel,
+        Insert,
+        Paste,
+        Redo,
+        Undo,
+        Accept,
+        Again,
+        Attn,
+        Cancel,
+        ContextMenu,
+        Escape,
+        Execute,
+        Find,
+        Help,
+        Pause,
+        Play,
+        Props,
+        Select,
+        ZoomIn,
+        ZoomOut,
+        BrightnessDown,
+        BrightnessUp,
+        Eject,
+        LogOff,
+        Power,
+        PowerOff,
+        PrintScreen,
+        Hibernate,
+        Standby,
+        WakeUp,
+        AllCandidates,
+        Alphanumeric,
+        CodeInput,
+        Compose,
+        Convert,
+        FinalMode,
+        GroupFirst,
+        GroupLast,
+        GroupNext,
+        GroupPrevious,
+        ModeChange,
+        NextCandidate,
+        NonConvert,
+        PreviousCandidate,
+        Process,
+        SingleCandidate,
+        HangulMode,
+        HanjaMode,
+        JunjaMode,
+        Eisu,
+        Hankaku,
+        Hiragana,
+        HiraganaKatakana,
+        KanaMode,
+        KanjiMode,
+        Katakana,
+        Romaji,
+        Zenkaku,
+        ZenkakuHankaku,
+        Soft1,
+        Soft2,
+        Soft3,
+        Soft4,
+        ChannelDown,
+        ChannelUp,
+        Close,
+        MailForward,
+        MailReply,
+        MailSend,
+        MediaClose,
+        MediaFastForward,
+        MediaPause,
+        MediaPlay,
+        MediaPlayPause,
+        MediaRecord,
+        MediaRewind,
+        MediaStop,
+        MediaTrackNext,
+        MediaTrackPrevious,
+        New,
+        Open,
+        Print,
+        Save,
+        SpellCheck,
+        Key11,
+        Key12,
+        AudioBalanceLeft,
+        AudioBalanceRight,
+        AudioBassBoostDown,
+        AudioBassBoostToggle,
+        AudioBassBoostUp,
+        AudioFaderFront,
+        AudioFaderRear,
+        AudioSurroundModeNext,
+        AudioTrebleDown,
+        AudioTrebleUp,
+        AudioVolumeDown,
+        AudioVolumeUp,
+        AudioVolumeMute,
+        MicrophoneToggle,
+        MicrophoneVolumeDown,
+        MicrophoneVolumeUp,
+        MicrophoneVolumeMute,
+        SpeechCorrectionList,
+        SpeechInputToggle,
+        LaunchApplication1,
+        LaunchApplication2,
+        LaunchCalendar,
+        LaunchContacts,
+        LaunchMail,
+        LaunchMediaPlayer,
+        LaunchMusicPlayer,
+        LaunchPhone,
+        LaunchScreenSaver,
+        LaunchSpreadsheet,
+        LaunchWebBrowser,
+        LaunchWebCam,
+        LaunchWordProcessor,
+        BrowserBack,
+        BrowserFavorites,
+        BrowserForward,
+        BrowserHome,
+        BrowserRefresh,
+        BrowserSearch,
+        BrowserStop,
+        AppSwitch,
+        Call,
+        Camera,
+        CameraFocus,
+        EndCall,
+        GoBack,
+        GoHome,
+        HeadsetHook,
+        LastNumberRedial,
+        Notification,
+        MannerMode,
+        VoiceDial,
+        TV,
+        TV3DMode,
+        TVAntennaCable,
+        TVAudioDescription,
+        TVAudioDescriptionMixDown,
+        TVAudioDescriptionMixUp,
+        TVContentsMenu,
+        TVDataService,
+        TVInput,
+        TVInputComponent1,
+        TVInputComponent2,
+        TVInputComposite1,
+        TVInputComposite2,
+        TVInputHDMI1,
+        TVInputHDMI2,
+        TVInputHDMI3,
+        TVInputHDMI4,
+        TVInputVGA1,
+        TVMediaContext,
+        TVNetwork,
+        TVNumberEntry,
+        TVPower,
+        TVRadioService,
+        TVSatellite,
+        TVSatelliteBS,
+        TVSatelliteCS,
+        TVSatelliteToggle,
+        TVTerrestrialAnalog,
+        TVTerrestrialDigital,
+        TVTimer,
+        AVRInput,
+        AVRPower,
+        ColorF0Red,
+        ColorF1Green,
+        ColorF2Yellow,
+        ColorF3Blue,
+        ColorF4Grey,
+        ColorF5Brown,
+        ClosedCaptionToggle,
+        Dimmer,
+        DisplaySwap,
+        DVR,
+        Exit,
+        FavoriteClear0,
+        FavoriteClear1,
+        FavoriteClear2,
+        FavoriteClear3,
+        FavoriteRecall0,
+        FavoriteRecall1,
+        FavoriteRecall2,
+        FavoriteRecall3,
+        FavoriteStore0,
+        FavoriteStore1,
+        FavoriteStore2,
+        FavoriteStore3,
+        Guide,
+        GuideNextDay,
+        GuidePreviousDay,
+        Info,
+        InstantReplay,
+        Link,
+        ListProgram,
+        LiveContent,
+        Lock,
+        MediaApps,
+        MediaAudioTrack,
+        MediaLast,
+        MediaSkipBackward,
+        MediaSkipForward,
+        MediaStepBackward,
+        MediaStepForward,
+        MediaTopMenu,
+        NavigateIn,
+        NavigateNext,
+        NavigateOut,
+        NavigatePrevious,
+        NextFavoriteChannel,
+        NextUserProfile,
+        OnDemand,
+        Pairing,
+        PinPDown,
+        PinPMove,
+        PinPToggle,
+        PinPUp,
+        PlaySpeedDown,
+        PlaySpeedReset,
+        PlaySpeedUp,
+        RandomToggle,
+        RcLowBattery,
+        RecordSpeedNext,
+        RfBypass,
+        ScanChannelsToggle,
+        ScreenModeNext,
+        Settings,
+        SplitScreenToggle,
+        STBInput,
+        STBPower,
+        Subtitle,
+        Teletext,
+        VideoModeNext,
+        Wink,
+        ZoomToggle,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        F25,
+        F26,
+        F27,
+        F28,
+        F29,
+        F30,
+        F31,
+        F32,
+        F33,
+        F34,
+        F35,
+    ]
+);
+
+key_names!(
+    str_to_key_code,
+    key_code_to_str,
+    KeyCode,
+    [
+        Backquote,
+        Backslash,
+        BracketLeft,
+        BracketRight,
+        Comma,
+        Digit0,
+        Digit1,
+        Digit2,
+        Digit3,
+        Digit4,
+        Digit5,
+        Digit6,
+        Digit7,
+        Digit8,
+        Digit9,
+        Equal,
+        IntlBackslash,
+        IntlRo,
+        IntlYen,
+        KeyA,
+        KeyB,
+        KeyC,
+        KeyD,
+        KeyE,
+        KeyF,
+        KeyG,
+        KeyH,
+        KeyI,
+        KeyJ,
+        KeyK,
+        KeyL,
+        KeyM,
+        KeyN,
+        KeyO,
+        KeyP,
+        KeyQ,
+        KeyR,
+        KeyS,
+        KeyT,
+        KeyU,
+        KeyV,
+        KeyW,
+        KeyX,
+        KeyY,
+        KeyZ,
+        Minus,
+        Period,
+        Quote,
+        Semicolon,
+        Slash,
+        AltLeft,
+        AltRight,
+        Backspace,
+        CapsLock,
+        ContextMenu,
+        ControlLeft,
+        ControlRight,
+        Enter,
+        SuperLeft,
+        SuperRight,
+        ShiftLeft,
+        ShiftRight,
+        Space,
+        Tab,
+        Convert,
+        KanaMode,
+        Lang1,
+        Lang2,
+        Lang3,
+        Lang4,
+        Lang5,
+        NonConvert,
+        Delete,
+        End,
+        Help,
+        Home,
+        Insert,
+        PageDown,
+        PageUp,
+        ArrowDown,
+        ArrowLeft,
+        ArrowRight,
+        ArrowUp,
+        NumLock,
+        Numpad0,
+        Numpad1,
+        Numpad2,
+        Numpad3,
+        Numpad4,
+        Numpad5,
+        Numpad6,
+        Numpad7,
+        Numpad8,
+        Numpad9,
+        NumpadAdd,
+        NumpadBackspace,
+        NumpadClear,
+        NumpadClearEntry,
+        NumpadComma,
+        NumpadDecimal,
+        NumpadDivide,
+        NumpadEnter,
+        NumpadEqual,
+        NumpadHash,
+        NumpadMemoryAdd,
+        NumpadMemoryClear,
+        NumpadMemoryRecall,
+        NumpadMemoryStore,
+        NumpadMemorySubtract,
+        NumpadMultiply,
+        NumpadParenLeft,
+        NumpadParenRight,
+        NumpadStar,
+        NumpadSubtract,
+        Escape,
+        Fn,
+        FnLock,
+        PrintScreen,
+        ScrollLock,
+        Pause,
+        BrowserBack,
+        BrowserFavorites,
+        BrowserForward,
+        BrowserHome,
+        BrowserRefresh,
+        BrowserSearch,
+        BrowserStop,
+        Eject,
+        LaunchApp1,
+        LaunchApp2,
+        LaunchMail,
+        MediaPlayPause,
+        MediaSelect,
+        MediaStop,
+        MediaTrackNext,
+        MediaTrackPrevious,
+        Power,
+        Sleep,
+        AudioVolumeDown,
+        AudioVolumeMute,
+        AudioVolumeUp,
+        WakeUp,
+        Meta,
+        Hyper,
+        Turbo,
+        Abort,
+        Resume,
+        Suspend,
+        Again,
+        Copy,
+        Cut,
+        Find,
+        Open,
+        Paste,
+        Props,
+        Select,
+        Undo,
+        Hiragana,
+        Katakana,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        F25,
+        F26,
+        F27,
+        F28,
+        F29,
+        F30,
+        F31,
+        F32,
+        F33,
+        F34,
+        F35,
+    ]
+);
+
+/// Parses the name of a logical key. Named keys use the names of winit's `NamedKey`, characters
+/// are given as is. Returns `None` if the name isn't a key.
+pub fn str_to_key(key: &str) -> Option<Key> {
+    let named = match key {
+        "Left" => Some(NamedKey::ArrowLeft),
+        "Up" => Some(NamedKey::ArrowUp),
+        "Right" => Some(NamedKey::ArrowRight),
+        "Down" => Some(NamedKey::ArrowDown),
+        "Ctrl" => Some(NamedKey::Control),
+        _ => str_to_named_key(key),
+    };
+    if let Some(named) = named {
+        return Some(Key::Named(named));
+    }
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(Key::Character(c.to_lowercase().to_string().into())),
+        _ => None,
+    }
+}
+
+/// Parses either a logical key or, when prefixed with `Code:`, a physical key such as
+/// `Code:KeyW`
+pub fn parse_key(key: &str) -> Option<KeyInput> {
+    match key.strip_prefix("Code:") {
+        Some(code) => str_to_key_code(code).map(KeyInput::Physical),
+        None => str_to_key(key).map(KeyInput::Logical),
+    }
+}
+
+/// Characters are stored in lowercase so that releasing shift before the key can't leave the key
+/// stuck
+pub fn normalize_key(key: Key) -> Key {
+    match key {
+        Key::Character(c) => Key::Character(c.to_lowercase().into()),
+        key => key,
+    }
+}
+
+pub fn physical_key_code(key: PhysicalKey) -> Option<KeyCode> {
+    match key {
+        PhysicalKey::Code(code) => Some(code),
+        PhysicalKey::Unidentified(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(str_to_key("Shift"), Some(Key::Named(NamedKey::Shift)));
+        assert_eq!(str_to_key("F12"), Some(Key::Named(NamedKey::F12)));
+        assert_eq!(str_to_key("Left"), Some(Key::Named(NamedKey::ArrowLeft)));
+        assert_eq!(str_to_key("W"), Some(Key::Character("w".into())));
+        assert_eq!(str_to_key("ä"), Some(Key::Character("ä".into())));
+        assert_eq!(str_to_key(""), None);
+        assert_eq!(str_to_key("Shfit"), None);
+        assert_eq!(
+            parse_key("Code:KeyW"),
+            Some(KeyInput::Physical(KeyCode::KeyW))
+        );
+        assert_eq!(parse_key("Code:W"), None);
+    }
+}
@@ -1,58 +1,449 @@
-use winit::keyboard::{Key, NamedKey};
+use crate::rendering::core::CameraState;
+use crate::util::random::Random;
+use gamepad::{GamepadBackend, GamepadEvent, GamepadId, GamepadState};
+use keyboard::{KeyInput, TextInput};
+use winit::event::{Ime, MouseButton, TouchPhase};
+use winit::keyboard::{Key, KeyCode, PhysicalKey};
 
+pub mod actions;
+pub mod gamepad;
+pub mod keyboard;
 pub mod mouse;
+pub mod recording;
+pub mod touch;
+
+/// A single change to the input state, positions are normalized to the window size
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Key {
+        logical_key: Key,
+        physical_key: PhysicalKey,
+        pressed: bool,
+        text: Option<String>,
+    },
+    CursorMoved((f32, f32)),
+    MouseButton(MouseButton, bool),
+    /// Scrolled distance in lines
+    MouseWheel((f32, f32)),
+    Touch(u64, (f32, f32), TouchPhase),
+    Ime(Ime),
+    Gamepad(GamepadEvent),
+}
 
 pub struct InputState {
+    pub actions: actions::ActionMap,
     pub mouse: mouse::MouseState,
+    pub touches: Vec<touch::Touch>,
+    pub gamepads: Vec<GamepadState>,
+    /// Gamepads that were connected this frame
+    pub connected_gamepads: Vec<GamepadId>,
+    /// Gamepads that were disconnected this frame
+    pub disconnected_gamepads: Vec<GamepadId>,
+    pub dead_zone: f32,
     pub down_keys: Vec<Key>,
     pub new_keys: Vec<Key>,
     pub up_keys: Vec<Key>,
+    pub down_codes: Vec<KeyCode>,
+    pub new_codes: Vec<KeyCode>,
+    pub up_codes: Vec<KeyCode>,
+    /// Text typed this frame, in the order it was typed
+    pub text_input: Vec<TextInput>,
+    /// The text currently being composed in an IME
+    pub composition: String,
+    /// Whether scripts want to receive IME text input, synced to the window each frame
+    pub text_input_enabled: bool,
+    ime_active: bool,
+    /// The time since the first frame, in seconds. Advanced by the engine so that replays can
+    /// drive it.
+    pub time: f64,
+    /// The duration of the current frame, in seconds
+    pub delta_time: f32,
+    pub recorder: Option<recording::InputRecorder>,
+    /// While set, input comes from the replay and live events are ignored
+    pub replay: Option<recording::InputReplay>,
+    /// The streams of the `rand` script module. They live next to the input so that replays
+    /// can restore the seed they were recorded with.
+    pub random: Random,
 }
 
 impl InputState {
     pub fn new() -> InputState {
         InputState {
-            mouse: mouse::MouseState {
-                position: (0.0, 0.0),
-                left_button: false,
-                right_button: false,
-                middle_button: false,
-                drag_start: None,
-                last_position: None,
-            },
+            actions: actions::ActionMap::default(),
+            mouse: mouse::MouseState::new(),
+            touches: Vec::new(),
+            gamepads: Vec::new(),
+            connected_gamepads: Vec::new(),
+            disconnected_gamepads: Vec::new(),
+            dead_zone: gamepad::DEFAULT_DEAD_ZONE,
             down_keys: Vec::new(),
             new_keys: Vec::new(),
             up_keys: Vec::new(),
+            down_codes: Vec::new(),
+            new_codes: Vec::new(),
+            up_codes: Vec::new(),
+            text_input: Vec::new(),
+            composition: String::new(),
+            text_input_enabled: false,
+            ime_active: false,
+            time: 0.0,
+            delta_time: 0.0,
+            recorder: None,
+            replay: None,
+            random: Random::default(),
         }
     }
 
-    pub fn get_key(&self, key: Key) -> bool {
-        self.down_keys.contains(&key)
+    /// Seeds the random streams before the scene is loaded. Replays keep the seed they were
+    /// recorded with.
+    pub fn seed_random(&mut self, seed: u64) {
+        let seed = match &self.replay {
+            Some(replay) => replay.seed(),
+            None => seed,
+        };
+        self.random.reseed(seed);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.set_seed(seed);
+        }
     }
 
-    pub fn get_key_down(&self, key: Key) -> bool {
-        self.new_keys.contains(&key)
+    /// Replaces live input with the recording, starting from the next frame
+    pub fn start_replay(&mut self, recording: recording::InputRecording) {
+        self.random.reseed(recording.seed);
+        self.replay = Some(recording::InputReplay::new(recording));
     }
 
-    pub fn get_key_up(&self, key: Key) -> bool {
-        self.up_keys.contains(&key)
+    /// Applies a live input event, recording it if a recording is in progress
+    pub fn process_event(&mut self, event: InputEvent) {
+        if self.replay.is_some() {
+            return;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&event);
+        }
+        self.apply_event(event);
+    }
+
+    fn apply_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key {
+                logical_key,
+                physical_key,
+                pressed,
+                text,
+            } => self.process_key(logical_key, physical_key, pressed, text.as_deref()),
+            InputEvent::CursorMoved(position) => {
+                self.mouse.last_position = Some(self.mouse.position);
+                self.mouse.position = position;
+            }
+            InputEvent::MouseButton(button, pressed) => self.mouse.set_button(button, pressed),
+            InputEvent::MouseWheel(delta) => self.mouse.add_wheel_delta(delta),
+            InputEvent::Touch(id, position, phase) => self.update_touch(id, position, phase),
+            InputEvent::Ime(ime) => self.process_ime(ime),
+            InputEvent::Gamepad(event) => self.process_gamepad_event(event),
+        }
+    }
+
+    /// Starts a new frame that took `delta` seconds. When replaying, the recorded events and
+    /// delta are used instead.
+    pub fn begin_frame(&mut self, delta: f32) {
+        let mut delta = delta;
+        if let Some(replay) = &mut self.replay {
+            match replay.next_frame() {
+                Some((recorded_delta, events)) => {
+                    delta = recorded_delta;
+                    for event in events {
+                        self.apply_event(event);
+                    }
+                }
+                None => {
+                    crate::log!("Input replay finished");
+                    self.replay = None;
+                }
+            }
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.end_frame(delta);
+        }
+        self.delta_time = delta;
+        self.time += delta as f64;
+    }
+
+    fn process_key(
+        &mut self,
+        logical_key: Key,
+        physical_key: PhysicalKey,
+        pressed: bool,
+        text: Option<&str>,
+    ) {
+        let key = keyboard::normalize_key(logical_key);
+        let code = keyboard::physical_key_code(physical_key);
+        if pressed {
+            if !self.down_keys.contains(&key) {
+                self.down_keys.push(key.clone());
+                self.new_keys.push(key);
+            }
+            if let Some(code) = code {
+                if !self.down_codes.contains(&code) {
+                    self.down_codes.push(code);
+                    self.new_codes.push(code);
+                }
+            }
+            // while an IME is active the text arrives through it instead
+            if !self.ime_active {
+                let text: String = text
+                    .unwrap_or_default()
+                    .chars()
+                    .filter(|c| !c.is_control())
+                    .collect();
+                if !text.is_empty() {
+                    self.text_input.push(TextInput::Commit(text));
+                }
+            }
+        } else {
+            self.down_keys.retain(|x| *x != key);
+            self.up_keys.push(key);
+            if let Some(code) = code {
+                self.down_codes.retain(|x| *x != code);
+                self.up_codes.push(code);
+            }
+        }
+    }
+
+    fn process_ime(&mut self, ime: Ime) {
+        match ime {
+            Ime::Enabled => self.ime_active = true,
+            Ime::Preedit(text, _) => {
+                self.composition = text.clone();
+                self.text_input.push(TextInput::Preedit(text));
+            }
+            Ime::Commit(text) => {
+                self.composition.clear();
+                self.text_input.push(TextInput::Commit(text));
+            }
+            Ime::Disabled => {
+                self.ime_active = false;
+                if !self.composition.is_empty() {
+                    self.composition.clear();
+                    self.text_input.push(TextInput::Preedit(String::new()));
+                }
+            }
+        }
+    }
+
+    /// Returns the text that was committed this frame
+    pub fn get_text(&self) -> String {
+        self.text_input
+            .iter()
+            .filter_map(|input| match input {
+                TextInput::Commit(text) => Some(text.as_str()),
+                TextInput::Preedit(_) => None,
+            })
+            .collect()
+    }
+
+    fn update_touch(&mut self, id: u64, position: (f32, f32), phase: TouchPhase) {
+        match self.touches.iter_mut().find(|touch| touch.id == id) {
+            Some(touch) => {
+                touch.position = position;
+                // a touch that started this frame should still be seen as started by the scripts
+                match phase {
+                    TouchPhase::Moved if touch.phase == TouchPhase::Started => {}
+                    TouchPhase::Ended | TouchPhase::Cancelled
+                        if touch.phase == TouchPhase::Started =>
+                    {
+                        touch.pending_end = Some(phase)
+                    }
+                    phase => touch.phase = phase,
+                }
+            }
+            None => self.touches.push(touch::Touch {
+                id,
+                position,
+                world_position: (0.0, 0.0),
+                phase,
+                pending_end: None,
+            }),
+        }
+    }
+
+    pub fn get_gamepad(&self, id: GamepadId) -> Option<&GamepadState> {
+        self.gamepads.iter().find(|gamepad| gamepad.id == id)
+    }
+
+    pub fn poll_gamepads(&mut self, backend: &mut dyn GamepadBackend) {
+        for event in backend.poll() {
+            self.process_event(InputEvent::Gamepad(event));
+        }
+    }
+
+    fn process_gamepad_event(&mut self, event: GamepadEvent) {
+        let id = match &event {
+            GamepadEvent::Connected(id)
+            | GamepadEvent::Disconnected(id)
+            | GamepadEvent::ButtonPressed(id, _)
+            | GamepadEvent::ButtonReleased(id, _)
+            | GamepadEvent::AxisChanged(id, _, _) => *id,
+        };
+        if let GamepadEvent::Disconnected(_) = event {
+            self.gamepads.retain(|gamepad| gamepad.id != id);
+            self.disconnected_gamepads.push(id);
+            return;
+        }
+        let gamepad = match self.gamepads.iter().position(|gamepad| gamepad.id == id) {
+            Some(index) => &mut self.gamepads[index],
+            None => {
+                // some backends report input before the connect event
+                self.connected_gamepads.push(id);
+                self.gamepads.push(GamepadState::new(id));
+                self.gamepads.last_mut().unwrap()
+            }
+        };
+        match event {
+            GamepadEvent::ButtonPressed(_, button) => {
+                if !gamepad.down_buttons.contains(&button) {
+                    gamepad.down_buttons.push(button);
+                    gamepad.new_buttons.push(button);
+                }
+            }
+            GamepadEvent::ButtonReleased(_, button) => {
+                gamepad.down_buttons.retain(|x| *x != button);
+                gamepad.up_buttons.push(button);
+            }
+            GamepadEvent::AxisChanged(_, axis, value) => gamepad.set_axis(axis, value),
+            GamepadEvent::Connected(_) | GamepadEvent::Disconnected(_) => {}
+        }
     }
-}
 
-pub fn str_to_key(key: &str) -> Option<Key> {
-    match key {
-        "Space" => Some(Key::Named(NamedKey::Space)),
-        "Escape" => Some(Key::Named(NamedKey::Escape)),
-        "Backspace" => Some(Key::Named(NamedKey::Backspace)),
-        "Enter" => Some(Key::Named(NamedKey::Enter)),
-        "Tab" => Some(Key::Named(NamedKey::Tab)),
-        "Left" => Some(Key::Named(NamedKey::ArrowLeft)),
-        "Up" => Some(Key::Named(NamedKey::ArrowUp)),
-        "Right" => Some(Key::Named(NamedKey::ArrowRight)),
-        "Down" => Some(Key::Named(NamedKey::ArrowDown)),
-        _ => {
-            let smol_str = key.chars().next().unwrap().to_string();
-            Some(Key::Character(smol_str.to_ascii_lowercase().into()))
+    /// Updates the world space positions of the cursor and touches, called before scripts run
+    pub fn update_world_positions(&mut self, camera: &CameraState) {
+        self.mouse.world_position = self.mouse.get_world_position(camera);
+        for touch in &mut self.touches {
+            touch.world_position = touch.get_world_position(camera);
         }
     }
+
+    /// Clears everything that should only last for a single frame
+    pub fn end_frame(&mut self) {
+        self.new_keys.clear();
+        self.up_keys.clear();
+        self.new_codes.clear();
+        self.up_codes.clear();
+        self.text_input.clear();
+        self.mouse.new_buttons.clear();
+        self.mouse.up_buttons.clear();
+        self.mouse.wheel_delta = (0.0, 0.0);
+        self.connected_gamepads.clear();
+        self.disconnected_gamepads.clear();
+        for gamepad in &mut self.gamepads {
+            gamepad.new_buttons.clear();
+            gamepad.up_buttons.clear();
+        }
+        self.touches.retain(|touch| {
+            touch.phase != TouchPhase::Ended && touch.phase != TouchPhase::Cancelled
+        });
+        for touch in &mut self.touches {
+            touch.phase = touch.pending_end.take().unwrap_or(TouchPhase::Moved);
+        }
+    }
+
+    pub fn action_pressed(&self, name: &str) -> bool {
+        self.actions.action_pressed(name, self)
+    }
+
+    pub fn action_down(&self, name: &str) -> bool {
+        self.actions.action_down(name, self)
+    }
+
+    pub fn action_up(&self, name: &str) -> bool {
+        self.actions.action_up(name, self)
+    }
+
+    pub fn axis(&self, name: &str) -> f32 {
+        self.actions.axis(name, self)
+    }
+
+    pub fn get_key(&self, key: &KeyInput) -> bool {
+        match key {
+            KeyInput::Logical(key) => self.down_keys.contains(key),
+            KeyInput::Physical(code) => self.down_codes.contains(code),
+        }
+    }
+
+    pub fn get_key_down(&self, key: &KeyInput) -> bool {
+        match key {
+            KeyInput::Logical(key) => self.new_keys.contains(key),
+            KeyInput::Physical(code) => self.new_codes.contains(code),
+        }
+    }
+
+    pub fn get_key_up(&self, key: &KeyInput) -> bool {
+        match key {
+            KeyInput::Logical(key) => self.up_keys.contains(key),
+            KeyInput::Physical(code) => self.up_codes.contains(code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phases(input_state: &InputState) -> Vec<(u64, TouchPhase)> {
+        input_state
+            .touches
+            .iter()
+            .map(|touch| (touch.id, touch.phase))
+            .collect()
+    }
+
+    #[test]
+    fn test_mouse() {
+        let mut input_state = InputState::new();
+        input_state.process_event(InputEvent::CursorMoved((0.25, 0.5)));
+        input_state.process_event(InputEvent::MouseButton(MouseButton::Left, true));
+        input_state.process_event(InputEvent::MouseWheel((0.0, 1.0)));
+        input_state.process_event(InputEvent::MouseWheel((0.0, 2.0)));
+        assert!(input_state.mouse.get_button(MouseButton::Left));
+        assert!(input_state.mouse.get_button_down(MouseButton::Left));
+        assert!(!input_state.mouse.get_button(MouseButton::Right));
+        assert_eq!(input_state.mouse.wheel_delta, (0.0, 3.0));
+        input_state.end_frame();
+
+        input_state.process_event(InputEvent::CursorMoved((0.5, 0.5)));
+        assert!(input_state.mouse.get_button(MouseButton::Left));
+        assert!(!input_state.mouse.get_button_down(MouseButton::Left));
+        assert_eq!(input_state.mouse.wheel_delta, (0.0, 0.0));
+        assert_eq!(input_state.mouse.get_delta(), (0.25, 0.0));
+        input_state.process_event(InputEvent::MouseButton(MouseButton::Left, false));
+        assert!(!input_state.mouse.get_button(MouseButton::Left));
+        assert!(input_state.mouse.get_button_up(MouseButton::Left));
+
+        assert_eq!(mouse::str_to_button("Middle"), Some(MouseButton::Middle));
+        assert_eq!(mouse::str_to_button("Thumb"), None);
+    }
+
+    #[test]
+    fn test_touches() {
+        let mut input_state = InputState::new();
+        input_state.process_event(InputEvent::Touch(1, (0.5, 0.5), TouchPhase::Started));
+        input_state.process_event(InputEvent::Touch(1, (0.6, 0.5), TouchPhase::Moved));
+        assert_eq!(phases(&input_state), vec![(1, TouchPhase::Started)]);
+        assert_eq!(input_state.touches[0].position, (0.6, 0.5));
+        input_state.end_frame();
+        assert_eq!(phases(&input_state), vec![(1, TouchPhase::Moved)]);
+        input_state.process_event(InputEvent::Touch(1, (0.6, 0.5), TouchPhase::Ended));
+        assert_eq!(phases(&input_state), vec![(1, TouchPhase::Ended)]);
+        input_state.end_frame();
+        assert!(input_state.touches.is_empty());
+
+        // a tap that starts and ends within a frame is seen starting and then ending
+        input_state.process_event(InputEvent::Touch(2, (0.1, 0.1), TouchPhase::Started));
+        input_state.process_event(InputEvent::Touch(2, (0.1, 0.1), TouchPhase::Ended));
+        assert_eq!(phases(&input_state), vec![(2, TouchPhase::Started)]);
+        input_state.end_frame();
+        assert_eq!(phases(&input_state), vec![(2, TouchPhase::Ended)]);
+        input_state.end_frame();
+        assert!(input_state.touches.is_empty());
+    }
 }
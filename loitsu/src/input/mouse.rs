@@ -1,15 +1,42 @@
 use crate::{rendering::core::CameraState, util::scaling};
+use winit::event::MouseButton;
+
+/// The amount of pixels that are treated as one line when scrolling with a touchpad
+const PIXELS_PER_LINE: f32 = 20.0;
 
 pub struct MouseState {
     pub position: (f32, f32),
+    /// The position of the cursor in world space, updated at the start of each frame
+    pub world_position: (f32, f32),
     pub left_button: bool,
     pub right_button: bool,
     pub middle_button: bool,
+    /// Buttons that were pressed this frame
+    pub new_buttons: Vec<MouseButton>,
+    /// Buttons that were released this frame
+    pub up_buttons: Vec<MouseButton>,
+    /// The distance scrolled this frame, in lines
+    pub wheel_delta: (f32, f32),
     pub drag_start: Option<(f32, f32)>,
     pub last_position: Option<(f32, f32)>,
 }
 
 impl MouseState {
+    pub fn new() -> MouseState {
+        MouseState {
+            position: (0.0, 0.0),
+            world_position: (0.0, 0.0),
+            left_button: false,
+            right_button: false,
+            middle_button: false,
+            new_buttons: Vec::new(),
+            up_buttons: Vec::new(),
+            wheel_delta: (0.0, 0.0),
+            drag_start: None,
+            last_position: None,
+        }
+    }
+
     pub fn get_world_position(&self, camera: &CameraState) -> (f32, f32) {
         scaling::as_world_pos(camera, (self.position.0, self.position.1))
     }
@@ -23,4 +50,69 @@ impl MouseState {
             None => (0.0, 0.0),
         }
     }
+
+    pub fn get_button(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Left => self.left_button,
+            MouseButton::Right => self.right_button,
+            MouseButton::Middle => self.middle_button,
+            _ => false,
+        }
+    }
+
+    pub fn get_button_down(&self, button: MouseButton) -> bool {
+        self.new_buttons.contains(&button)
+    }
+
+    pub fn get_button_up(&self, button: MouseButton) -> bool {
+        self.up_buttons.contains(&button)
+    }
+
+    pub fn set_button(&mut self, button: MouseButton, pressed: bool) {
+        match button {
+            MouseButton::Left => self.left_button = pressed,
+            MouseButton::Right => self.right_button = pressed,
+            MouseButton::Middle => self.middle_button = pressed,
+            _ => {}
+        }
+        if pressed {
+            self.new_buttons.push(button);
+        } else {
+            self.up_buttons.push(button);
+        }
+    }
+
+    /// Adds to the distance scrolled this frame, in lines
+    pub fn add_wheel_delta(&mut self, delta: (f32, f32)) {
+        self.wheel_delta.0 += delta.0;
+        self.wheel_delta.1 += delta.1;
+    }
+}
+
+/// Converts a scroll delta to lines, touchpads report theirs in pixels
+pub fn scroll_delta_lines(delta: winit::event::MouseScrollDelta) -> (f32, f32) {
+    match delta {
+        winit::event::MouseScrollDelta::LineDelta(x, y) => (x, y),
+        winit::event::MouseScrollDelta::PixelDelta(pos) => (
+            pos.x as f32 / PIXELS_PER_LINE,
+            pos.y as f32 / PIXELS_PER_LINE,
+        ),
+    }
+}
+
+impl Default for MouseState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn str_to_button(button: &str) -> Option<MouseButton> {
+    match button {
+        "Left" => Some(MouseButton::Left),
+        "Right" => Some(MouseButton::Right),
+        "Middle" => Some(MouseButton::Middle),
+        "Back" => Some(MouseButton::Back),
+        "Forward" => Some(MouseButton::Forward),
+        _ => None,
+    }
 }
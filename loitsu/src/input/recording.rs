@@ -0,0 +1,370 @@
+use super::gamepad::GamepadEvent;
+use super::keyboard::{key_code_to_str, named_key_to_str, str_to_key_code, str_to_named_key};
+use super::{InputEvent, InputState};
+use std::collections::VecDeque;
+use winit::event::{Ime, MouseButton, TouchPhase};
+use winit::keyboard::{Key, NativeKey, NativeKeyCode, PhysicalKey};
+
+/// Bumped whenever the format of the recorded events changes
+const RECORDING_VERSION: u32 = 3;
+
+/// Every input event of a play session grouped by frame, along with the time each frame took
+#[derive(bitcode::Encode, bitcode::Decode)]
+pub struct InputRecording {
+    version: u32,
+    /// The seed of the random streams when the recording started
+    pub seed: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+#[derive(Clone, bitcode::Encode, bitcode::Decode)]
+pub struct RecordedFrame {
+    /// The time since the previous frame, in seconds
+    pub delta: f32,
+    /// The events that happened before this frame
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Named keys and key codes are stored by the names scripts use for them
+#[derive(Debug, Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
+pub enum RecordedKey {
+    Named(String),
+    Character(String),
+    Unidentified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, bitcode::Encode, bitcode::Decode)]
+pub enum RecordedMouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+/// An encodable version of `InputEvent`
+#[derive(Debug, Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
+pub enum RecordedEvent {
+    Key {
+        key: RecordedKey,
+        code: Option<String>,
+        pressed: bool,
+        text: Option<String>,
+    },
+    CursorMoved(f32, f32),
+    MouseButton(RecordedMouseButton, bool),
+    MouseWheel(f32, f32),
+    Touch(u64, f32, f32, u8),
+    ImeEnabled,
+    ImePreedit(String),
+    ImeCommit(String),
+    ImeDisabled,
+    Gamepad(GamepadEvent),
+}
+
+impl InputRecording {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bitcode::encode(self).unwrap()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<InputRecording, String> {
+        let recording: InputRecording =
+            bitcode::decode(data).map_err(|e| format!("Invalid input recording: {}", e))?;
+        if recording.version != RECORDING_VERSION {
+            return Err(format!(
+                "The input recording is from an incompatible version ({}, expected {})",
+                recording.version, RECORDING_VERSION
+            ));
+        }
+        Ok(recording)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<InputRecording, String> {
+        let data = std::fs::read(path).map_err(|e| {
+            format!(
+                "Couldn't read the input recording '{}': {}",
+                path.display(),
+                e
+            )
+        })?;
+        InputRecording::from_bytes(&data)
+    }
+}
+
+/// Collects input events as they happen
+#[derive(Default)]
+pub struct InputRecorder {
+    seed: u64,
+    frames: Vec<RecordedFrame>,
+    pending: Vec<RecordedEvent>,
+}
+
+impl InputRecorder {
+    pub fn new() -> InputRecorder {
+        InputRecorder::default()
+    }
+
+    pub fn record(&mut self, event: &InputEvent) {
+        self.pending.push(RecordedEvent::from(event));
+    }
+
+    /// Closes the current frame, the pending events are replayed right before it
+    pub fn end_frame(&mut self, delta: f32) {
+        self.frames.push(RecordedFrame {
+            delta,
+            events: std::mem::take(&mut self.pending),
+        });
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn finish(self) -> InputRecording {
+        InputRecording {
+            version: RECORDING_VERSION,
+            seed: self.seed,
+            frames: self.frames,
+        }
+    }
+}
+
+/// Hands out the frames of a recording one at a time
+pub struct InputReplay {
+    seed: u64,
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> InputReplay {
+        InputReplay {
+            seed: recording.seed,
+            frames: recording.frames.into(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the delta and events of the next frame, or `None` once the recording has ended
+    pub fn next_frame(&mut self) -> Option<(f32, Vec<InputEvent>)> {
+        let frame = self.frames.pop_front()?;
+        Some((
+            frame.delta,
+            frame.events.into_iter().map(InputEvent::from).collect(),
+        ))
+    }
+
+    pub fn remaining_frames(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Starts recording when `LOITSU_RECORD` is set and replaying when `LOITSU_REPLAY` is set, both
+/// pointing to a recording file. Returns the path the recording should be saved to.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn setup_from_env(input_state: &mut InputState) -> Option<std::path::PathBuf> {
+    if let Some(path) = std::env::var_os("LOITSU_REPLAY") {
+        match InputRecording::load(std::path::Path::new(&path)) {
+            Ok(recording) => {
+                crate::log!("Replaying {} frames of input", recording.frames.len());
+                input_state.start_replay(recording);
+            }
+            Err(e) => crate::error!("{}", e),
+        }
+    }
+    let path = std::path::PathBuf::from(std::env::var_os("LOITSU_RECORD")?);
+    crate::log!("Recording input to '{}'", path.display());
+    input_state.recorder = Some(InputRecorder::new());
+    Some(path)
+}
+
+/// Stops the recording in progress and writes it to `path`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_recording(input_state: &mut InputState, path: &std::path::Path) {
+    let Some(recorder) = input_state.recorder.take() else {
+        return;
+    };
+    let frames = recorder.frame_count();
+    match recorder.finish().save(path) {
+        Ok(()) => crate::log!("Saved {} frames of input to '{}'", frames, path.display()),
+        Err(e) => crate::error!("Couldn't save the input recording: {}", e),
+    }
+}
+
+impl From<&InputEvent> for RecordedEvent {
+    fn from(event: &InputEvent) -> Self {
+        match event {
+            InputEvent::Key {
+                logical_key,
+                physical_key,
+                pressed,
+                text,
+            } => RecordedEvent::Key {
+                key: match logical_key {
+                    Key::Named(named) => match named_key_to_str(*named) {
+                        Some(name) => RecordedKey::Named(name.to_string()),
+                        None => RecordedKey::Unidentified,
+                    },
+                    Key::Character(c) => RecordedKey::Character(c.to_string()),
+                    _ => RecordedKey::Unidentified,
+                },
+                code: match physical_key {
+                    PhysicalKey::Code(code) => key_code_to_str(*code).map(str::to_string),
+                    PhysicalKey::Unidentified(_) => None,
+                },
+                pressed: *pressed,
+                text: text.clone(),
+            },
+            InputEvent::CursorMoved(position) => RecordedEvent::CursorMoved(position.0, position.1),
+            InputEvent::MouseButton(button, pressed) => {
+                let button = match button {
+                    MouseButton::Left => RecordedMouseButton::Left,
+                    MouseButton::Right => RecordedMouseButton::Right,
+                    MouseButton::Middle => RecordedMouseButton::Middle,
+                    MouseButton::Back => RecordedMouseButton::Back,
+                    MouseButton::Forward => RecordedMouseButton::Forward,
+                    MouseButton::Other(other) => RecordedMouseButton::Other(*other),
+                };
+                RecordedEvent::MouseButton(button, *pressed)
+            }
+            InputEvent::MouseWheel(delta) => RecordedEvent::MouseWheel(delta.0, delta.1),
+            InputEvent::Touch(id, position, phase) => {
+                let phase = match phase {
+                    TouchPhase::Started => 0,
+                    TouchPhase::Moved => 1,
+                    TouchPhase::Ended => 2,
+                    TouchPhase::Cancelled => 3,
+                };
+                RecordedEvent::Touch(*id, position.0, position.1, phase)
+            }
+            InputEvent::Ime(ime) => match ime {
+                Ime::Enabled => RecordedEvent::ImeEnabled,
+                Ime::Preedit(text, _) => RecordedEvent::ImePreedit(text.clone()),
+                Ime::Commit(text) => RecordedEvent::ImeCommit(text.clone()),
+                Ime::Disabled => RecordedEvent::ImeDisabled,
+            },
+            InputEvent::Gamepad(event) => RecordedEvent::Gamepad(event.clone()),
+        }
+    }
+}
+
+impl From<RecordedEvent> for InputEvent {
+    fn from(event: RecordedEvent) -> Self {
+        match event {
+            RecordedEvent::Key {
+                key,
+                code,
+                pressed,
+                text,
+            } => InputEvent::Key {
+                logical_key: match key {
+                    RecordedKey::Named(name) => match str_to_named_key(&name) {
+                        Some(named) => Key::Named(named),
+                        None => Key::Unidentified(NativeKey::Unidentified),
+                    },
+                    RecordedKey::Character(c) => Key::Character(c.into()),
+                    RecordedKey::Unidentified => Key::Unidentified(NativeKey::Unidentified),
+                },
+                physical_key: match code.as_deref().and_then(str_to_key_code) {
+                    Some(code) => PhysicalKey::Code(code),
+                    None => PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+                },
+                pressed,
+                text,
+            },
+            RecordedEvent::CursorMoved(x, y) => InputEvent::CursorMoved((x, y)),
+            RecordedEvent::MouseButton(button, pressed) => {
+                let button = match button {
+                    RecordedMouseButton::Left => MouseButton::Left,
+                    RecordedMouseButton::Right => MouseButton::Right,
+                    RecordedMouseButton::Middle => MouseButton::Middle,
+                    RecordedMouseButton::Back => MouseButton::Back,
+                    RecordedMouseButton::Forward => MouseButton::Forward,
+                    RecordedMouseButton::Other(other) => MouseButton::Other(other),
+                };
+                InputEvent::MouseButton(button, pressed)
+            }
+            RecordedEvent::MouseWheel(x, y) => InputEvent::MouseWheel((x, y)),
+            RecordedEvent::Touch(id, x, y, phase) => {
+                let phase = match phase {
+                    0 => TouchPhase::Started,
+                    1 => TouchPhase::Moved,
+                    2 => TouchPhase::Ended,
+                    _ => TouchPhase::Cancelled,
+                };
+                InputEvent::Touch(id, (x, y), phase)
+            }
+            RecordedEvent::ImeEnabled => InputEvent::Ime(Ime::Enabled),
+            RecordedEvent::ImePreedit(text) => InputEvent::Ime(Ime::Preedit(text, None)),
+            RecordedEvent::ImeCommit(text) => InputEvent::Ime(Ime::Commit(text)),
+            RecordedEvent::ImeDisabled => InputEvent::Ime(Ime::Disabled),
+            RecordedEvent::Gamepad(event) => InputEvent::Gamepad(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::keyboard::KeyInput;
+    use winit::keyboard::{KeyCode, NamedKey};
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut input_state = InputState::new();
+        input_state.recorder = Some(InputRecorder::new());
+        input_state.seed_random(5);
+        input_state.process_event(InputEvent::Key {
+            logical_key: Key::Named(NamedKey::Space),
+            physical_key: PhysicalKey::Code(KeyCode::Space),
+            pressed: true,
+            text: Some(" ".to_string()),
+        });
+        input_state.process_event(InputEvent::CursorMoved((0.25, 0.5)));
+        input_state.begin_frame(0.016);
+        input_state.end_frame();
+        input_state.process_event(InputEvent::MouseButton(MouseButton::Left, true));
+        input_state.process_event(InputEvent::MouseButton(MouseButton::Other(0), true));
+        input_state.begin_frame(0.02);
+        let recording = input_state.recorder.take().unwrap().finish();
+        let recording = InputRecording::from_bytes(&recording.to_bytes()).unwrap();
+
+        let mut replayed = InputState::new();
+        replayed.start_replay(recording);
+        // the replay keeps its own seed
+        replayed.seed_random(6);
+        assert_eq!(replayed.random.seed(), 5);
+        // live input is ignored while replaying
+        replayed.process_event(InputEvent::MouseWheel((1.0, 0.0)));
+        replayed.begin_frame(1.0);
+        let space = KeyInput::Logical(Key::Named(NamedKey::Space));
+        assert!(replayed.get_key_down(&space));
+        assert!(replayed.get_key_down(&KeyInput::Physical(KeyCode::Space)));
+        assert_eq!(replayed.get_text(), " ");
+        assert_eq!(replayed.mouse.position, (0.25, 0.5));
+        assert_eq!(replayed.mouse.wheel_delta, (0.0, 0.0));
+        assert_eq!(replayed.delta_time, 0.016);
+
+        replayed.end_frame();
+        replayed.begin_frame(1.0);
+        assert!(replayed.get_key(&space));
+        assert!(replayed.mouse.get_button_down(MouseButton::Left));
+        assert!(replayed.mouse.get_button_down(MouseButton::Other(0)));
+        assert!(!replayed.mouse.get_button(MouseButton::Back));
+        assert!((replayed.time - 0.036).abs() < 1e-6);
+        assert!(replayed.replay.as_ref().unwrap().remaining_frames() == 0);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{rendering::core::CameraState, util::scaling};
+use winit::event::TouchPhase;
+
+pub struct Touch {
+    pub id: u64,
+    /// The position of the touch in screen space, from 0 to 1
+    pub position: (f32, f32),
+    /// The position of the touch in world space, updated at the start of each frame
+    pub world_position: (f32, f32),
+    /// `Started` and `Ended` only last for the frame the touch started or ended on, after that a
+    /// touch is `Moved` until it ends
+    pub phase: TouchPhase,
+    /// An end that arrived on the frame the touch started, it is reported on the next frame so
+    /// that the start isn't missed
+    pub pending_end: Option<TouchPhase>,
+}
+
+impl Touch {
+    pub fn get_world_position(&self, camera: &CameraState) -> (f32, f32) {
+        scaling::as_world_pos(camera, self.position)
+    }
+}
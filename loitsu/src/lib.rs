@@ -2,20 +2,37 @@ pub mod asset_management;
 pub mod ecs;
 #[cfg(feature = "editor")]
 pub mod editor;
+pub mod engine;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
 pub mod input;
 pub mod logging;
 pub mod rendering;
 pub mod scene_management;
 pub mod scripting;
+pub mod storage;
 pub mod util;
+pub use engine::{Engine, Plugin};
 use scripting::ScriptingInstance;
 #[allow(unused_imports)]
 use std::sync::{Arc, Mutex, RwLock};
 
 #[cfg_attr(feature = "json_preference_parse", derive(serde::Deserialize))]
-#[derive(Clone, bitcode::Decode, bitcode::Encode)]
+#[derive(Clone, Default, bitcode::Decode, bitcode::Encode)]
 pub struct Preferences {
     pub default_scene: String,
+    /// Named input actions and axes that scripts can query instead of specific keys
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub input: input::actions::InputPreferences,
+    /// Seeds the `rand` script module, a new seed is picked on every run if this isn't set
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub random_seed: Option<u64>,
+    /// How many instructions component methods may run, per call and per frame, before they are
+    /// stopped
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub script_budget: scripting::ScriptBudget,
+    #[cfg_attr(feature = "json_preference_parse", serde(default))]
+    pub draw_sorting: rendering::drawable::order::DrawSorting,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -26,12 +43,7 @@ pub fn build_scenes(
     scenes: Vec<(String, String)>,
     scripts: Vec<scripting::ScriptingSource>,
 ) -> Vec<scene_management::Scene> {
-    #[cfg(not(feature = "editor"))]
-    let e = Arc::new(RwLock::new(ecs::ECS::new()));
-    #[cfg(feature = "editor")]
-    let e = Arc::new(RwLock::new(ecs::ECS::new(std::sync::Arc::new(
-        std::sync::Mutex::new(editor::EventHandler::new()),
-    ))));
+    let e = build_ecs();
     let mut rune =
         scripting::rune_runtime::RuneInstance::new_with_sources(scripts, e.clone()).unwrap();
     let mut generated_scenes = Vec::new();
@@ -57,6 +69,26 @@ pub fn build_scenes(
     generated_scenes
 }
 
+/// Compiles the scripts so that they can be shipped in the static shard, logging the diagnostics
+/// of any errors
+#[cfg(feature = "scene_generation")]
+pub fn validate_scripts(
+    scripts: Vec<scripting::ScriptingSource>,
+) -> scripting::Result<scripting::ValidatedScripts> {
+    scripting::rune_runtime::RuneInstance::new_with_sources(scripts, build_ecs())?.validated()
+}
+
+#[cfg(feature = "scene_generation")]
+fn build_ecs() -> Arc<RwLock<ecs::ECS<scripting::rune_runtime::RuneInstance>>> {
+    #[cfg(not(feature = "editor"))]
+    let e = Arc::new(RwLock::new(ecs::ECS::new()));
+    #[cfg(feature = "editor")]
+    let e = Arc::new(RwLock::new(ecs::ECS::new(std::sync::Arc::new(
+        std::sync::Mutex::new(editor::EventHandler::new()),
+    ))));
+    e
+}
+
 #[cfg(target_arch = "wasm32")]
 #[cfg(feature = "editor")]
 pub fn load_scene_in_edit_mode(
@@ -81,42 +113,13 @@ pub fn load_scene_in_edit_mode(
     #[cfg(not(target_arch = "wasm32"))]
     {
         use rendering::desktop;
-        desktop::init_window(rune, e);
-    }
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        use rendering::web;
-        web::init_view(rune, e);
-    }
-}
-
-/// Initializes the core systems of loitsu.
-/// This function should be called before any other loitsu functions.
-#[cfg(not(feature = "editor"))]
-pub fn init_engine() {
-    #[cfg(target_arch = "wasm32")]
-    {
-        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-        console_log::init().expect("could not initialize logger");
-    }
-
-    log!("Loitsu core v{} starting up...", env!("CARGO_PKG_VERSION"));
-    let ecs = Arc::new(RwLock::new(
-        ecs::ECS::<scripting::rune_runtime::RuneInstance>::new(),
-    ));
-    let rune = scripting::rune_runtime::RuneInstance::new_uninitialized().unwrap();
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        use rendering::desktop;
-        desktop::init_window(rune, ecs);
+        desktop::init_window(rune, e, engine::EngineOptions::default());
     }
 
     #[cfg(target_arch = "wasm32")]
     {
         use rendering::web;
-        web::init_view(rune, ecs);
+        web::init_view(rune, e, engine::EngineOptions::default());
     }
 }
 
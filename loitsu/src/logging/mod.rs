@@ -94,6 +94,26 @@ pub fn error(s: &str) {
     }
 }
 
+/// Logs a scripting error along with its location and stack
+pub fn script_error(e: &crate::scripting::ScriptingError) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::console::error_1(&e.to_string().into());
+        // the web log and editor notifications are single line, so we only include the location
+        let summary = match e.location() {
+            Some(location) => format!("{} ({})", e.message(), location),
+            None => e.message().to_string(),
+        };
+        add_error(summary.clone());
+        #[cfg(feature = "editor")]
+        crate::web::notify(2, "Script error", &summary);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        eprintln!("{} {}", colorize("[LoiScript]", Color::Red), e);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn colorize(s: &str, color: Color) -> String {
     match color {
@@ -0,0 +1,656 @@
+use crate::ecs::{ComponentFlags, ECS};
+use crate::rendering::core::CameraState;
+use crate::rendering::drawable::{batch::SpriteBatcher, QUAD_INDICES, QUAD_VERTICES};
+use crate::rendering::shader::{Shader, ShaderManager};
+use crate::scene_management::Property;
+use crate::scripting::{native, Result, ScriptingData, ScriptingError, ScriptingInstance};
+use crate::util::scaling;
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Mutex;
+use wgpu::util::DeviceExt;
+
+lazy_static! {
+    /// The camera that scripts control. It drives the main camera of the scene, the one with the
+    /// highest priority, and is applied after the scripts of a frame have run.
+    pub static ref CAMERA: Mutex<Camera> = Mutex::new(Camera::new());
+}
+
+/// An entity the camera moves towards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Follow {
+    pub entity: u32,
+    /// Roughly the seconds it takes the camera to catch up with the entity, 0 snaps to it
+    pub smoothing: f32,
+}
+
+/// The area the camera may show, in world space
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+#[derive(Debug, Clone)]
+struct Shake {
+    intensity: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+pub struct Camera {
+    /// The center of the view in world space
+    pub position: (f32, f32),
+    pub zoom: f32,
+    /// In radians, counter-clockwise
+    pub rotation: f32,
+    pub follow: Option<Follow>,
+    /// Keeps the view inside of the given area. If the area is smaller than the view, the view
+    /// is centered on it instead.
+    pub bounds: Option<Bounds>,
+    shake: Option<Shake>,
+    shake_offset: (f32, f32),
+    // what was last applied to the renderer, used to convert between screen and world space
+    state: CameraState,
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            position: (0.0, 0.0),
+            zoom: 1.0,
+            rotation: 0.0,
+            follow: None,
+            bounds: None,
+            shake: None,
+            shake_offset: (0.0, 0.0),
+            state: CameraState::new(),
+        }
+    }
+
+    /// Moves the camera to the main camera of a newly loaded scene, forgetting everything
+    /// scripts did to it in the previous one
+    pub fn reset(&mut self, main_camera: Option<&SceneCamera>) {
+        *self = Camera::new();
+        if let Some(main_camera) = main_camera {
+            self.position = main_camera.position;
+            self.zoom = main_camera.settings.zoom;
+            self.rotation = main_camera.rotation;
+        }
+    }
+
+    /// Shakes the camera by up to `intensity` world units, fading out over `duration` seconds
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        self.shake = Some(Shake {
+            intensity,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Moves the camera towards the followed entity and keeps it within the bounds. `target` is
+    /// the world position of the followed entity, if it still exists.
+    pub fn update(&mut self, delta_time: f32, target: Option<(f32, f32)>) {
+        if let (Some(follow), Some(target)) = (&self.follow, target) {
+            let t = if follow.smoothing > 0.0 {
+                1.0 - (-delta_time / follow.smoothing).exp()
+            } else {
+                1.0
+            };
+            self.position.0 += (target.0 - self.position.0) * t;
+            self.position.1 += (target.1 - self.position.1) * t;
+        }
+
+        if let Some(bounds) = &self.bounds {
+            let mut state = self.state.clone();
+            state.scale = self.zoom;
+            let size = scaling::as_world_scale(&state, (state.viewport[2], state.viewport[3]));
+            self.position.0 = clamp_view(self.position.0, size.0, bounds.min.0, bounds.max.0);
+            self.position.1 = clamp_view(self.position.1, size.1, bounds.min.1, bounds.max.1);
+        }
+
+        self.shake_offset = (0.0, 0.0);
+        if let Some(shake) = &mut self.shake {
+            shake.elapsed += delta_time;
+            if shake.elapsed >= shake.duration {
+                self.shake = None;
+            } else {
+                let strength = shake.intensity * (1.0 - shake.elapsed / shake.duration);
+                // two frequencies that don't line up, so the shake doesn't look like a loop
+                self.shake_offset = (
+                    (shake.elapsed * 47.0).sin() * strength,
+                    (shake.elapsed * 61.0).cos() * strength,
+                );
+            }
+        }
+    }
+
+    /// Places the given state, which already has its viewport and size, where the camera is
+    fn apply(&mut self, state: &mut CameraState) {
+        state.set_world_position((
+            self.position.0 + self.shake_offset.0,
+            self.position.1 + self.shake_offset.1,
+        ));
+        state.scale = self.zoom;
+        state.rotation = self.rotation;
+        self.state = state.clone();
+    }
+
+    /// Places the cameras of the scene, ordered by priority, for the frame. The last one is the
+    /// main camera and is placed by this camera, the others by their entities. Without any
+    /// cameras in the scene a single one that covers the screen is used.
+    pub fn views(&mut self, cameras: Vec<SceneCamera>, size: (u32, u32)) -> Vec<CameraView> {
+        let mut cameras = cameras;
+        if cameras.is_empty() {
+            cameras.push(SceneCamera::default());
+        }
+        let main = cameras.len() - 1;
+        cameras
+            .into_iter()
+            .enumerate()
+            .map(|(i, camera)| {
+                let viewport = camera.settings.viewport;
+                let mut state = CameraState::new();
+                state.viewport = viewport;
+                state.set_size(size.0 as f32 * viewport[2], size.1 as f32 * viewport[3]);
+                if i == main {
+                    self.apply(&mut state);
+                } else {
+                    state.set_world_position(camera.position);
+                    state.scale = camera.settings.zoom;
+                    state.rotation = camera.rotation;
+                }
+                CameraView {
+                    state,
+                    clear_color: camera.settings.clear_color,
+                }
+            })
+            .collect()
+    }
+
+    /// Converts a position on the screen, (0, 0) being the top-left corner and (1, 1) the
+    /// bottom-right one, to world space
+    pub fn screen_to_world(&self, position: (f32, f32)) -> (f32, f32) {
+        scaling::as_world_pos(&self.state, position)
+    }
+
+    /// The inverse of `screen_to_world`
+    pub fn world_to_screen(&self, position: (f32, f32)) -> (f32, f32) {
+        scaling::as_screen_pos(&self.state, position)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
+/// Clamps the center of a view of the given size to the bounds on one axis
+fn clamp_view(center: f32, size: f32, min: f32, max: f32) -> f32 {
+    if max - min <= size {
+        (min + max) / 2.0
+    } else {
+        center.clamp(min + size / 2.0, max - size / 2.0)
+    }
+}
+
+/// The `Camera` component. Scenes can have any number of cameras, each one is drawn into its
+/// viewport in the order of their priorities. The position and rotation come from the entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraComponent {
+    pub zoom: f32,
+    /// Cameras with a higher priority are drawn on top, the highest one is the main camera
+    pub priority: f32,
+    /// The area of the screen that is drawn to: x, y, width and height from 0 to 1, (0, 0)
+    /// being the top-left corner
+    pub viewport: [f32; 4],
+    /// The viewport is filled with this before drawing, a transparent color keeps what the
+    /// cameras below drew
+    pub clear_color: [f32; 4],
+}
+
+impl Default for CameraComponent {
+    fn default() -> Self {
+        CameraComponent {
+            zoom: 1.0,
+            priority: 0.0,
+            viewport: [0.0, 0.0, 1.0, 1.0],
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl native::Component for CameraComponent {
+    fn new(properties: &BTreeMap<String, Property>) -> Self {
+        let mut camera = CameraComponent::default();
+        for (name, value) in properties {
+            if let Err(e) = camera.set_property(name, value.clone()) {
+                crate::error!("Invalid camera property: {}", e);
+            }
+        }
+        camera
+    }
+
+    fn methods() -> ComponentFlags {
+        ComponentFlags::EMPTY
+    }
+
+    fn properties(&self) -> BTreeMap<String, Property> {
+        BTreeMap::from([
+            ("zoom".to_string(), Property::Number(self.zoom)),
+            ("priority".to_string(), Property::Number(self.priority)),
+            ("viewport".to_string(), numbers(&self.viewport)),
+            ("clear_color".to_string(), numbers(&self.clear_color)),
+        ])
+    }
+
+    fn set_property(&mut self, name: &str, value: Property) -> Result<()> {
+        match (name, value) {
+            ("zoom", Property::Number(zoom)) if zoom > 0.0 => self.zoom = zoom,
+            ("priority", Property::Number(priority)) => self.priority = priority,
+            ("viewport", value) => self.viewport = viewport(&value)?,
+            ("clear_color", value) => self.clear_color = four_numbers(name, &value)?,
+            (name, value) => {
+                return Err(ScriptingError::new(&format!(
+                    "Can't set '{}' of a camera to {:?}",
+                    name, value
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn numbers(values: &[f32]) -> Property {
+    Property::Array(
+        values
+            .iter()
+            .map(|value| Property::Number(*value))
+            .collect(),
+    )
+}
+
+fn four_numbers(name: &str, value: &Property) -> Result<[f32; 4]> {
+    if let Property::Array(values) = value {
+        if let [Property::Number(a), Property::Number(b), Property::Number(c), Property::Number(d)] =
+            values.as_slice()
+        {
+            return Ok([*a, *b, *c, *d]);
+        }
+    }
+    Err(ScriptingError::new(&format!(
+        "'{}' of a camera should be an array of 4 numbers, got {:?}",
+        name, value
+    )))
+}
+
+/// Reads a viewport, which has to start on the screen and have an area
+fn viewport(value: &Property) -> Result<[f32; 4]> {
+    let [x, y, width, height] = four_numbers("viewport", value)?;
+    let on_screen = (0.0..1.0).contains(&x) && (0.0..1.0).contains(&y);
+    if !(on_screen && width > 0.0 && height > 0.0) {
+        return Err(ScriptingError::new(&format!(
+            "The viewport of a camera should start on the screen and have an area, got {:?}",
+            [x, y, width, height]
+        )));
+    }
+    Ok([x, y, width, height])
+}
+
+/// A camera of the scene, where its entity is
+#[derive(Debug, Clone, Default)]
+pub struct SceneCamera {
+    pub settings: CameraComponent,
+    pub position: (f32, f32),
+    pub rotation: f32,
+}
+
+/// Finds the cameras of the loaded scene, ordered from the lowest priority to the highest
+pub fn scene_cameras<T: ScriptingInstance>(ecs: &ECS<T>, frame_num: u64) -> Vec<SceneCamera> {
+    let mut cameras = Vec::new();
+    for entity in ecs.get_all_runtime_entities_flat() {
+        let entity = entity.lock().unwrap();
+        for component in &entity.components {
+            if component.component_proto.name != "Camera" {
+                continue;
+            }
+            let properties = match component
+                .data
+                .to_component_proto(&component.component_proto)
+            {
+                Ok(proto) => proto.properties,
+                Err(_) => component.component_proto.properties.clone(),
+            };
+            let (position, rotation, _) =
+                entity.transform.lock().unwrap().eval_transform(frame_num);
+            cameras.push(SceneCamera {
+                settings: <CameraComponent as native::Component>::new(&properties),
+                position,
+                rotation,
+            });
+        }
+    }
+    cameras.sort_by(|a, b| a.settings.priority.total_cmp(&b.settings.priority));
+    cameras
+}
+
+/// A camera placed for a frame
+#[derive(Clone)]
+pub struct CameraView {
+    pub state: CameraState,
+    pub clear_color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraMatrix {
+    view: [[f32; 4]; 4],
+    camera: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClearUniform {
+    color: [f32; 4],
+}
+
+/// The buffers a camera is drawn with
+struct CameraTarget {
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    clear_buffer: wgpu::Buffer,
+    clear_bind_group: wgpu::BindGroup,
+}
+
+/// Draws the drawables once for every camera
+pub struct CameraRenderer {
+    camera_layout: wgpu::BindGroupLayout,
+    clear_layout: wgpu::BindGroupLayout,
+    clear_shader: Rc<Shader>,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    targets: Vec<CameraTarget>,
+}
+
+impl CameraRenderer {
+    pub fn new(device: &wgpu::Device, shader_manager: &ShaderManager) -> CameraRenderer {
+        CameraRenderer {
+            camera_layout: crate::rendering::core::get_global_bind_group_layout(device),
+            clear_layout: get_clear_bind_group_layout(device),
+            clear_shader: shader_manager.get_shader("clear").unwrap(),
+            vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Clear Vertex Buffer"),
+                contents: bytemuck::cast_slice(QUAD_VERTICES),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Clear Index Buffer"),
+                contents: bytemuck::cast_slice(QUAD_INDICES),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Writes the uniforms of the cameras, creating buffers for new ones
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, views: &[CameraView]) {
+        while self.targets.len() < views.len() {
+            self.targets.push(self.create_target(device));
+        }
+        for (view, target) in views.iter().zip(&self.targets) {
+            let matrix = CameraMatrix {
+                view: view.state.view,
+                camera: view.state.get_transformation_matrix(),
+            };
+            queue.write_buffer(&target.camera_buffer, 0, bytemuck::cast_slice(&[matrix]));
+            let clear = ClearUniform {
+                color: view.clear_color,
+            };
+            queue.write_buffer(&target.clear_buffer, 0, bytemuck::cast_slice(&[clear]));
+        }
+    }
+
+    /// Records a render pass for every camera, the first one clears the whole frame
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        size: (u32, u32),
+        views: &[CameraView],
+        batcher: &SpriteBatcher,
+    ) {
+        for (i, (view, camera)) in views.iter().zip(&self.targets).enumerate() {
+            let viewport = view.state.viewport;
+            let x = (viewport[0] * size.0 as f32).clamp(0.0, size.0 as f32);
+            let y = (viewport[1] * size.1 as f32).clamp(0.0, size.1 as f32);
+            let width = (viewport[2] * size.0 as f32).min(size.0 as f32 - x);
+            let height = (viewport[3] * size.1 as f32).min(size.1 as f32 - y);
+            // viewports are validated when they are set but may still be below a pixel
+            let empty = !(width >= 1.0 && height >= 1.0);
+            if empty && i > 0 {
+                continue;
+            }
+            let covers_frame =
+                x == 0.0 && y == 0.0 && width == size.0 as f32 && height == size.1 as f32;
+            let load = match i {
+                0 if covers_frame => wgpu::LoadOp::Clear(to_wgpu_color(view.clear_color)),
+                0 => wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                _ => wgpu::LoadOp::Load,
+            };
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Camera Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            if empty {
+                continue;
+            }
+            pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            pass.set_scissor_rect(x as u32, y as u32, width as u32, height as u32);
+            if !(i == 0 && covers_frame) && view.clear_color[3] > 0.0 {
+                pass.set_pipeline(self.clear_shader.get_pipeline());
+                pass.set_bind_group(0, &camera.clear_bind_group, &[]);
+                pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..1);
+            }
+            batcher.draw(&mut pass, &camera.camera_bind_group);
+        }
+    }
+
+    fn create_target(&self, device: &wgpu::Device) -> CameraTarget {
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera Matrix Buffer"),
+            size: std::mem::size_of::<CameraMatrix>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("Camera Bind Group"),
+        });
+        let clear_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera Clear Buffer"),
+            size: std::mem::size_of::<ClearUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let clear_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.clear_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: clear_buffer.as_entire_binding(),
+            }],
+            label: Some("Camera Clear Bind Group"),
+        });
+        CameraTarget {
+            camera_buffer,
+            camera_bind_group,
+            clear_buffer,
+            clear_bind_group,
+        }
+    }
+}
+
+pub fn get_clear_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Clear Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+    })
+}
+
+fn to_wgpu_color(color: [f32; 4]) -> wgpu::Color {
+    wgpu::Color {
+        r: color[0] as f64,
+        g: color[1] as f64,
+        b: color[2] as f64,
+        a: color[3] as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 0.001 && (a.1 - b.1).abs() < 0.001
+    }
+
+    fn position(view: &CameraView) -> (f32, f32) {
+        (view.state.position.x, view.state.position.y)
+    }
+
+    #[test]
+    fn test_camera() {
+        let mut camera = Camera::new();
+        // a 2:1 screen, the view is 2 by 1 world units at 1x zoom
+        let size = (200, 100);
+        camera.views(Vec::new(), size);
+        camera.follow = Some(Follow {
+            entity: 1,
+            smoothing: 0.0,
+        });
+        camera.update(1.0 / 60.0, Some((3.0, 4.0)));
+        assert_eq!(camera.position, (3.0, 4.0));
+        let views = camera.views(Vec::new(), size);
+        assert_eq!(position(&views[0]), (1.5, 4.0));
+        assert!(close(camera.world_to_screen((3.0, 4.0)), (0.5, 0.5)));
+
+        // smoothing only moves part of the way
+        camera.follow.as_mut().unwrap().smoothing = 0.5;
+        camera.update(1.0 / 60.0, Some((4.0, 4.0)));
+        assert!(camera.position.0 > 3.0 && camera.position.0 < 4.0);
+
+        // the view is 4 by 2 world units, which keeps the center 2 units from the sides
+        camera.follow = None;
+        camera.bounds = Some(Bounds {
+            min: (0.0, 0.0),
+            max: (10.0, 1.0),
+        });
+        camera.position = (-5.0, 4.0);
+        camera.update(1.0 / 60.0, None);
+        assert_eq!(camera.position, (2.0, 0.5));
+
+        camera.bounds = None;
+        camera.rotation = std::f32::consts::FRAC_PI_2;
+        camera.zoom = 2.0;
+        camera.views(Vec::new(), size);
+        for position in [(0.0, 0.0), (0.25, 0.75), (1.0, 1.0)] {
+            let world = camera.screen_to_world(position);
+            assert!(close(camera.world_to_screen(world), position));
+        }
+        // the top of a camera that is turned left points to the left of the world
+        let top = camera.screen_to_world((0.5, 0.0));
+        assert!(top.0 < camera.position.0);
+
+        camera.shake(1.0, 0.5);
+        camera.update(0.1, None);
+        assert_ne!(position(&camera.views(Vec::new(), size)[0]), (1.0, 0.5));
+        camera.update(0.5, None);
+        assert_eq!(position(&camera.views(Vec::new(), size)[0]), (1.0, 0.5));
+    }
+
+    #[test]
+    fn test_camera_views() {
+        use native::Component;
+
+        let minimap = SceneCamera {
+            settings: CameraComponent::new(&BTreeMap::from([
+                ("priority".to_string(), Property::Number(1.0)),
+                ("zoom".to_string(), Property::Number(0.25)),
+                ("viewport".to_string(), numbers(&[0.75, 0.0, 0.25, 0.5])),
+            ])),
+            position: (2.0, 2.0),
+            rotation: 0.0,
+        };
+        let player = SceneCamera {
+            position: (5.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            CameraComponent::new(&minimap.settings.properties()),
+            minimap.settings,
+            "the properties should round trip"
+        );
+
+        let mut camera = Camera::new();
+        camera.reset(Some(&minimap));
+        assert_eq!(camera.position, (2.0, 2.0));
+        let views = camera.views(vec![player, minimap.clone()], (400, 200));
+        assert_eq!(views.len(), 2);
+        // the main camera is drawn last
+        assert_eq!(views[0].state.viewport, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(views[1].state.viewport, [0.75, 0.0, 0.25, 0.5]);
+        assert_eq!(views[1].state.scale, 0.25);
+        // the minimap viewport is 100 by 100 pixels
+        assert_eq!(views[1].state.aspect, (1.0, 1.0));
+        // screen positions are relative to the window, not the viewport
+        assert!(close(camera.world_to_screen((2.0, 2.0)), (0.875, 0.25)));
+        assert_eq!(position(&views[0]), (5.0 * 0.5, 0.0));
+    }
+
+    #[test]
+    fn test_invalid_viewports() {
+        use native::Component;
+
+        let mut camera = CameraComponent::default();
+        for viewport in [
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0, -0.5],
+            [1.0, 0.0, 0.5, 0.5],
+            [0.0, 1.5, 0.5, 0.5],
+            [f32::NAN, 0.0, 1.0, 1.0],
+        ] {
+            assert!(camera.set_property("viewport", numbers(&viewport)).is_err());
+        }
+        assert_eq!(camera.viewport, [0.0, 0.0, 1.0, 1.0]);
+        camera
+            .set_property("viewport", numbers(&[0.5, 0.5, 1.0, 1.0]))
+            .unwrap();
+        assert_eq!(camera.viewport, [0.5, 0.5, 1.0, 1.0]);
+    }
+}
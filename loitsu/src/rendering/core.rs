@@ -5,11 +5,11 @@ use winit::{
     window::Window,
 };
 #[allow(unused_imports)]
-use crate::{log_render as log, scripting::{ScriptingInstance, EntityUpdate}, scene_management::Scene, rendering::drawable::{sprite::SpriteDrawable, DrawablePrototype}, asset_management::AssetManager, ecs::{Transform, RuntimeEntity}, log_scripting, input::InputState};
+use crate::{log_render as log, scripting::{ScriptingInstance, EntityUpdate}, scene_management::Scene, rendering::drawable::{sprite::SpriteDrawable, DrawablePrototype}, asset_management::AssetManager, ecs::{Transform, RuntimeEntity}, log_scripting, input::{InputEvent, InputState}};
 #[allow(unused_imports)]
 use crate::ecs::{ECS, ComponentFlags};
-use std::{cmp::max, sync::{Mutex, Arc, RwLock}, rc::Rc, cell::RefCell};
-use crate::{asset_management::ASSET_MANAGER, util::scaling, ecs::RuntimeTransform};
+use std::{sync::{Mutex, Arc, RwLock}, rc::Rc, cell::RefCell};
+use crate::{asset_management::ASSET_MANAGER, util::scaling, ecs::RuntimeTransform, engine::{EngineOptions, Plugin, RenderContext}};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::wasm_bindgen;
@@ -24,9 +24,9 @@ static mut WEB_RESIZED: bool = true;
 #[cfg(target_arch = "wasm32")]
 use crate::web::update_loading_status;
 
-use super::{drawable::Drawable, shader::ShaderManager};
+use super::{camera::{CameraRenderer, CameraView}, drawable::{Drawable, batch::SpriteBatcher, order::DrawSorting}, shader::ShaderManager};
 
-pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T, ecs: Arc<RwLock<ECS<T>>>) where T: ScriptingInstance + 'static {
+pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T, ecs: Arc<RwLock<ECS<T>>>, mut options: EngineOptions<T>) where T: ScriptingInstance + 'static {
     unsafe { HAS_RENDERED = false; }
     unsafe { HAS_LOADED = false; }
     #[cfg(target_arch = "wasm32")]
@@ -80,26 +80,11 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
     // lets load our default shaders
     let mut shader_manager: ShaderManager = crate::rendering::shader::ShaderManager::new();
     shader_manager.load_default_shaders(&device);
-    // lets init the global bind group
-    let camera_matrix_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Camera Matrix Buffer"),
-        size: (std::mem::size_of::<[[f32; 4]; 4]>() as u64) * 2,
-        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
-    let global_bind_group_layout = get_global_bind_group_layout(&device);
-    let global_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &global_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                buffer: &camera_matrix_buffer,
-                offset: 0,
-                size: wgpu::BufferSize::new((std::mem::size_of::<[[f32; 4]; 4]>() as u64) * 2),
-            }),
-        }],
-        label: None,
-    });
+    // every camera gets its own global bind group
+    let mut camera_renderer = CameraRenderer::new(&device, &shader_manager);
+    let mut sprite_batcher = SpriteBatcher::new(&device, &shader_manager);
+    #[allow(unused_mut)]
+    let mut draw_sorting = DrawSorting::default();
 
     // lets create the default sampler
 
@@ -123,17 +108,29 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
     let mut ecs_initialized = false;
     let mut drawables = Vec::<Box<dyn Drawable>>::new();
     let mut frame_count: u64 = 0;
-    let mut state = State {
-        camera: CameraState::new()
-    };
+    // the camera input is mapped with, the main camera of the scene
+    let mut main_camera = CameraState::new();
     let input_state = Arc::new(Mutex::new(InputState::new()));
-    state.camera.set_scale(1.0);
-    state.camera.set_position([0.0, 0.0].into());
+    let mut gamepad_backend = crate::input::gamepad::default_backend();
+    let mut ime_allowed = false;
+    #[cfg(not(feature = "direct_asset_management"))]
+    let mut last_script_frame: Option<instant::Instant> = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let recording_path = crate::input::recording::setup_from_env(&mut input_state.lock().unwrap());
+    #[cfg(not(target_arch = "wasm32"))]
+    let profile_path = crate::scripting::profiler::setup_from_env();
+    #[cfg(all(debug_assertions, not(target_arch = "wasm32"), not(feature = "direct_asset_management")))]
+    let mut script_watcher = crate::scripting::hot_reload::ScriptWatcher::new();
+    main_camera.set_scale(1.0);
+    main_camera.set_position([0.0, 0.0].into());
     #[cfg(feature = "editor")]
     let mut selected_entity: Option<Arc<Mutex<RuntimeEntity<T>>>> = None;
     #[cfg(not(target_arch = "wasm32"))]
     #[allow(unused)]
     let mut last_frame_time = std::time::Instant::now();
+    for plugin in options.plugins.iter_mut() {
+        plugin.on_start();
+    }
 
     event_loop.run(move |event, window_target| {
         let _ = (&instance, &adapter);
@@ -154,17 +151,7 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                 window_size.height = window_size.height.max(1.0);
                 let _ = window.borrow().request_inner_size(window_size);
                 if frame_count == 0 {
-                    let max = max(window_size.width as i32, window_size.height as i32);
-                    state.camera.view = [
-                            [(size.height as f32) / max as f32, 0.0, 0.0, 0.0], 
-                            [0.0, (size.width as f32) / max as f32, 0.0, 0.0],
-                            [0.0, 0.0, 1.0, 0.0],
-                            [0.0, 0.0, 0.0, 1.0]
-                    ];
-                    queue.write_buffer(&camera_matrix_buffer, 0, bytemuck::cast_slice(&[CameraMatrix {
-                        view: state.camera.view,
-                        camera: state.camera.get_transformation_matrix()
-                    }]));
+                    main_camera.set_size(window_size.width as f32, window_size.height as f32);
                 }
                 unsafe {WEB_RESIZED = false;}
             }
@@ -186,18 +173,7 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
 
                 // okay gamers lets resize the screen & camera matrix buffers
                 // from atlas :D
-                let max = max(new_size.width, new_size.height);
-                state.camera.aspect = (new_size.width as f32 / max as f32, new_size.height as f32 / max as f32);
-                state.camera.view = [
-                        [(new_size.height as f32) / max as f32, 0.0, 0.0, 0.0], 
-                        [0.0, (new_size.width as f32) / max as f32, 0.0, 0.0],
-                        [0.0, 0.0, 1.0, 0.0],
-                        [0.0, 0.0, 0.0, 1.0]
-                ];
-                queue.write_buffer(&camera_matrix_buffer, 0, bytemuck::cast_slice(&[CameraMatrix {
-                    view: state.camera.view,
-                    camera: state.camera.get_transformation_matrix()
-                }]));
+                main_camera.set_size(new_size.width as f32, new_size.height as f32);
                 // On macos the window needs to be redrawn manually after resizing
                 window.borrow().request_redraw();
             },
@@ -227,6 +203,15 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                 // okay lets start
                 #[allow(unused_mut)]
                 let mut updates = Vec::new();
+                {
+                    let mut input_state = input_state.lock().unwrap();
+                    input_state.poll_gamepads(gamepad_backend.as_mut());
+                    if input_state.text_input_enabled != ime_allowed {
+                        ime_allowed = input_state.text_input_enabled;
+                        window.borrow().set_ime_allowed(ime_allowed);
+                    }
+                    input_state.update_world_positions(&main_camera);
+                }
                 #[cfg(feature = "editor")]
                 {
                     let mut ecs = ecs.write().unwrap();
@@ -238,7 +223,7 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                                     selected_entity = Some(entity.clone());
                                     let entity = (*entity).lock().unwrap();
                                     let as_entity = entity.as_entity();
-                                    let entity_bounds = get_entity_screen_space_bounds(&state.camera, &mut entity.transform.lock().unwrap(), frame_count - 1).unwrap();
+                                    let entity_bounds = get_entity_screen_space_bounds(&main_camera, &mut entity.transform.lock().unwrap(), frame_count - 1).unwrap();
                                     ecs.emit(crate::editor::Event::EntitySelected(as_entity));
                                     ecs.emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
                                 }
@@ -256,7 +241,7 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                             crate::editor::ClientEvent::MoveSelected(x, y) => {
                                 if let Some(entity) = &selected_entity {
                                     let entity = (*entity).lock().unwrap();
-                                    let (x, y) = crate::util::scaling::as_world_scale(&state.camera, (x, y));
+                                    let (x, y) = crate::util::scaling::as_world_scale(&main_camera, (x, y));
                                     {
                                         let mut rtransform = entity.transform.lock().unwrap();
                                         match rtransform.transform {
@@ -272,7 +257,7 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                                     }
                                     {
                                         let mut rtransform = entity.transform.lock().unwrap();
-                                        let entity_bounds = get_entity_screen_space_bounds(&state.camera, &mut rtransform, frame_count - 1).unwrap();
+                                        let entity_bounds = get_entity_screen_space_bounds(&main_camera, &mut rtransform, frame_count - 1).unwrap();
                                         ecs.emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
                                     }
                                 }
@@ -290,10 +275,24 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                     let scene: Option<Scene> = {
                         let asset_manager = crate::asset_management::ASSET_MANAGER.lock().unwrap();
                         let x = if let Some(static_shard) = &asset_manager.assets.lock().unwrap().static_shard {
+                            let preferences = options.apply_preferences(static_shard.get_preferences());
                             // init scripts
+                            if let Some(validated_scripts) = static_shard.get_validated_scripts() {
+                                scripting.set_validated(validated_scripts.clone());
+                            }
                             scripting.initialize(static_shard.get_scripts().clone(), input_state.clone(), ecs.clone()).unwrap();
+                            scripting.set_budget(preferences.script_budget.clone());
+                            draw_sorting = preferences.draw_sorting.clone();
                             log_scripting!("Scripting initialized");
-                            let default_scene_name = static_shard.get_preferences().default_scene.as_str();
+                            {
+                                let mut input_state = input_state.lock().unwrap();
+                                input_state.actions = crate::input::actions::ActionMap::new(preferences.input.clone());
+                                if let Some(overrides) = crate::input::actions::load_overrides() {
+                                    input_state.actions.set_overrides(overrides);
+                                }
+                                input_state.seed_random(preferences.random_seed.unwrap_or_else(crate::util::random::time_seed));
+                            }
+                            let default_scene_name = preferences.default_scene.as_str();
                             let scene = static_shard.get_scene(default_scene_name);
                             Some(scene.expect(
                                     format!("Default scene wasn't included in the static shard! Expected to find scene '{}'. Available scenes are '{}'", 
@@ -308,18 +307,61 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                         let mut ecs = ecs.write().unwrap();
                         ecs.load_scene(
                             scene, &mut scripting);
+                        #[cfg(not(feature = "editor"))]
+                        crate::rendering::camera::CAMERA.lock().unwrap().reset(crate::rendering::camera::scene_cameras(&ecs, frame_count).last());
                         ecs_initialized = true;
                         log!("ECS initialized");
+                        for plugin in options.plugins.iter_mut() {
+                            plugin.on_scene_loaded(&mut ecs);
+                        }
                     }
                 } else {
-                    #[allow(unused)]
-                    let ecs = ecs.read().unwrap();
-                    let mut asset_manager = crate::asset_management::ASSET_MANAGER.lock().unwrap();
-                    asset_manager.initialize_shards(&device, &queue);
-                    if frame_count > 1 && ecs_initialized && asset_manager.pending_tasks.load(std::sync::atomic::Ordering::SeqCst) == 0 {
-                        #[cfg(not(feature = "disable_common_ecs_methods"))]
+                    let mut ran_frame = false;
+                    {
+                        #[allow(unused)]
+                        let ecs = ecs.read().unwrap();
+                        let mut asset_manager = crate::asset_management::ASSET_MANAGER.lock().unwrap();
+                        asset_manager.initialize_shards(&device, &queue);
+                        #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+                        if let Some(sources) = script_watcher.poll() {
+                            // a failed reload leaves the previous scripts running, the diagnostics have already been emitted
+                            match ecs.reload_scripts(&mut scripting, sources) {
+                                Ok(()) => log_scripting!("Scripts reloaded"),
+                                Err(e) => crate::error!("Couldn't reload scripts: {}", e)
+                            }
+                        }
+                        if frame_count > 1 && ecs_initialized && asset_manager.pending_tasks.load(std::sync::atomic::Ordering::SeqCst) == 0 {
+                            {
+                                // scripts only see time pass on the frames they run on
+                                let now = instant::Instant::now();
+                                let delta = last_script_frame.map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+                                last_script_frame = Some(now);
+                                let mut input_state = input_state.lock().unwrap();
+                                input_state.begin_frame(delta);
+                                input_state.update_world_positions(&main_camera);
+                            }
+                            scripting.begin_frame();
+                            #[cfg(not(feature = "disable_common_ecs_methods"))]
+                            {
+                                updates.extend(ecs.run_frame(&mut scripting));
+                            }
+                            ran_frame = true;
+                        }
+                    }
+                    if ran_frame {
+                        let mut ecs = ecs.write().unwrap();
+                        for plugin in options.plugins.iter_mut() {
+                            plugin.on_frame(&mut ecs);
+                        }
+                        // the editor moves its own camera
+                        #[cfg(not(feature = "editor"))]
                         {
-                            updates.extend(ecs.run_frame(&mut scripting));
+                            let mut camera = crate::rendering::camera::CAMERA.lock().unwrap();
+                            let target = camera.follow.as_ref()
+                                .and_then(|follow| ecs.get_entity(follow.entity))
+                                .map(|entity| entity.lock().unwrap().transform.lock().unwrap().eval_transform(frame_count).0);
+                            let delta_time = input_state.lock().unwrap().delta_time;
+                            camera.update(delta_time, target);
                         }
                     }
                 }
@@ -335,114 +377,121 @@ pub async fn run<T>(event_loop: EventLoop<()>, window: Window, mut scripting: T,
                 }
                 {
                     let asset_manager = crate::asset_management::ASSET_MANAGER.lock().unwrap();
-                    process_entity_updates(&device, &asset_manager, &shader_manager, &mut drawables, updates);
-                }
-                {
-                    let mut input_state = input_state.lock().unwrap();
-                    input_state.new_keys.clear();
-                    input_state.up_keys.clear();
+                    process_entity_updates(&asset_manager, &mut drawables, updates);
                 }
-                if state.camera.dirty {
-                    queue.write_buffer(&camera_matrix_buffer, 0, bytemuck::cast_slice(&[CameraMatrix {
-                        view: state.camera.view,
-                        camera: state.camera.get_transformation_matrix()
-                    }]));
-                    state.camera.dirty = false;
+                input_state.lock().unwrap().end_frame();
+                // the editor looks at the scene through its own camera
+                #[cfg(feature = "editor")]
+                let cameras = vec![CameraView { state: main_camera.clone(), clear_color: [0.0, 0.0, 0.0, 1.0] }];
+                #[cfg(not(feature = "editor"))]
+                let cameras = {
+                    let scene_cameras = crate::rendering::camera::scene_cameras(&ecs.read().unwrap(), frame_count);
+                    let cameras = crate::rendering::camera::CAMERA.lock().unwrap().views(scene_cameras, (config.width, config.height));
+                    main_camera = cameras.last().unwrap().state.clone();
+                    cameras
+                };
+                if main_camera.dirty {
+                    main_camera.dirty = false;
                     #[cfg(feature = "editor")]
                     {
                         let mut ecs = ecs.write().unwrap();
-                        ecs.emit(crate::editor::Event::CameraChanged(state.camera.position.x, state.camera.position.y, state.camera.scale));
+                        ecs.emit(crate::editor::Event::CameraChanged(main_camera.position.x, main_camera.position.y, main_camera.scale));
                     }
                 }
-                render_frame(&surface, &device, &queue, &mut drawables, &global_bind_group, ecs_initialized, frame_count);
+                camera_renderer.prepare(&device, &queue, &cameras);
+                render_frame(&surface, &device, &queue, &mut drawables, &draw_sorting, &mut sprite_batcher, &camera_renderer, &cameras, ecs_initialized, frame_count, &mut options.plugins);
                 frame_count += 1;
+                crate::scripting::profiler::update_overlay(&window.borrow(), &options.window.title);
                 window.borrow().request_redraw();
             },
             Event::WindowEvent {
                 ref event,
                 window_id,
             } if window_id == window.borrow().id() => match event {
-                WindowEvent::CloseRequested => window_target.exit(),
+                WindowEvent::CloseRequested => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(path) = &recording_path {
+                        crate::input::recording::save_recording(&mut input_state.lock().unwrap(), path);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(path) = &profile_path {
+                        crate::scripting::profiler::save_report(path);
+                    }
+                    window_target.exit()
+                },
                 WindowEvent::CursorMoved { position, ..} => {
                     let mut input_state = input_state.lock().unwrap();
-                    input_state.mouse.last_position = Some(input_state.mouse.position);
-                    input_state.mouse.position = (position.x as f32 / config.width as f32, position.y as f32 / config.height as f32);
+                    input_state.process_event(InputEvent::CursorMoved((position.x as f32 / config.width as f32, position.y as f32 / config.height as f32)));
                     #[cfg(feature = "editor")]
                     if input_state.mouse.right_button {
                         let delta = input_state.mouse.get_delta();
-                        let world_scale_delta = crate::util::scaling::as_world_scale(&state.camera, (-delta.0 * state.camera.aspect.1, delta.1 * state.camera.aspect.0));
-                        state.camera.position.x += world_scale_delta.0;
-                        state.camera.position.y += world_scale_delta.1;
-                        state.camera.dirty = true;
+                        let world_scale_delta = crate::util::scaling::as_world_scale(&main_camera, (-delta.0 * main_camera.aspect.1, delta.1 * main_camera.aspect.0));
+                        main_camera.position.x += world_scale_delta.0;
+                        main_camera.position.y += world_scale_delta.1;
+                        main_camera.dirty = true;
                         if let Some(entity) = &selected_entity {
                             let rentity = entity.lock().unwrap();
-                            let entity_bounds = get_entity_screen_space_bounds(&state.camera, &mut rentity.transform.lock().unwrap(), frame_count - 1).unwrap();
+                            let entity_bounds = get_entity_screen_space_bounds(&main_camera, &mut rentity.transform.lock().unwrap(), frame_count - 1).unwrap();
                             ecs.write().unwrap().emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
                         }
                     }
                 },
                 WindowEvent::MouseInput { state: element_state, button, .. } => {
                     let mut input_state = input_state.lock().unwrap();
-                    match button {
-                        MouseButton::Left => {
-                            input_state.mouse.left_button = *element_state == ElementState::Pressed;
-                            #[cfg(feature = "editor")]
-                            if *element_state == ElementState::Pressed {
-                                let click_pos = input_state.mouse.get_world_position(&state.camera);
-
-                                let mut ecs = ecs.write().unwrap();
-                                if let Some(entity) = find_overlapping_entity(&ecs, click_pos, frame_count - 1) {
-                                    selected_entity = Some(entity.clone());
-                                    let entity = entity.lock().unwrap();
-                                    let as_entity = entity.as_entity();
-                                    let entity_bounds = get_entity_screen_space_bounds(&state.camera, &mut entity.transform.lock().unwrap(), frame_count - 1).unwrap();
-                                    ecs.emit(crate::editor::Event::EntitySelected(as_entity));
-                                    ecs.emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
-                                }
-                            }
-                        },
-                        MouseButton::Right => {
-                            input_state.mouse.right_button = *element_state == ElementState::Pressed;
-                        },
-                        MouseButton::Middle => {
-                            input_state.mouse.middle_button = *element_state == ElementState::Pressed;
-                        },
-                        _ => {}
+                    input_state.process_event(InputEvent::MouseButton(*button, *element_state == ElementState::Pressed));
+                    #[cfg(feature = "editor")]
+                    if *button == MouseButton::Left && *element_state == ElementState::Pressed {
+                        let click_pos = input_state.mouse.get_world_position(&main_camera);
+
+                        let mut ecs = ecs.write().unwrap();
+                        if let Some(entity) = find_overlapping_entity(&ecs, click_pos, frame_count - 1) {
+                            selected_entity = Some(entity.clone());
+                            let entity = entity.lock().unwrap();
+                            let as_entity = entity.as_entity();
+                            let entity_bounds = get_entity_screen_space_bounds(&main_camera, &mut entity.transform.lock().unwrap(), frame_count - 1).unwrap();
+                            ecs.emit(crate::editor::Event::EntitySelected(as_entity));
+                            ecs.emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
+                        }
                     }
                 },
-                #[cfg(feature = "editor")]
                 WindowEvent::MouseWheel { delta, .. } => {
-                    match delta {
-                        MouseScrollDelta::LineDelta(_x, y) => {
-                            state.camera.scale += y * 0.001;
-                            state.camera.dirty = true;
-                        },
-                        MouseScrollDelta::PixelDelta(pos) => {
-                            state.camera.scale += pos.y as f32 * 0.001;
-                            state.camera.dirty = true;
+                    input_state.lock().unwrap().process_event(InputEvent::MouseWheel(crate::input::mouse::scroll_delta_lines(*delta)));
+                    #[cfg(feature = "editor")]
+                    {
+                        match delta {
+                            MouseScrollDelta::LineDelta(_x, y) => {
+                                main_camera.scale += y * 0.001;
+                                main_camera.dirty = true;
+                            },
+                            MouseScrollDelta::PixelDelta(pos) => {
+                                main_camera.scale += pos.y as f32 * 0.001;
+                                main_camera.dirty = true;
+                            }
+                        }
+                        if main_camera.scale < 0.1 {
+                            main_camera.scale = 0.1;
+                        }
+                        if let Some(entity) = &selected_entity {
+                            let rentity = entity.lock().unwrap();
+                            let entity_bounds = get_entity_screen_space_bounds(&main_camera, &mut rentity.transform.lock().unwrap(), frame_count - 1).unwrap();
+                            ecs.write().unwrap().emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
                         }
-                    }
-                    if state.camera.scale < 0.1 {
-                        state.camera.scale = 0.1;
-                    }
-                    if let Some(entity) = &selected_entity {
-                        let rentity = entity.lock().unwrap();
-                        let entity_bounds = get_entity_screen_space_bounds(&state.camera, &mut rentity.transform.lock().unwrap(), frame_count - 1).unwrap();
-                        ecs.write().unwrap().emit(crate::editor::Event::SelectedEntityPosition(entity_bounds.0, entity_bounds.1, entity_bounds.2, entity_bounds.3));
                     }
                 },
+                WindowEvent::Touch(touch) => {
+                    let position = (touch.location.x as f32 / config.width as f32, touch.location.y as f32 / config.height as f32);
+                    input_state.lock().unwrap().process_event(InputEvent::Touch(touch.id, position, touch.phase));
+                },
                 WindowEvent::KeyboardInput { event, .. } => {
-                    let mut input_state = input_state.lock().unwrap();
-                    // check if we should add or remove the key
-                    if event.state == ElementState::Released {
-                        input_state.down_keys.retain(|x| *x != event.logical_key);
-                        input_state.up_keys.push(event.logical_key.clone());
-                    } else {
-                        if !input_state.down_keys.contains(&event.logical_key) {
-                            input_state.down_keys.push(event.logical_key.clone());
-                            input_state.new_keys.push(event.logical_key.clone());
-                        }
-                    }
+                    input_state.lock().unwrap().process_event(InputEvent::Key {
+                        logical_key: event.logical_key.clone(),
+                        physical_key: event.physical_key,
+                        pressed: event.state == ElementState::Pressed,
+                        text: event.text.as_ref().map(|text| text.to_string())
+                    });
+                },
+                WindowEvent::Ime(ime) => {
+                    input_state.lock().unwrap().process_event(InputEvent::Ime(ime.clone()));
                 },
                 _ => {}
             },
@@ -481,9 +530,7 @@ fn find_overlapping_entity<T>(ecs: &ECS<T>, check_position: (f32, f32), frame_nu
     None
 }
 
-fn process_entity_updates(device: &wgpu::Device, 
-                          asset_manager: &AssetManager, 
-                          shader_manager: &ShaderManager,
+fn process_entity_updates(asset_manager: &AssetManager, 
                           drawables: &mut Vec<Box<dyn Drawable>>,
                           updates: Vec<(Arc<Mutex<RuntimeTransform>>, Vec<EntityUpdate>)>) {
     for entity_updates in updates {
@@ -491,9 +538,9 @@ fn process_entity_updates(device: &wgpu::Device,
             match update {
                 EntityUpdate::AddDrawable(drawable) => {
                     match drawable {
-                        DrawablePrototype::Sprite {sprite, color, id} => {
-                            let mut drawable = Box::new(SpriteDrawable::new(sprite.as_str(), color, id, shader_manager));
-                            drawable.init(device, asset_manager, entity_updates.0.clone());
+                        DrawablePrototype::Sprite {sprite, color, order, id} => {
+                            let mut drawable = Box::new(SpriteDrawable::new(sprite.as_str(), color, order, id));
+                            drawable.init(asset_manager, entity_updates.0.clone());
                             drawables.push(drawable);
                         }
                     }
@@ -521,31 +568,27 @@ fn process_entity_updates(device: &wgpu::Device,
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct CameraMatrix {
-    view: [[f32; 4]; 4],
-    camera: [[f32; 4]; 4],
-}
-
-struct State {
-    camera: CameraState
-}
-
+#[derive(Clone)]
 pub struct CameraState {
     pub position: cgmath::Vector2<f32>,
     pub scale: f32,
+    /// In radians, counter-clockwise
+    pub rotation: f32,
     pub aspect: (f32, f32),
+    /// The area of the screen the camera draws to: x, y, width and height from 0 to 1
+    pub viewport: [f32; 4],
     pub dirty: bool,
     pub view: [[f32; 4]; 4]
 }
 
 impl CameraState {
-    fn new() -> CameraState {
+    pub fn new() -> CameraState {
         CameraState {
             position: cgmath::Vector2::<f32> {x: 0.0, y: 0.0},
             scale: 1.0,
+            rotation: 0.0,
             aspect: (1.0, 1.0),
+            viewport: [0.0, 0.0, 1.0, 1.0],
             dirty: false,
             view: [[0.0,0.0,0.0,0.0], [0.0,0.0,0.0,0.0], [0.0,0.0,0.0,0.0], [0.0,0.0,0.0,0.0]]
         }
@@ -556,28 +599,62 @@ impl CameraState {
         self.dirty = true;
     }
 
+    /// Moves the camera to a position in world space
+    pub fn set_world_position(&mut self, position: (f32, f32)) {
+        // the position is kept in view space, which is scaled by the aspect ratio
+        self.set_position((position.0 * self.aspect.1, position.1 * self.aspect.0).into());
+    }
+
     fn set_scale(&mut self, scale: f32) {
         self.scale = scale;
         self.dirty = true;
     }
 
-    fn get_transformation_matrix(&self) -> [[f32; 4]; 4] {
+    /// Sets the size of the viewport in pixels
+    pub fn set_size(&mut self, width: f32, height: f32) {
+        let max = width.max(height).max(1.0);
+        self.aspect = (width / max, height / max);
+        self.view = [
+                [height / max, 0.0, 0.0, 0.0], 
+                [0.0, width / max, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+        ];
+        self.dirty = true;
+    }
+
+    pub fn get_transformation_matrix(&self) -> [[f32; 4]; 4] {
+        // the view matrix has already scaled the world by the aspect ratio, which has to be undone
+        // for the rotation so that it doesn't skew the world
+        let (sin, cos) = (-self.rotation).sin_cos();
+        let k = self.aspect.1 / self.aspect.0;
+        let (x, y) = (self.position.x, self.position.y);
         [
-            [self.scale, 0.0, 0.0, -self.position.x * self.scale], // position is inverted because we want to move the world, not the camera
-            [0.0, self.scale, 0.0, -self.position.y * self.scale],
+            [self.scale * cos, -self.scale * sin * k, 0.0, -self.scale * (cos * x - sin * k * y)], // position is inverted because we want to move the world, not the camera
+            [self.scale * sin / k, self.scale * cos, 0.0, -self.scale * (sin / k * x + cos * y)],
             [0.0, 0.0, 1.0, 0.0],
             [0.0, 0.0, 0.0, 1.0],
         ]
     }
 }
 
+impl Default for CameraState {
+    fn default() -> Self {
+        CameraState::new()
+    }
+}
+
 static mut HAS_RENDERED: bool = false;
 static mut HAS_LOADED: bool = false;
 
-pub fn render_frame(surface: &wgpu::Surface, device: &wgpu::Device, 
+#[allow(clippy::too_many_arguments)]
+pub fn render_frame<T: ScriptingInstance>(surface: &wgpu::Surface, device: &wgpu::Device, 
                     queue: &wgpu::Queue, drawables: &mut Vec<Box<dyn Drawable>>, 
-                    global_bind_group: &wgpu::BindGroup,
-                    ecs_initialized: bool, frame_num: u64) {
+                    draw_sorting: &DrawSorting,
+                    sprite_batcher: &mut SpriteBatcher,
+                    camera_renderer: &CameraRenderer, cameras: &[CameraView],
+                    ecs_initialized: bool, frame_num: u64,
+                    plugins: &mut [Box<dyn Plugin<T>>]) {
     #[cfg(target_arch = "wasm32")]
     {
         if !unsafe { HAS_RENDERED } { 
@@ -596,7 +673,6 @@ pub fn render_frame(surface: &wgpu::Surface, device: &wgpu::Device,
         });
 
     let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-    let clear_color = wgpu::Color::BLACK;
     {
         let asset_manager = ASSET_MANAGER.lock().unwrap();
         if asset_manager.pending_tasks.load(std::sync::atomic::Ordering::SeqCst) == 0 && ecs_initialized {
@@ -609,25 +685,26 @@ pub fn render_frame(surface: &wgpu::Surface, device: &wgpu::Device,
             }
         }
     }
-    {
-        let mut r_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Primary Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    // Lets clear the main texture
-                    load: wgpu::LoadOp::Clear(clear_color),
-                    store: wgpu::StoreOp::Store 
-                }
-            })],
-            depth_stencil_attachment: None,
-            occlusion_query_set: None,
-            timestamp_writes: None
-        });
-
-        for drawable in drawables {
-            drawable.draw(frame_num, &device, &queue, &mut r_pass, global_bind_group);
+    // the drawables are batched once, then each camera draws the batches into its own viewport
+    // the sort is stable, so drawables that are drawn in the same place keep the order they were added in
+    drawables.sort_by_cached_key(|drawable| draw_sorting.key(drawable.get_draw_order(), drawable.get_position(frame_num)));
+    sprite_batcher.begin();
+    for drawable in drawables {
+        drawable.prepare(frame_num, device, queue, sprite_batcher);
+    }
+    sprite_batcher.finish(device, queue);
+    let size = (frame.texture.width(), frame.texture.height());
+    camera_renderer.render(&mut encoder, &view, size, cameras, sprite_batcher);
+    if !plugins.is_empty() {
+        let mut context = RenderContext {
+            device,
+            queue,
+            view: &view,
+            encoder: &mut encoder,
+            frame: frame_num
+        };
+        for plugin in plugins {
+            plugin.on_render(&mut context);
         }
     }
     queue.submit(Some(encoder.finish()));
@@ -654,27 +731,15 @@ pub fn get_sprite_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLay
         entries: &[
             wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
-                count: None
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
                 visibility: wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Texture { sample_type: wgpu::TextureSampleType::Float {filterable: true}, view_dimension: wgpu::TextureViewDimension::D2, multisampled: false },
                 count: None
             },
             wgpu::BindGroupLayoutEntry {
-                binding: 2,
+                binding: 1,
                 visibility: wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                 count: None
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 3,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
-                count: None
             }
         ],
     })
@@ -1,20 +1,27 @@
 use crate::ecs::ECS;
+use crate::engine::EngineOptions;
 use crate::log_render as log;
 use crate::scripting::ScriptingInstance;
 use std::sync::{Arc, RwLock};
 use winit::event_loop::EventLoop;
 
-pub fn init_window<T>(scripting: T, ecs: Arc<RwLock<ECS<T>>>)
+pub fn init_window<T>(scripting: T, ecs: Arc<RwLock<ECS<T>>>, options: EngineOptions<T>)
 where
     T: ScriptingInstance + 'static,
 {
     let event_loop = EventLoop::new().unwrap();
     log!("Opening window...");
-    let window = winit::window::WindowBuilder::new()
-        .with_title("loitsu")
+    let mut builder = winit::window::WindowBuilder::new()
+        .with_title(options.window.title.as_str())
         .with_min_inner_size(winit::dpi::LogicalSize::new(20.0, 20.0))
-        .build(&event_loop)
-        .unwrap();
+        .with_resizable(options.window.resizable);
+    if let Some((width, height)) = options.window.size {
+        builder = builder.with_inner_size(winit::dpi::LogicalSize::new(width, height));
+    }
+    if options.window.fullscreen {
+        builder = builder.with_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+    }
+    let window = builder.build(&event_loop).unwrap();
 
     log!("Preparing window...");
     env_logger::init();
@@ -24,6 +31,6 @@ where
         .build()
         .unwrap()
         .block_on(crate::rendering::core::run(
-            event_loop, window, scripting, ecs,
+            event_loop, window, scripting, ecs, options,
         ));
 }
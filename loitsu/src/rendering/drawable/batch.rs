@@ -0,0 +1,302 @@
+use super::{QUAD_INDICES, QUAD_VERTICES};
+use crate::asset_management::{asset::Asset, texture_asset::TextureMeta};
+use crate::rendering::shader::{Shader, ShaderManager};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+
+/// What a single sprite is drawn with. Instances are read from a vertex buffer rather than a
+/// storage buffer, as WebGL doesn't have the latter.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteInstance {
+    pub transform: [[f32; 4]; 4],
+    /// The left, top, right and bottom of the sprite in its texture
+    pub uv: [f32; 4],
+    pub color: [f32; 4],
+}
+
+impl SpriteInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+        2 => Float32x4,
+        3 => Float32x4,
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Sprites drawn with a single draw call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    /// The texture of the sprites, as given to `SpriteBatches::push`
+    pub texture: usize,
+    pub instances: Range<u32>,
+}
+
+/// Groups sprites into batches, the CPU side of `SpriteBatcher`. Only consecutive sprites that
+/// share a texture are batched, so that sprites are still drawn in the order they were pushed.
+#[derive(Default)]
+pub struct SpriteBatches {
+    instances: Vec<SpriteInstance>,
+    batches: Vec<Batch>,
+}
+
+impl SpriteBatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.batches.clear();
+    }
+
+    pub fn push(&mut self, texture: usize, instance: SpriteInstance) {
+        let index = self.instances.len() as u32;
+        self.instances.push(instance);
+        match self.batches.last_mut() {
+            Some(batch) if batch.texture == texture => batch.instances.end = index + 1,
+            _ => self.batches.push(Batch {
+                texture,
+                instances: index..index + 1,
+            }),
+        }
+    }
+
+    pub fn instances(&self) -> &[SpriteInstance] {
+        &self.instances
+    }
+
+    pub fn batches(&self) -> &[Batch] {
+        &self.batches
+    }
+
+    /// The draw calls each camera makes for the sprites
+    pub fn draw_calls(&self) -> usize {
+        self.batches.len()
+    }
+}
+
+struct BatchTexture {
+    version: u32,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Draws the sprites of a frame with one instanced draw call per batch. Drawables push their
+/// sprites once per frame, after which every camera draws the same batches.
+pub struct SpriteBatcher {
+    shader: Rc<Shader>,
+    layout: wgpu::BindGroupLayout,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    instance_buffer: Option<wgpu::Buffer>,
+    textures: Vec<BatchTexture>,
+    // the textures by the name of the texture asset
+    texture_lookup: HashMap<String, usize>,
+    batches: SpriteBatches,
+}
+
+impl SpriteBatcher {
+    pub fn new(device: &wgpu::Device, shader_manager: &ShaderManager) -> Self {
+        Self {
+            shader: shader_manager.get_shader("sprite").unwrap(),
+            layout: crate::rendering::core::get_sprite_bind_group_layout(device),
+            vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Sprite Vertex Buffer"),
+                contents: bytemuck::cast_slice(QUAD_VERTICES),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Sprite Index Buffer"),
+                contents: bytemuck::cast_slice(QUAD_INDICES),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
+            instance_buffer: None,
+            textures: Vec::new(),
+            texture_lookup: HashMap::new(),
+            batches: SpriteBatches::new(),
+        }
+    }
+
+    /// Forgets the sprites of the previous frame
+    pub fn begin(&mut self) {
+        self.batches.clear();
+    }
+
+    /// Adds a sprite to the frame. Returns false if its texture hasn't been loaded yet.
+    pub fn push(
+        &mut self,
+        device: &wgpu::Device,
+        meta: &TextureMeta,
+        instance: SpriteInstance,
+    ) -> bool {
+        let version = meta.get_texture_version();
+        let index = match self.texture_lookup.get(meta.get_target()) {
+            Some(&index) if self.textures[index].version == version => index,
+            index => {
+                let Some(bind_group) = self.create_bind_group(device, meta) else {
+                    return false;
+                };
+                let texture = BatchTexture {
+                    version,
+                    bind_group,
+                };
+                match index {
+                    Some(&index) => {
+                        self.textures[index] = texture;
+                        index
+                    }
+                    None => {
+                        self.textures.push(texture);
+                        self.texture_lookup
+                            .insert(meta.get_target().to_string(), self.textures.len() - 1);
+                        self.textures.len() - 1
+                    }
+                }
+            }
+        };
+        self.batches.push(index, instance);
+        true
+    }
+
+    /// Uploads the instances of the frame
+    pub fn finish(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let instances = self.batches.instances();
+        if instances.is_empty() {
+            return;
+        }
+        let size = std::mem::size_of_val(instances) as u64;
+        if self
+            .instance_buffer
+            .as_ref()
+            .is_none_or(|buffer| buffer.size() < size)
+        {
+            // leave room to grow, so that the buffer isn't recreated every time a sprite is added
+            self.instance_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Sprite Instance Buffer"),
+                size: size.next_power_of_two(),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+        queue.write_buffer(
+            self.instance_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::cast_slice(instances),
+        );
+    }
+
+    pub fn draw<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        global_bind_group: &'a wgpu::BindGroup,
+    ) {
+        let Some(instance_buffer) = &self.instance_buffer else {
+            return;
+        };
+        if self.batches.batches().is_empty() {
+            return;
+        }
+        pass.set_pipeline(self.shader.get_pipeline());
+        pass.set_bind_group(0, global_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for batch in self.batches.batches() {
+            pass.set_bind_group(1, &self.textures[batch.texture].bind_group, &[]);
+            pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, batch.instances.clone());
+        }
+    }
+
+    pub fn batches(&self) -> &SpriteBatches {
+        &self.batches
+    }
+
+    fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        meta: &TextureMeta,
+    ) -> Option<wgpu::BindGroup> {
+        let asset = meta.get_texture().ok()?;
+        let asset = asset.lock().unwrap();
+        let texture = match *asset {
+            Asset::Texture(ref texture) => texture,
+            _ => return None,
+        };
+        let tex_view = texture.get_texture_view()?;
+        Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(tex_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(
+                        crate::rendering::core::get_default_sampler().unwrap(),
+                    ),
+                },
+            ],
+            label: Some("sprite_bind_group"),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(x: f32) -> SpriteInstance {
+        SpriteInstance {
+            transform: [[x, 0.0, 0.0, 0.0]; 4],
+            uv: [0.0, 0.0, 1.0, 1.0],
+            color: [1.0; 4],
+        }
+    }
+
+    #[test]
+    fn test_sprite_batches() {
+        let mut batches = SpriteBatches::new();
+        for i in 0..1000 {
+            batches.push(0, instance(i as f32));
+        }
+        assert_eq!(batches.draw_calls(), 1);
+        assert_eq!(batches.batches()[0].instances, 0..1000);
+
+        // a sprite of another texture in between splits the batch to keep the order
+        batches.clear();
+        for (i, texture) in [0, 0, 1, 1, 0, 2].into_iter().enumerate() {
+            batches.push(texture, instance(i as f32));
+        }
+        let batches_of = |batches: &SpriteBatches| {
+            batches
+                .batches()
+                .iter()
+                .map(|batch| (batch.texture, batch.instances.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            batches_of(&batches),
+            vec![(0, 0..2), (1, 2..4), (0, 4..5), (2, 5..6)]
+        );
+        // the instances are in the order they were pushed
+        let order: Vec<f32> = batches
+            .instances()
+            .iter()
+            .map(|instance| instance.transform[0][0])
+            .collect();
+        assert_eq!(order, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+}
@@ -1,8 +1,11 @@
+pub mod batch;
+pub mod order;
 pub mod sprite;
 use std::sync::{Arc, Mutex};
 
 use crate::{asset_management::AssetManager, ecs::RuntimeTransform};
-use wgpu::RenderPass;
+use batch::SpriteBatcher;
+use order::DrawOrder;
 
 use super::vertex::Vertex;
 
@@ -52,6 +55,7 @@ pub enum DrawablePrototype {
     Sprite {
         sprite: String,
         color: [f32; 4],
+        order: DrawOrder,
         id: u32,
     },
 }
@@ -60,36 +64,26 @@ pub enum DrawablePrototype {
 pub enum DrawableProperty {
     Sprite(String),
     Color([f32; 4]),
+    Layer(String),
+    Order(i32),
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct TransformUniform {
-    transform: [[f32; 4]; 4],
-}
-impl TransformUniform {
-    pub fn new(transform: &mut RuntimeTransform, frame_num: u64) -> Self {
-        Self {
-            transform: transform.eval_transform_mat(frame_num),
-        }
-    }
-}
 pub trait Drawable<'b> {
-    fn init<'a>(
-        &mut self,
-        device: &wgpu::Device,
-        asset_manager: &AssetManager,
-        transform: Arc<Mutex<RuntimeTransform>>,
-    ) where
+    fn init<'a>(&mut self, asset_manager: &AssetManager, transform: Arc<Mutex<RuntimeTransform>>)
+    where
         'a: 'b;
-    fn draw<'a>(
-        &'a mut self,
+    /// Updates the drawable for the frame and hands what it draws to the batcher. This runs once
+    /// per frame, every camera then draws the batches.
+    fn prepare(
+        &mut self,
         frame_num: u64,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        pass: &mut RenderPass<'a>,
-        global_bind_group: &'a wgpu::BindGroup,
+        batcher: &mut SpriteBatcher,
     );
     fn get_id(&self) -> u32;
+    fn get_draw_order(&self) -> &DrawOrder;
+    /// The position of the drawable in world space
+    fn get_position(&self, frame_num: u64) -> (f32, f32);
     fn set_property(&mut self, name: String, property: DrawableProperty);
 }
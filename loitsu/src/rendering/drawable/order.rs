@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+
+/// The sorting layer drawables are on unless they are given another one
+pub const DEFAULT_LAYER: &str = "Default";
+
+/// Where a drawable is drawn relative to the others
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawOrder {
+    /// The sorting layer, layers are drawn in the order of `DrawSorting::layers`
+    pub layer: String,
+    /// The order within the layer, higher is drawn on top
+    pub order: i32,
+}
+
+impl Default for DrawOrder {
+    fn default() -> Self {
+        DrawOrder {
+            layer: DEFAULT_LAYER.to_string(),
+            order: 0,
+        }
+    }
+}
+
+/// How drawables are ordered, set with `draw_sorting` in `preferences.json`
+#[cfg_attr(
+    feature = "json_preference_parse",
+    derive(serde::Deserialize),
+    serde(default)
+)]
+#[derive(Debug, Clone, Default, bitcode::Encode, bitcode::Decode)]
+pub struct DrawSorting {
+    /// The sorting layers from back to front. Drawables on a layer that isn't listed are drawn
+    /// on the default layer, which comes first if it isn't listed either.
+    pub layers: Vec<String>,
+    /// Draws drawables that are lower in the world on top of the ones above them, if they have
+    /// the same layer and order. Meant for top-down games.
+    pub y_sort: bool,
+}
+
+impl DrawSorting {
+    /// Where a drawable at the given world position is drawn, drawables with a lower key are
+    /// drawn first
+    pub fn key(&self, order: &DrawOrder, position: (f32, f32)) -> SortKey {
+        SortKey {
+            layer: self.layer_index(&order.layer),
+            order: order.order,
+            depth: if self.y_sort { -position.1 } else { 0.0 },
+        }
+    }
+
+    fn layer_index(&self, layer: &str) -> usize {
+        self.layers
+            .iter()
+            .position(|name| name == layer)
+            .or_else(|| self.layers.iter().position(|name| name == DEFAULT_LAYER))
+            .map_or(0, |index| index + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    layer: usize,
+    order: i32,
+    depth: f32,
+}
+
+impl Eq for SortKey {}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.layer
+            .cmp(&other.layer)
+            .then(self.order.cmp(&other.order))
+            .then(self.depth.total_cmp(&other.depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drawable(layer: &str, order: i32) -> DrawOrder {
+        DrawOrder {
+            layer: layer.to_string(),
+            order,
+        }
+    }
+
+    #[test]
+    fn test_draw_order() {
+        let mut sorting = DrawSorting {
+            layers: vec![
+                "Background".to_string(),
+                DEFAULT_LAYER.to_string(),
+                "Foreground".to_string(),
+            ],
+            y_sort: false,
+        };
+        // (name, order, y), in the order they were added
+        let drawables = [
+            ("player", drawable("Default", 0), 0.0),
+            ("ui", drawable("Foreground", 0), 0.0),
+            ("tree", drawable("Default", 0), -1.0),
+            ("sky", drawable("Background", 0), 5.0),
+            ("shadow", drawable("Default", -1), 0.0),
+            ("unknown layer", drawable("Missing", 0), 3.0),
+        ];
+        let sorted = |sorting: &DrawSorting| {
+            let mut drawables = drawables.to_vec();
+            drawables.sort_by_cached_key(|(_, order, y)| sorting.key(order, (0.0, *y)));
+            drawables
+                .into_iter()
+                .map(|(name, _, _)| name)
+                .collect::<Vec<_>>()
+        };
+        // equal keys keep the order the drawables were added in
+        assert_eq!(
+            sorted(&sorting),
+            vec!["sky", "shadow", "player", "tree", "unknown layer", "ui"]
+        );
+
+        sorting.y_sort = true;
+        assert_eq!(
+            sorted(&sorting),
+            vec!["sky", "shadow", "unknown layer", "player", "tree", "ui"]
+        );
+
+        // without any layers everything is on the default layer
+        assert_eq!(
+            sorted(&DrawSorting::default()),
+            vec!["shadow", "player", "ui", "tree", "sky", "unknown layer"]
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub mod web;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod desktop;
 
+pub mod camera;
 pub mod core;
 
 pub mod drawable;
@@ -1,4 +1,4 @@
-use super::vertex::Vertex;
+use super::{drawable::batch::SpriteInstance, vertex::Vertex};
 use std::{collections::HashMap, rc::Rc};
 
 use wgpu::{Device, PrimitiveState, RenderPipeline, ShaderModule};
@@ -41,6 +41,20 @@ impl<'a> ShaderManager<'a> {
                 "sprite",
                 include_str!("shaders/sprite.wgsl"),
                 bindings,
+                &[Vertex::desc(), SpriteInstance::desc()],
+            );
+        }
+        {
+            // the background of cameras that don't cover the whole screen
+            let bindings = vec![crate::rendering::camera::get_clear_bind_group_layout(
+                device,
+            )];
+            self.load_shader(
+                device,
+                "clear",
+                include_str!("shaders/clear.wgsl"),
+                bindings,
+                &[Vertex::desc()],
             );
         }
         log!("Default shaders loaded");
@@ -52,6 +66,7 @@ impl<'a> ShaderManager<'a> {
         name: &'a str,
         shader: &str,
         bindings: Vec<wgpu::BindGroupLayout>,
+        buffers: &[wgpu::VertexBufferLayout],
     ) {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some(format!("{}_shader", name).as_str()),
@@ -76,7 +91,7 @@ impl<'a> ShaderManager<'a> {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
+                buffers,
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
@@ -0,0 +1,23 @@
+struct ClearUniform {
+    color: vec4<f32>,
+}
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> u_clear: ClearUniform;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> @builtin(position) vec4<f32> {
+    // the quad is a unit square, this makes it cover the viewport
+    return vec4<f32>(model.position.xy * 2.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return u_clear.color;
+}
@@ -2,44 +2,52 @@ struct CameraUniform {
     view: mat4x4<f32>,
     camera: mat4x4<f32>
 }
-struct Transform {
-    transform: mat4x4<f32>
-}
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
 }
+struct InstanceInput {
+    @location(2) transform_0: vec4<f32>,
+    @location(3) transform_1: vec4<f32>,
+    @location(4) transform_2: vec4<f32>,
+    @location(5) transform_3: vec4<f32>,
+    @location(6) uv: vec4<f32>,
+    @location(7) color: vec4<f32>,
+}
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
 }
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
-@group(1) @binding(0)
-var<uniform> transform: Transform;
 
 @vertex
 fn vs_main(
     model: VertexInput,
+    instance: InstanceInput,
 ) -> VertexOutput {
+    let transform = mat4x4<f32>(
+        instance.transform_0,
+        instance.transform_1,
+        instance.transform_2,
+        instance.transform_3,
+    );
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
+    // the quad covers the whole texture, the uv picks the sprite out of it
+    out.tex_coords = mix(instance.uv.xy, instance.uv.zw, model.tex_coords);
+    out.color = instance.color;
     out.clip_position = vec4<f32>(model.position, 1.0);
-    out.clip_position = out.clip_position * transform.transform * camera.view * camera.camera;
+    out.clip_position = out.clip_position * transform * camera.view * camera.camera;
     return out;
 }
-struct SpriteUniform {
-    color: vec4<f32>,
-}
-@group(1) @binding(1)
+@group(1) @binding(0)
 var t_diffuse: texture_2d<f32>;
-@group(1) @binding(2)
+@group(1) @binding(1)
 var s_diffuse: sampler;
-@group(1) @binding(3)
-var<uniform> u_sprite: SpriteUniform;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * u_sprite.color;
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color;
 }
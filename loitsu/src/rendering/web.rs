@@ -1,4 +1,5 @@
 use crate::ecs::ECS;
+use crate::engine::EngineOptions;
 use crate::log;
 use crate::scripting::ScriptingInstance;
 use crate::web::update_loading_status;
@@ -6,7 +7,7 @@ use std::sync::{Arc, RwLock};
 use winit::event_loop::EventLoop;
 use winit::platform::web::WindowExtWebSys;
 
-pub fn init_view<T>(scripting: T, ecs: Arc<RwLock<ECS<T>>>)
+pub fn init_view<T>(scripting: T, ecs: Arc<RwLock<ECS<T>>>, options: EngineOptions<T>)
 where
     T: ScriptingInstance + 'static,
 {
@@ -15,7 +16,7 @@ where
 
     let event_loop = EventLoop::new().unwrap();
     let window = winit::window::WindowBuilder::new()
-        .with_title("loitsu")
+        .with_title(options.window.title.as_str())
         .with_inner_size(winit::dpi::LogicalSize::new(20.0, 20.0))
         .build(&event_loop)
         .unwrap();
@@ -29,6 +30,6 @@ where
         })
         .expect("couldn't append canvas to document body");
     wasm_bindgen_futures::spawn_local(crate::rendering::core::run(
-        event_loop, window, scripting, ecs,
+        event_loop, window, scripting, ecs, options,
     ));
 }
@@ -3,7 +3,7 @@ use serde_json::{Map, Value};
 
 use bitcode;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{Debug, Display},
 };
 
@@ -13,7 +13,7 @@ use crate::ecs::Transform;
     feature = "scene_generation",
     derive(serde::Serialize, serde::Deserialize)
 )]
-#[derive(Clone, bitcode::Encode, bitcode::Decode)]
+#[derive(Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
 #[bitcode(recursive)]
 pub enum Property {
     String(String),
@@ -103,7 +103,7 @@ pub struct Entity {
 pub struct Component {
     pub name: String,
     pub id: u32,
-    pub properties: HashMap<String, Property>,
+    pub properties: BTreeMap<String, Property>,
 }
 
 impl Scene {
@@ -134,16 +134,24 @@ impl Scene {
     #[cfg(feature = "scene_generation")]
     pub fn to_json(&self) -> String {
         let mut entities = Vec::new();
-        let mut scene_entities = self.entities.clone();
-        let (scene_entities, id_space) = normalize_ids(&mut scene_entities);
-        for entity in scene_entities {
+        for entity in &self.entities {
             entities.push(entity.to_json());
         }
-        let mut scene: HashMap<&str, Value> = HashMap::new();
-        scene.insert("name", serde_json::Value::String(self.name.clone()));
-        scene.insert("entities", serde_json::Value::Array(entities));
+        // IDs are never renumbered, as scenes are diffed and merged by them
+        let id_space = self
+            .entities
+            .iter()
+            .filter_map(Entity::max_id)
+            .max()
+            .map_or(self.id_space, |id| self.id_space.max(id + 1));
+        let mut scene = Map::new();
+        scene.insert(
+            "name".to_string(),
+            serde_json::Value::String(self.name.clone()),
+        );
+        scene.insert("entities".to_string(), serde_json::Value::Array(entities));
         scene.insert(
-            "required_assets",
+            "required_assets".to_string(),
             serde_json::Value::Array(
                 self.required_assets
                     .clone()
@@ -153,52 +161,80 @@ impl Scene {
             ),
         );
         scene.insert(
-            "id_space",
+            "id_space".to_string(),
             serde_json::Value::Number(serde_json::Number::from(id_space)),
         );
-        serde_json::to_string(&scene).unwrap()
+        // serde_json keeps object keys sorted, so the output is the same for equal scenes
+        let mut json = serde_json::to_string_pretty(&Value::Object(scene)).unwrap();
+        json.push('\n');
+        json
     }
 
-    pub fn reserve_ids(&mut self) {
+    /// Moves the IDs of the scene to a freshly reserved range, returning the start of the range
+    pub fn reserve_ids(&mut self) -> u32 {
         let id_space_begin = crate::util::id::reserve_id_space(self.id_space);
         for entity in self.entities.iter_mut() {
             entity.reserve_ids(id_space_begin);
         }
+        id_space_begin
+    }
+
+    /// Undoes `reserve_ids`. Entities and components that were added after the IDs were reserved
+    /// get new IDs from the end of the ID space, so that the existing IDs never change.
+    pub fn release_ids(&mut self, id_space_begin: u32) {
+        let reserved = id_space_begin..id_space_begin + self.id_space;
+        let mut id_map = HashMap::new();
+        release_entity_ids(
+            &mut self.entities,
+            &reserved,
+            &mut id_map,
+            &mut self.id_space,
+        );
+        remap_references(&mut self.entities, &id_map);
     }
 }
 
-/// Will change all entities and components to the smallest possible IDs while preserving
-/// uniqueness
-#[allow(dead_code)]
-fn normalize_ids(entities: &mut Vec<Entity>) -> (Vec<Entity>, u32) {
-    let mut id_map: HashMap<u32, u32> = HashMap::new();
-    let mut next_id = 0;
+fn release_entity_ids(
+    entities: &mut [Entity],
+    reserved: &std::ops::Range<u32>,
+    id_map: &mut HashMap<u32, u32>,
+    id_space: &mut u32,
+) {
     for entity in entities.iter_mut() {
-        let old_id = entity.id;
-        let new_id = match id_map.get(&old_id) {
-            Some(id) => *id,
-            None => {
-                id_map.insert(old_id, next_id);
-                next_id += 1;
-                next_id - 1
-            }
-        };
-        entity.id = new_id;
+        release_id(&mut entity.id, reserved, id_map, id_space);
         for component in entity.components.iter_mut() {
-            let old_id = component.id;
-            let new_id = match id_map.get(&old_id) {
-                Some(id) => *id,
-                None => {
-                    id_map.insert(old_id, next_id);
-                    next_id += 1;
-                    next_id - 1
-                }
-            };
-            component.id = new_id;
+            release_id(&mut component.id, reserved, id_map, id_space);
+        }
+        release_entity_ids(&mut entity.children, reserved, id_map, id_space);
+    }
+}
+
+fn release_id(
+    id: &mut u32,
+    reserved: &std::ops::Range<u32>,
+    id_map: &mut HashMap<u32, u32>,
+    id_space: &mut u32,
+) {
+    let new_id = if reserved.contains(id) {
+        *id - reserved.start
+    } else {
+        *id_space += 1;
+        *id_space - 1
+    };
+    id_map.insert(*id, new_id);
+    *id = new_id;
+}
+
+/// Points entity and component references at the new IDs of `id_map`
+pub fn remap_references(entities: &mut [Entity], id_map: &HashMap<u32, u32>) {
+    for entity in entities.iter_mut() {
+        for component in entity.components.iter_mut() {
+            for property in component.properties.values_mut() {
+                property.remap_references(id_map);
+            }
         }
-        normalize_ids(&mut entity.children);
+        remap_references(&mut entity.children, id_map);
     }
-    (entities.clone(), next_id)
 }
 
 #[cfg(feature = "scene_generation")]
@@ -284,6 +320,14 @@ impl Entity {
         Value::Object(entity)
     }
 
+    /// The largest entity or component ID of the entity and its children
+    pub fn max_id(&self) -> Option<u32> {
+        std::iter::once(self.id)
+            .chain(self.components.iter().map(|component| component.id))
+            .chain(self.children.iter().filter_map(Entity::max_id))
+            .max()
+    }
+
     pub fn reserve_ids(&mut self, id_space_begin: u32) {
         self.id += id_space_begin;
         for component in self.components.iter_mut() {
@@ -302,12 +346,30 @@ impl Entity {
     }
 }
 
+impl Property {
+    pub fn remap_references(&mut self, id_map: &HashMap<u32, u32>) {
+        match self {
+            Property::EntityReference(id) | Property::ComponentReference(id) => {
+                if let Some(new_id) = id_map.get(id) {
+                    *id = *new_id;
+                }
+            }
+            Property::Array(items) => {
+                for item in items.iter_mut() {
+                    item.remap_references(id_map);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl Component {
     pub fn new(name: String, id: u32) -> Component {
         Component {
             name,
             id,
-            properties: HashMap::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -317,8 +379,8 @@ impl Component {
     #[cfg(feature = "scene_generation")]
     pub fn to_json(&self) -> Value {
         let mut properties = Map::new();
-        for property in self.properties.clone() {
-            properties.insert(property.0, property.1.to_json());
+        for (name, property) in &self.properties {
+            properties.insert(name.clone(), property.to_json());
         }
         let mut component = Map::new();
         component.insert(
@@ -361,7 +423,7 @@ fn json_value_as_property(value: Value) -> Property {
             }
             Property::String(s)
         }
-        Value::Number(n) => Property::Number(n.as_f64().unwrap() as f32),
+        Value::Number(_) | Value::Null => Property::Number(json_to_float(&value)),
         Value::Bool(b) => Property::Boolean(b),
         Value::Array(a) => {
             let mut out = Vec::new();
@@ -374,12 +436,38 @@ fn json_value_as_property(value: Value) -> Property {
     }
 }
 
+/// Converts a float to a JSON number using its shortest representation, so that values such as
+/// `0.1` are written as `0.1` instead of `0.10000000149011612`. JSON has no NaN, so it is written
+/// as `null`, and infinities are written as numbers too large for an `f32` so that they are read
+/// back as infinities.
+#[cfg(feature = "scene_generation")]
+pub fn float_to_json(n: f32) -> Value {
+    if n.is_nan() {
+        return Value::Null;
+    }
+    let n = if n.is_infinite() {
+        n.signum() as f64 * 1e39
+    } else {
+        n.to_string().parse::<f64>().unwrap()
+    };
+    Value::Number(serde_json::Number::from_f64(n).unwrap())
+}
+
+/// Reads a float written by `float_to_json`
+#[cfg(feature = "scene_generation")]
+pub fn json_to_float(value: &Value) -> f32 {
+    match value {
+        Value::Null => f32::NAN,
+        value => value.as_f64().unwrap() as f32,
+    }
+}
+
 #[cfg(feature = "scene_generation")]
 impl Property {
     pub fn to_json(&self) -> Value {
         match self {
             Property::String(s) => Value::String(s.clone()),
-            Property::Number(n) => Value::Number(serde_json::Number::from_f64(*n as f64).unwrap()),
+            Property::Number(n) => float_to_json(*n),
             Property::Boolean(b) => Value::Bool(*b),
             Property::Array(a) => {
                 let mut out = Vec::new();
@@ -388,7 +476,39 @@ impl Property {
                 }
                 Value::Array(out)
             }
-            _ => panic!("Unsupported property type!"),
+            Property::EntityReference(id) => Value::String(format!("EID{}", id)),
+            Property::ComponentReference(id) => Value::String(format!("CID{}", id)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_ids() {
+        let mut scene = Scene::new("test".to_string());
+        let mut entity = Entity::new("Player".to_string(), 0);
+        entity.add_component(Component::new("Health".to_string(), 1));
+        scene.add_entity(entity);
+        scene.id_space = 2;
+
+        let begin = scene.reserve_ids();
+        // an entity added at runtime, referenced by the existing component
+        let added = crate::util::id::get_unique_id();
+        scene.add_entity(Entity::new("Tree".to_string(), added));
+        scene.entities[0].components[0]
+            .add_property("target".to_string(), Property::EntityReference(added));
+
+        scene.release_ids(begin);
+        assert_eq!(scene.entities[0].id, 0);
+        assert_eq!(scene.entities[0].components[0].id, 1);
+        assert_eq!(scene.entities[1].id, 2);
+        assert_eq!(scene.id_space, 3);
+        assert_eq!(
+            scene.entities[0].components[0].properties.get("target"),
+            Some(&Property::EntityReference(2))
+        );
+    }
+}
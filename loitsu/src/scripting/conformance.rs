@@ -0,0 +1,161 @@
+use crate::ecs::ComponentFlags;
+use crate::headless::HeadlessRunner;
+use crate::scene_management::{Component, Entity, Property, Scene};
+use crate::scripting::{ScriptValue, ScriptingData, ScriptingInstance, ScriptingSource};
+use std::collections::BTreeMap;
+
+/// A scripting backend that can be checked with the conformance suite
+pub trait ConformanceBackend: ScriptingInstance + 'static {
+    /// Scripts in the language of the backend that implement what the suite expects:
+    ///
+    /// - `Echo::echo(value)` returns its argument unchanged
+    /// - `Math::add(a, b)` returns the sum of two numbers
+    /// - a `Counter` component with the fields `count`, starting at 0, and `label`. Its `frame`
+    ///   method adds 1 to `count`.
+    /// - a `Broken` component whose `frame` method always fails
+    fn conformance_sources() -> Vec<ScriptingSource>;
+}
+
+/// Runs every conformance check against the backend, panicking on the first one that fails.
+/// Meant to be called from a test of the backend.
+pub fn run<T: ConformanceBackend>() {
+    check_values::<T>();
+    check_components::<T>();
+}
+
+fn new_runner<T: ConformanceBackend>() -> HeadlessRunner<T> {
+    let mut scene = Scene::new("conformance".to_string());
+    // the broken component runs first, so that it could stop the ones after it
+    let mut broken = Entity::new("broken".to_string(), 1);
+    broken
+        .components
+        .push(Component::new("Broken".to_string(), 2));
+    scene.add_entity(broken);
+    let mut counter = Entity::new("counter".to_string(), 3);
+    let mut component = Component::new("Counter".to_string(), 4);
+    component.add_property("label".to_string(), Property::String("hello".to_string()));
+    counter.components.push(component);
+    scene.add_entity(counter);
+    scene.id_space = 5;
+    match HeadlessRunner::<T>::new(T::conformance_sources(), scene) {
+        Ok(runner) => runner,
+        Err(e) => panic!("Couldn't load the conformance scripts: {}", e),
+    }
+}
+
+fn check_values<T: ConformanceBackend>() {
+    let mut runner = new_runner::<T>();
+    let scripting = &mut runner.scripting;
+    let values = vec![
+        ScriptValue::Unit,
+        ScriptValue::Bool(true),
+        ScriptValue::Integer(-42),
+        ScriptValue::Float(1.5),
+        ScriptValue::String("loitsu".to_string()),
+        ScriptValue::Array(vec![
+            ScriptValue::Integer(1),
+            "two".into(),
+            ScriptValue::Array(vec![]),
+        ]),
+        ScriptValue::Object(BTreeMap::from([
+            ("x".to_string(), 1.0.into()),
+            ("name".to_string(), "a".into()),
+        ])),
+    ];
+    for value in values {
+        match scripting.call(["Echo", "echo"], vec![value.clone()]) {
+            Ok(echoed) => assert_eq!(echoed, value, "Echo::echo changed the value"),
+            Err(e) => panic!("Echo::echo({}) failed: {}", value, e),
+        }
+    }
+
+    let sum = scripting
+        .call(
+            ["Math", "add"],
+            vec![ScriptValue::Integer(2), ScriptValue::Integer(3)],
+        )
+        .unwrap_or_else(|e| panic!("Math::add failed: {}", e));
+    assert_eq!(sum.as_f64(), Some(5.0), "Math::add returned {}", sum);
+
+    assert!(
+        scripting.call(["Math", "missing"], vec![]).is_err(),
+        "calling a function that doesn't exist should fail"
+    );
+}
+
+fn check_components<T: ConformanceBackend>() {
+    let mut runner = new_runner::<T>();
+    let flags = runner.scripting.get_component_flags("Counter");
+    assert!(
+        flags.contains(ComponentFlags::FRAME),
+        "Counter has a frame method"
+    );
+    assert!(
+        !flags.contains(ComponentFlags::TICK),
+        "Counter has no tick method"
+    );
+    assert_eq!(
+        runner.scripting.get_component_flags("Missing"),
+        ComponentFlags::EMPTY,
+        "components that don't exist have no methods"
+    );
+
+    assert_eq!(counter_property(&runner, "count"), Property::Number(0.0));
+    assert_eq!(
+        counter_property(&runner, "label"),
+        Property::String("hello".to_string()),
+        "properties from the scene should be assigned"
+    );
+    // Broken fails on every frame without keeping Counter from running
+    for _ in 0..3 {
+        runner.step(1.0 / 60.0);
+    }
+    assert_eq!(counter_property(&runner, "count"), Property::Number(3.0));
+
+    with_counter(&runner, |component| {
+        component
+            .set_property("count", Property::Number(10.0))
+            .expect("Couldn't set the count of Counter");
+    });
+    runner.step(1.0 / 60.0);
+    assert_eq!(counter_property(&runner, "count"), Property::Number(11.0));
+
+    // reloading keeps the values of the fields
+    let ecs = runner.ecs.clone();
+    ecs.read()
+        .unwrap()
+        .reload_scripts(&mut runner.scripting, T::conformance_sources())
+        .unwrap_or_else(|e| panic!("Couldn't reload the conformance scripts: {}", e));
+    assert_eq!(
+        counter_property(&runner, "count"),
+        Property::Number(11.0),
+        "reloading the scripts should keep the values of the fields"
+    );
+    runner.step(1.0 / 60.0);
+    assert_eq!(counter_property(&runner, "count"), Property::Number(12.0));
+}
+
+fn with_counter<T: ConformanceBackend, R>(
+    runner: &HeadlessRunner<T>,
+    f: impl FnOnce(&mut T::Data) -> R,
+) -> R {
+    for entity in runner.ecs.read().unwrap().get_all_runtime_entities_flat() {
+        let mut entity = entity.lock().unwrap();
+        for component in entity.components.iter_mut() {
+            if component.component_proto.name == "Counter" {
+                return f(&mut component.data);
+            }
+        }
+    }
+    panic!("The Counter component wasn't loaded");
+}
+
+fn counter_property<T: ConformanceBackend>(runner: &HeadlessRunner<T>, name: &str) -> Property {
+    let proto = Component::new("Counter".to_string(), 0);
+    let proto = with_counter(runner, |data| data.to_component_proto(&proto))
+        .unwrap_or_else(|e| panic!("Couldn't read the fields of Counter: {}", e));
+    match proto.properties.get(name) {
+        Some(property) => property.clone(),
+        None => panic!("Counter has no field '{}'", name),
+    }
+}
@@ -0,0 +1,77 @@
+use super::ScriptingSource;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches the `.rn` files in the project's assets directory and hands out a fresh set of
+/// sources whenever one of them is added, changed or removed
+pub struct ScriptWatcher {
+    assets_path: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+    last_poll: Instant,
+}
+
+impl ScriptWatcher {
+    /// Creates a watcher for the `assets` directory in the current working directory. If there is
+    /// no such directory (e.g. in a shipped build) the watcher never reports any changes.
+    pub fn new() -> ScriptWatcher {
+        let assets_path = std::env::current_dir().unwrap_or_default().join("assets");
+        let mut modified = HashMap::new();
+        collect_scripts(&assets_path, &mut modified);
+        ScriptWatcher {
+            assets_path,
+            modified,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Returns all scripts if any of them have changed since the last poll
+    pub fn poll(&mut self) -> Option<Vec<ScriptingSource>> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+        let mut modified = HashMap::new();
+        collect_scripts(&self.assets_path, &mut modified);
+        if modified == self.modified {
+            return None;
+        }
+
+        let mut sources = Vec::new();
+        for path in modified.keys() {
+            // the file might be mid-write, so the change is only taken once every file could be
+            // read, until then it is retried on every poll
+            let source = std::fs::read_to_string(path).ok()?;
+            sources.push(ScriptingSource {
+                name: path.file_name().unwrap().to_str().unwrap().to_string(),
+                source,
+            });
+        }
+        self.modified = modified;
+        Some(sources)
+    }
+}
+
+impl Default for ScriptWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn collect_scripts(path: &Path, scripts: &mut HashMap<PathBuf, SystemTime>) {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_scripts(&path, scripts);
+        } else if path.extension().is_some_and(|extension| extension == "rn") {
+            if let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) {
+                scripts.insert(path, modified);
+            }
+        }
+    }
+}
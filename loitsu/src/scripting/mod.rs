@@ -1,4 +1,11 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod conformance;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hot_reload;
+pub mod native;
+pub mod profiler;
 pub mod rune_runtime;
+mod value;
 use crate::ecs::{ComponentFlags, RuntimeTransform, ECS};
 use crate::input::InputState;
 use crate::scene_management::Property;
@@ -10,12 +17,31 @@ use bitcode;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, Mutex, RwLock};
+pub use value::ScriptValue;
 
 pub type Result<T> = std::result::Result<T, ScriptingError>;
 
 #[derive(Debug, Clone)]
 pub struct ScriptingError {
     message: String,
+    location: Option<SourceLocation>,
+    stack: Vec<StackFrame>,
+}
+
+/// A position in a script. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub source: String,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+/// A component method that was being executed when an error occurred
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub component: String,
+    pub method: String,
 }
 
 #[derive(Debug, Clone, bitcode::Encode, bitcode::Decode)]
@@ -24,17 +50,163 @@ pub struct ScriptingSource {
     pub source: String,
 }
 
+/// The scripts as they were compiled when the project was built. On startup the compiled unit is
+/// loaded instead of compiling the scripts again, as long as the version and sources match.
+#[derive(Debug, Clone, PartialEq, bitcode::Encode, bitcode::Decode)]
+pub struct ValidatedScripts {
+    /// Identifies the engine and compiler that checked the scripts
+    pub version: String,
+    /// The `hash_sources` of the sources that were checked
+    pub source_hash: u64,
+    /// The compiled unit, in a format only the scripting backend that built it understands
+    pub unit: Vec<u8>,
+}
+
+impl ValidatedScripts {
+    /// Hashes the names and contents of the sources. Unlike the std hasher this is the same on
+    /// every platform and build, as the hash is stored in the static shard.
+    pub fn hash_sources(sources: &[ScriptingSource]) -> u64 {
+        // FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for source in sources {
+            for part in [&source.name, &source.source] {
+                for byte in part.bytes().chain(std::iter::once(0)) {
+                    hash ^= byte as u64;
+                    hash = hash.wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+        hash
+    }
+
+    /// Whether `sources` were checked by the given version
+    pub fn matches(&self, version: &str, sources: &[ScriptingSource]) -> bool {
+        self.version == version && self.source_hash == Self::hash_sources(sources)
+    }
+}
+
 impl ScriptingError {
     pub fn new(message: &str) -> Self {
         Self {
             message: message.to_string(),
+            location: None,
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    pub fn with_location(mut self, location: SourceLocation) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Pushes a frame to the stack, the innermost frame should be pushed first
+    pub fn with_frame(mut self, component: &str, method: &str) -> Self {
+        self.stack.push(StackFrame {
+            component: component.to_string(),
+            method: method.to_string(),
+        });
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    pub fn stack(&self) -> &[StackFrame] {
+        &self.stack
+    }
+}
+
+impl SourceLocation {
+    /// Resolves a byte offset in the given source
+    pub fn from_offset(source: &ScriptingSource, offset: usize) -> Self {
+        let offset = offset.min(source.source.len());
+        let line_start = source.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source.source[offset..]
+            .find('\n')
+            .map_or(source.source.len(), |i| offset + i);
+        Self {
+            source: source.name.clone(),
+            line: source.source[..line_start].matches('\n').count() + 1,
+            column: source.source[line_start..offset].chars().count() + 1,
+            snippet: source.source[line_start..line_end].trim_end().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.source, self.line, self.column)
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // free functions don't belong to a component
+        if self.component.is_empty() {
+            return write!(f, "{}", self.method);
         }
+        write!(f, "{}::{}", self.component, self.method)
     }
 }
 
 impl fmt::Display for ScriptingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        write!(f, "{}", self.message)?;
+        if let Some(location) = &self.location {
+            let gutter = " ".repeat(location.line.to_string().len());
+            write!(f, "\n{} --> {}", gutter, location)?;
+            write!(f, "\n{} |", gutter)?;
+            write!(f, "\n{} | {}", location.line, location.snippet)?;
+            write!(
+                f,
+                "\n{} | {}^",
+                gutter,
+                " ".repeat(location.column.saturating_sub(1))
+            )?;
+        }
+        for frame in &self.stack {
+            write!(f, "\n    in {}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// Limits on how many instructions component methods may run, so that a runaway loop in a
+/// script can't hang the game
+#[cfg_attr(
+    feature = "json_preference_parse",
+    derive(serde::Deserialize),
+    serde(default)
+)]
+#[derive(Debug, Clone, bitcode::Encode, bitcode::Decode)]
+pub struct ScriptBudget {
+    /// The instructions a single component method may run, `None` for no limit
+    pub method_instructions: Option<u64>,
+    /// The instructions all component methods of a frame may run together, `None` for no limit.
+    /// Keeping track of this has a cost proportional to the unused method budgets, as rune only
+    /// lets us find out what is left of a budget by using it up.
+    pub frame_instructions: Option<u64>,
+    /// How many times in a row a component may run out of instructions before it is disabled
+    pub max_failures: u32,
+}
+
+impl Default for ScriptBudget {
+    fn default() -> Self {
+        Self {
+            method_instructions: Some(1_000_000),
+            frame_instructions: None,
+            max_failures: 3,
+        }
     }
 }
 
@@ -58,9 +230,14 @@ pub trait ScriptingInstance: Sized {
         input_state: Arc<Mutex<InputState>>,
         ecs: Arc<RwLock<ECS<Self>>>,
     ) -> Result<()>;
-    fn call<T>(&mut self, path: [&str; 2], args: T) -> Result<rune::runtime::Value>
-    where
-        T: rune::runtime::Args;
+    /// Hands over the scripts compiled at build time, before `initialize`. They are used instead
+    /// of compiling the sources if they match.
+    fn set_validated(&mut self, _validated: ValidatedScripts) {}
+    /// Recompiles the scripts of an initialized instance. If compilation fails the previously
+    /// loaded scripts are kept running.
+    fn reload(&mut self, sources: Vec<ScriptingSource>) -> Result<()>;
+    /// Calls a function of the scripts, `path` being the type (or module) and function name
+    fn call(&mut self, path: [&str; 2], args: Vec<ScriptValue>) -> Result<ScriptValue>;
     fn run_component_methods<T>(
         &mut self,
         entities: &[Arc<Mutex<crate::ecs::RuntimeEntity<Self>>>],
@@ -68,6 +245,9 @@ pub trait ScriptingInstance: Sized {
         method: ComponentFlags,
     ) -> Vec<(Arc<Mutex<RuntimeTransform>>, Vec<EntityUpdate>)>;
     fn get_component_flags(&self, component_name: &str) -> ComponentFlags;
+    fn set_budget(&mut self, budget: ScriptBudget);
+    /// Called at the start of every frame, before any component methods run
+    fn begin_frame(&mut self);
 }
 
 pub trait ScriptingData<T>
@@ -79,4 +259,25 @@ where
         Self: Sized;
     fn to_component_proto(&self, proto: &Component) -> Result<Component>;
     fn set_property(&mut self, name: &str, value: Property) -> Result<()>;
+    /// Re-creates the component data after the scripts have been reloaded, carrying over the
+    /// values of all fields that still exist
+    fn rebind(&mut self, proto: &Component, instance: &mut T) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_location() {
+        let source = ScriptingSource {
+            name: "player.rn".to_string(),
+            source: "pub fn frame() {\n    let x = y;\n}\n".to_string(),
+        };
+        let offset = source.source.find('y').unwrap();
+        let location = SourceLocation::from_offset(&source, offset);
+        assert_eq!(location.source, "player.rn");
+        assert_eq!((location.line, location.column), (2, 13));
+        assert_eq!(location.snippet, "    let x = y;");
+    }
 }
@@ -0,0 +1,455 @@
+use crate::ecs::{ComponentFlags, RuntimeEntity, RuntimeTransform, Transform, ECS};
+use crate::input::InputState;
+use crate::rendering::drawable::{DrawableProperty, DrawablePrototype};
+use crate::scene_management::{self, Property};
+use crate::scripting::{
+    EntityUpdate, Result, ScriptBudget, ScriptValue, ScriptingData, ScriptingError,
+    ScriptingInstance, ScriptingSource,
+};
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+
+lazy_static! {
+    static ref REGISTRY: RwLock<HashMap<String, Registration>> = RwLock::new(builtin_components());
+}
+
+/// A component implemented in Rust. Native components are registered by name with
+/// `register_component`, after which scenes can use them like any scripted component.
+pub trait Component: Send + 'static {
+    /// Creates the component from the properties it was given in the scene
+    fn new(properties: &BTreeMap<String, Property>) -> Self
+    where
+        Self: Sized;
+
+    /// The methods the component implements, the others are never called
+    fn methods() -> ComponentFlags
+    where
+        Self: Sized,
+    {
+        ComponentFlags::START
+            | ComponentFlags::FRAME
+            | ComponentFlags::TICK
+            | ComponentFlags::DESTROY
+    }
+
+    fn start(&mut self, _entity: &mut EntityContext) {}
+    fn frame(&mut self, _entity: &mut EntityContext) {}
+    fn tick(&mut self, _entity: &mut EntityContext) {}
+    fn destroy(&mut self, _entity: &mut EntityContext) {}
+
+    /// The fields that are saved with the scene
+    fn properties(&self) -> BTreeMap<String, Property> {
+        BTreeMap::new()
+    }
+
+    fn set_property(&mut self, name: &str, _value: Property) -> Result<()> {
+        Err(ScriptingError::new(&format!("Unknown property '{}'", name)))
+    }
+}
+
+struct Registration {
+    new: fn(&BTreeMap<String, Property>) -> Box<dyn Component>,
+    methods: ComponentFlags,
+}
+
+fn registration<C: Component>() -> Registration {
+    Registration {
+        new: |properties| Box::new(C::new(properties)),
+        methods: C::methods(),
+    }
+}
+
+/// The native components of the engine itself
+fn builtin_components() -> HashMap<String, Registration> {
+    HashMap::from([(
+        "Camera".to_string(),
+        registration::<crate::rendering::camera::CameraComponent>(),
+    )])
+}
+
+/// Makes a native component available to scenes under the given name. Scripts that define a
+/// component with the same name, including the builtin `Camera`, fail to load.
+pub fn register_component<C: Component>(name: &str) {
+    REGISTRY
+        .write()
+        .unwrap()
+        .insert(name.to_string(), registration::<C>());
+}
+
+pub fn is_registered(name: &str) -> bool {
+    REGISTRY.read().unwrap().contains_key(name)
+}
+
+/// The names of every registered native component
+pub(crate) fn names() -> Vec<String> {
+    REGISTRY.read().unwrap().keys().cloned().collect()
+}
+
+/// Creates the native component of a scene component, `None` if there isn't one by its name
+pub(crate) fn create(proto: &scene_management::Component) -> Option<Box<dyn Component>> {
+    let registry = REGISTRY.read().unwrap();
+    registry
+        .get(&proto.name)
+        .map(|registration| (registration.new)(&proto.properties))
+}
+
+/// The methods of a native component, `None` if there isn't one by the name
+pub(crate) fn methods(name: &str) -> Option<ComponentFlags> {
+    let registry = REGISTRY.read().unwrap();
+    registry.get(name).map(|registration| registration.methods)
+}
+
+/// The entity a native component belongs to. Changes to the name and transform are applied once
+/// all native components of the entity have run.
+pub struct EntityContext<'a> {
+    pub id: u32,
+    pub name: String,
+    pub transform: Transform,
+    /// `None` while scenes are being built
+    pub input: Option<&'a InputState>,
+    updates: Vec<EntityUpdate>,
+}
+
+impl EntityContext<'_> {
+    /// The duration of the current frame, in seconds
+    pub fn delta_time(&self) -> f32 {
+        self.input.map_or(0.0, |input| input.delta_time)
+    }
+
+    pub fn position(&self) -> (f32, f32) {
+        match &self.transform {
+            Transform::Transform2D { position, .. } => *position,
+            Transform::RectTransform { position } => *position,
+        }
+    }
+
+    pub fn set_position(&mut self, new_position: (f32, f32)) {
+        match &mut self.transform {
+            Transform::Transform2D { position, .. } => *position = new_position,
+            Transform::RectTransform { position } => *position = new_position,
+        }
+    }
+
+    pub fn register_drawable(&mut self, sprite: &str, color: [f32; 4]) -> u32 {
+        let id = crate::util::id::get_unique_id();
+        self.updates
+            .push(EntityUpdate::AddDrawable(DrawablePrototype::Sprite {
+                sprite: sprite.to_string(),
+                color,
+                order: Default::default(),
+                id,
+            }));
+        id
+    }
+
+    pub fn unregister_drawable(&mut self, id: u32) {
+        self.updates.push(EntityUpdate::RemoveDrawable(id));
+    }
+
+    pub fn set_drawable_property(&mut self, id: u32, property: &str, value: DrawableProperty) {
+        self.updates.push(EntityUpdate::SetDrawableProperty(
+            id,
+            property.to_string(),
+            value,
+        ));
+    }
+}
+
+/// Runs a method of the native components of an entity. `native` picks the native component out
+/// of the data of a component, if it has one.
+pub(crate) fn run_methods<T: ScriptingInstance>(
+    entity: &mut RuntimeEntity<T>,
+    method: ComponentFlags,
+    input_state: Option<&Arc<Mutex<InputState>>>,
+    native: fn(&mut T::Data) -> Option<&mut dyn Component>,
+) -> Vec<EntityUpdate> {
+    #[cfg(feature = "disable_common_ecs_methods")]
+    {
+        if method == ComponentFlags::START || method == ComponentFlags::DESTROY {
+            return Vec::new();
+        }
+    }
+    let input = input_state.map(|input_state| input_state.lock().unwrap());
+    let transform = entity.transform.lock().unwrap().transform.clone();
+    let mut context = EntityContext {
+        id: entity.get_id(),
+        name: entity.get_name().to_string(),
+        transform: transform.clone(),
+        input: input.as_deref(),
+        updates: Vec::new(),
+    };
+    let mut ran = false;
+    for component in entity.components.iter_mut() {
+        if component.flags & method != method {
+            continue;
+        }
+        let Some(native) = native(&mut component.data) else {
+            continue;
+        };
+        ran = true;
+        match method {
+            ComponentFlags::START => native.start(&mut context),
+            ComponentFlags::FRAME => native.frame(&mut context),
+            ComponentFlags::TICK => native.tick(&mut context),
+            ComponentFlags::DESTROY => native.destroy(&mut context),
+            _ => {}
+        }
+    }
+    if !ran {
+        return Vec::new();
+    }
+    if context.name != entity.get_name() {
+        entity.set_name(context.name);
+    }
+    if context.transform != transform {
+        let mut runtime_transform = entity.transform.lock().unwrap();
+        runtime_transform.transform = context.transform;
+        runtime_transform.has_changed = true;
+    }
+    context.updates
+}
+
+/// A scripting backend that only runs native components
+pub struct NativeInstance {
+    input_state: Option<Arc<Mutex<InputState>>>,
+}
+
+pub struct NativeData {
+    pub component: Option<Box<dyn Component>>,
+}
+
+impl ScriptingInstance for NativeInstance {
+    type Data = NativeData;
+
+    fn new_with_sources(
+        _sources: Vec<ScriptingSource>,
+        _ecs: Arc<RwLock<ECS<Self>>>,
+    ) -> Result<Self> {
+        Self::new_uninitialized()
+    }
+
+    fn new_uninitialized() -> Result<Self> {
+        Ok(NativeInstance { input_state: None })
+    }
+
+    fn initialize(
+        &mut self,
+        _sources: Vec<ScriptingSource>,
+        input_state: Arc<Mutex<InputState>>,
+        _ecs: Arc<RwLock<ECS<Self>>>,
+    ) -> Result<()> {
+        self.input_state = Some(input_state);
+        Ok(())
+    }
+
+    fn reload(&mut self, _sources: Vec<ScriptingSource>) -> Result<()> {
+        Ok(())
+    }
+
+    fn call(&mut self, path: [&str; 2], _args: Vec<ScriptValue>) -> Result<ScriptValue> {
+        Err(ScriptingError::new(&format!(
+            "Native components have no function {}::{}",
+            path[0], path[1]
+        )))
+    }
+
+    fn run_component_methods<T>(
+        &mut self,
+        entities: &[Arc<Mutex<RuntimeEntity<Self>>>],
+        _lookup: HashMap<u32, Arc<Mutex<RuntimeEntity<Self>>>>,
+        method: ComponentFlags,
+    ) -> Vec<(Arc<Mutex<RuntimeTransform>>, Vec<EntityUpdate>)> {
+        let mut updates = Vec::new();
+        let run = |entity: &mut RuntimeEntity<Self>| {
+            let mut entity_updates = Vec::new();
+            if entity.is_new {
+                entity_updates.extend(run_methods(
+                    entity,
+                    ComponentFlags::START,
+                    self.input_state.as_ref(),
+                    |data| data.component.as_deref_mut(),
+                ));
+                entity.is_new = false;
+            }
+            entity_updates.extend(run_methods(
+                entity,
+                method,
+                self.input_state.as_ref(),
+                |data| data.component.as_deref_mut(),
+            ));
+            (entity.transform.clone(), entity_updates)
+        };
+        for entity in entities {
+            let mut entity = entity.lock().unwrap();
+            updates.push(run(&mut entity));
+            for child in &entity.children {
+                updates.push(run(&mut child.lock().unwrap()));
+            }
+        }
+        updates
+    }
+
+    fn get_component_flags(&self, component_name: &str) -> ComponentFlags {
+        methods(component_name).unwrap_or(ComponentFlags::EMPTY)
+    }
+
+    // native code isn't budgeted
+    fn set_budget(&mut self, _budget: ScriptBudget) {}
+
+    fn begin_frame(&mut self) {}
+}
+
+impl ScriptingData<NativeInstance> for NativeData {
+    fn from_component_proto(
+        proto: scene_management::Component,
+        _instance: &mut NativeInstance,
+    ) -> Result<Self> {
+        let component = create(&proto);
+        if component.is_none() {
+            crate::error!(
+                "No native component named '{}' has been registered",
+                proto.name
+            );
+        }
+        Ok(NativeData { component })
+    }
+
+    fn to_component_proto(
+        &self,
+        proto: &scene_management::Component,
+    ) -> Result<scene_management::Component> {
+        let mut proto = proto.clone();
+        if let Some(component) = &self.component {
+            proto.properties.extend(component.properties());
+        }
+        Ok(proto)
+    }
+
+    fn set_property(&mut self, name: &str, value: Property) -> Result<()> {
+        match &mut self.component {
+            Some(component) => component.set_property(name, value),
+            None => Ok(()),
+        }
+    }
+
+    // native components aren't affected by reloading scripts
+    fn rebind(
+        &mut self,
+        _proto: &scene_management::Component,
+        _instance: &mut NativeInstance,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::headless::HeadlessRunner;
+    use crate::scene_management::{Entity, Scene};
+    use crate::scripting::rune_runtime::RuneInstance;
+
+    struct Mover {
+        speed: f32,
+    }
+
+    impl Component for Mover {
+        fn new(properties: &BTreeMap<String, Property>) -> Self {
+            let speed = match properties.get("speed") {
+                Some(Property::Number(speed)) => *speed,
+                _ => 1.0,
+            };
+            Mover { speed }
+        }
+
+        fn methods() -> ComponentFlags {
+            ComponentFlags::FRAME
+        }
+
+        fn frame(&mut self, entity: &mut EntityContext) {
+            let (x, y) = entity.position();
+            entity.set_position((x + self.speed, y));
+        }
+
+        fn properties(&self) -> BTreeMap<String, Property> {
+            BTreeMap::from([("speed".to_string(), Property::Number(self.speed))])
+        }
+    }
+
+    /// Keeps a native component registered until it is dropped, so the registration doesn't
+    /// leak into other tests
+    struct Registered(&'static str);
+
+    impl Registered {
+        fn new<C: Component>(name: &'static str) -> Registered {
+            register_component::<C>(name);
+            Registered(name)
+        }
+    }
+
+    impl Drop for Registered {
+        fn drop(&mut self) {
+            REGISTRY.write().unwrap().remove(self.0);
+        }
+    }
+
+    fn scene() -> Scene {
+        let mut scene = Scene::new("native".to_string());
+        let mut entity = Entity::new("mover".to_string(), 1);
+        let mut mover = scene_management::Component::new("Mover".to_string(), 2);
+        mover.add_property("speed".to_string(), Property::Number(2.0));
+        entity.components.push(mover);
+        entity
+            .components
+            .push(scene_management::Component::new("Counter".to_string(), 3));
+        scene.add_entity(entity);
+        scene.id_space = 4;
+        scene
+    }
+
+    fn position<T: ScriptingInstance>(runner: &HeadlessRunner<T>) -> (f32, f32) {
+        let entity = runner.ecs.read().unwrap().get_runtime_entities()[0].clone();
+        let entity = entity.lock().unwrap();
+        let transform = entity.transform.lock().unwrap();
+        match transform.transform {
+            Transform::Transform2D { position, .. } => position,
+            Transform::RectTransform { position } => position,
+        }
+    }
+
+    #[test]
+    fn test_native_components() {
+        let _mover = Registered::new::<Mover>("Mover");
+        let scripts = vec![ScriptingSource {
+            name: "counter.rn".to_string(),
+            source: "pub struct Counter { count }
+                impl Counter {
+                    pub fn new() { Counter { count: 0.0 } }
+                    pub fn frame(self, entity) { self.count += 1.0; }
+                }"
+            .to_string(),
+        }];
+
+        // native and scripted components on the same entity
+        let mut runner = HeadlessRunner::<RuneInstance>::new(scripts, scene()).unwrap();
+        runner.step(1.0 / 60.0);
+        runner.step(1.0 / 60.0);
+        assert_eq!(position(&runner), (4.0, 0.0));
+        let saved = runner.ecs.read().unwrap().as_scene();
+        let components = &saved.entities[0].components;
+        assert_eq!(
+            components[0].properties.get("speed"),
+            Some(&Property::Number(2.0))
+        );
+        assert_eq!(
+            components[1].properties.get("count"),
+            Some(&Property::Number(2.0))
+        );
+
+        // only native components
+        let mut runner = HeadlessRunner::<NativeInstance>::new(Vec::new(), scene()).unwrap();
+        runner.step(1.0 / 60.0);
+        assert_eq!(position(&runner), (2.0, 0.0));
+    }
+}
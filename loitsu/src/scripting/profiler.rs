@@ -0,0 +1,277 @@
+use lazy_static::lazy_static;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The amount of most recent calls the statistics of a method are calculated over
+const WINDOW: usize = 240;
+/// How often the overlay is refreshed, in seconds
+const OVERLAY_INTERVAL: f32 = 0.5;
+
+lazy_static! {
+    pub static ref PROFILER: Mutex<Profiler> = Mutex::new(Profiler::new());
+}
+
+#[derive(Default)]
+struct MethodStats {
+    calls: u64,
+    /// In milliseconds
+    durations: VecDeque<f64>,
+    /// Only the calls that ran within an instruction budget are counted
+    instructions: VecDeque<usize>,
+}
+
+/// Collects how long component methods take and how many instructions they run, per component
+/// type and method
+pub struct Profiler {
+    enabled: bool,
+    overlay: bool,
+    overlay_shown: bool,
+    last_overlay: Option<instant::Instant>,
+    stats: HashMap<String, HashMap<String, MethodStats>>,
+}
+
+/// The statistics of a single component method over its most recent calls
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodProfile {
+    pub component: String,
+    pub method: String,
+    /// Every call since profiling started, not just the ones the statistics are calculated over
+    pub calls: u64,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// The mean instructions of the calls that ran within an instruction budget, `None` if there
+    /// were none
+    pub instructions: Option<f64>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            enabled: false,
+            overlay: false,
+            overlay_shown: false,
+            last_overlay: None,
+            stats: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled || self.overlay
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Showing the overlay also enables profiling
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+        self.last_overlay = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.stats.clear();
+    }
+
+    pub fn record(
+        &mut self,
+        component: &str,
+        method: &str,
+        duration: Duration,
+        instructions: Option<usize>,
+    ) {
+        // the keys are only allocated the first time a method is seen
+        if !self.stats.contains_key(component) {
+            self.stats.insert(component.to_string(), HashMap::new());
+        }
+        let methods = self.stats.get_mut(component).unwrap();
+        if !methods.contains_key(method) {
+            methods.insert(method.to_string(), MethodStats::default());
+        }
+        let stats = methods.get_mut(method).unwrap();
+        stats.calls += 1;
+        if stats.durations.len() == WINDOW {
+            stats.durations.pop_front();
+        }
+        stats.durations.push_back(duration.as_secs_f64() * 1000.0);
+        if let Some(instructions) = instructions {
+            if stats.instructions.len() == WINDOW {
+                stats.instructions.pop_front();
+            }
+            stats.instructions.push_back(instructions);
+        }
+    }
+
+    /// Returns the statistics of every method, the slowest first
+    pub fn report(&self) -> Vec<MethodProfile> {
+        let mut report = Vec::new();
+        for (component, methods) in &self.stats {
+            for (method, stats) in methods {
+                let mut durations: Vec<f64> = stats.durations.iter().copied().collect();
+                durations.sort_by(|a, b| a.total_cmp(b));
+                let count = durations.len().max(1) as f64;
+                let p95_index = ((durations.len() as f64 * 0.95).ceil() as usize).max(1) - 1;
+                report.push(MethodProfile {
+                    component: component.clone(),
+                    method: method.clone(),
+                    calls: stats.calls,
+                    mean_ms: durations.iter().sum::<f64>() / count,
+                    p95_ms: durations.get(p95_index).copied().unwrap_or(0.0),
+                    max_ms: durations.last().copied().unwrap_or(0.0),
+                    instructions: (!stats.instructions.is_empty()).then(|| {
+                        stats.instructions.iter().sum::<usize>() as f64
+                            / stats.instructions.len() as f64
+                    }),
+                });
+            }
+        }
+        report.sort_by(|a, b| {
+            b.mean_ms
+                .total_cmp(&a.mean_ms)
+                .then_with(|| a.component.cmp(&b.component))
+                .then_with(|| a.method.cmp(&b.method))
+        });
+        report
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new()
+    }
+}
+
+/// Formats the report as a table
+pub fn format_report(report: &[MethodProfile]) -> String {
+    let names: Vec<String> = report
+        .iter()
+        .map(|profile| format!("{}::{}", profile.component, profile.method))
+        .collect();
+    let width = names
+        .iter()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let mut table = format!(
+        "{:<width$} {:>8} {:>9} {:>9} {:>9} {:>12}\n",
+        "method", "calls", "mean ms", "p95 ms", "max ms", "instructions"
+    );
+    for (name, profile) in names.iter().zip(report) {
+        let instructions = profile
+            .instructions
+            .map(|x| format!("{:.0}", x))
+            .unwrap_or_else(|| "-".to_string());
+        table.push_str(&format!(
+            "{:<width$} {:>8} {:>9.3} {:>9.3} {:>9.3} {:>12}\n",
+            name, profile.calls, profile.mean_ms, profile.p95_ms, profile.max_ms, instructions
+        ));
+    }
+    table
+}
+
+/// Refreshes the profiler overlay if it is enabled. Desktop builds can't draw text, so there the
+/// slowest methods are shown in the window title instead, after the title the game set.
+pub fn update_overlay(window: &winit::window::Window, title: &str) {
+    let mut profiler = PROFILER.lock().unwrap();
+    if !profiler.overlay {
+        if profiler.overlay_shown {
+            profiler.overlay_shown = false;
+            show_overlay(window, title, None);
+        }
+        return;
+    }
+    let now = instant::Instant::now();
+    if let Some(last) = profiler.last_overlay {
+        if now.duration_since(last).as_secs_f32() < OVERLAY_INTERVAL {
+            return;
+        }
+    }
+    profiler.last_overlay = Some(now);
+    profiler.overlay_shown = true;
+    let report = profiler.report();
+    drop(profiler);
+    show_overlay(window, title, Some(&report));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn show_overlay(_window: &winit::window::Window, _title: &str, report: Option<&[MethodProfile]>) {
+    crate::web::set_profiler_overlay(&report.map(format_report).unwrap_or_default());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn show_overlay(window: &winit::window::Window, title: &str, report: Option<&[MethodProfile]>) {
+    let Some(report) = report else {
+        window.set_title(title);
+        return;
+    };
+    let slowest: Vec<String> = report
+        .iter()
+        .take(3)
+        .map(|profile| {
+            format!(
+                "{}::{} {:.2}ms",
+                profile.component, profile.method, profile.mean_ms
+            )
+        })
+        .collect();
+    window.set_title(&format!("{} | {}", title, slowest.join(", ")));
+}
+
+/// Enables profiling when `LOITSU_PROFILE` is set, returning the path the report should be
+/// written to
+#[cfg(not(target_arch = "wasm32"))]
+pub fn setup_from_env() -> Option<std::path::PathBuf> {
+    let path = std::path::PathBuf::from(std::env::var_os("LOITSU_PROFILE")?);
+    crate::log_scripting!("Profiling scripts to '{}'", path.display());
+    PROFILER.lock().unwrap().set_enabled(true);
+    Some(path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_report(path: &std::path::Path) {
+    let report = format_report(&PROFILER.lock().unwrap().report());
+    match std::fs::write(path, report) {
+        Ok(()) => crate::log_scripting!("Saved the script profile to '{}'", path.display()),
+        Err(e) => crate::error!("Couldn't save the script profile: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut profiler = Profiler::new();
+        for i in 1..=100 {
+            profiler.record(
+                "Player",
+                "frame",
+                Duration::from_millis(i),
+                Some(i as usize * 20),
+            );
+        }
+        profiler.record("Enemy", "frame", Duration::from_millis(1), None);
+        let report = profiler.report();
+        assert_eq!(report.len(), 2);
+        let player = &report[0];
+        assert_eq!(player.component, "Player");
+        assert_eq!(player.calls, 100);
+        assert!((player.mean_ms - 50.5).abs() < 1e-6);
+        assert!((player.p95_ms - 95.0).abs() < 1e-6);
+        assert!((player.max_ms - 100.0).abs() < 1e-6);
+        assert_eq!(player.instructions, Some(1010.0));
+        assert_eq!(report[1].instructions, None);
+
+        let table = format_report(&report);
+        let mut lines = table.lines();
+        assert!(lines.next().unwrap().ends_with("instructions"));
+        let player_line = lines.next().unwrap();
+        assert!(player_line.starts_with("Player::frame"));
+        assert!(player_line.ends_with(" 1010"));
+        assert!(lines.next().unwrap().ends_with(" -"));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::scripting::{ScriptBudget, ScriptingError};
+use rune::runtime::budget;
+use std::collections::HashMap;
+
+/// Keeps track of the instruction budgets of the current frame and of the components that keep
+/// running out of them
+#[derive(Default)]
+pub struct BudgetState {
+    pub limits: ScriptBudget,
+    frame_left: Option<usize>,
+    frame_exhausted: bool,
+    // consecutive failures by entity id and component name
+    failures: HashMap<(u32, String), u32>,
+}
+
+/// How a budgeted call ended
+pub enum BudgetOutcome {
+    Finished,
+    /// The method used up its own budget
+    MethodExhausted(usize),
+    /// The frame budget ran out while the method was running
+    FrameExhausted(usize),
+}
+
+impl BudgetOutcome {
+    /// Explains the error of a call that ended this way
+    pub fn explain(&self, error: ScriptingError) -> ScriptingError {
+        match self {
+            BudgetOutcome::Finished => error,
+            BudgetOutcome::MethodExhausted(limit) => error.with_message(&format!(
+                "Ran out of its budget of {} instructions, is there an endless loop?",
+                limit
+            )),
+            BudgetOutcome::FrameExhausted(limit) => error.with_message(&format!(
+                "The frame ran out of its budget of {} instructions, the remaining component methods of this frame were skipped",
+                limit
+            )),
+        }
+    }
+}
+
+impl BudgetState {
+    pub fn new(limits: ScriptBudget) -> BudgetState {
+        BudgetState {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.frame_left = self.limits.frame_instructions.map(|x| x as usize);
+        self.frame_exhausted = false;
+    }
+
+    /// Whether the frame budget ran out, in which case no more methods should run this frame
+    pub fn frame_exhausted(&self) -> bool {
+        self.frame_exhausted
+    }
+
+    /// Runs `f` within the method budget, or whatever is left of the frame budget if that is less.
+    /// With `count` the instructions the call used are returned too, which costs as much as
+    /// keeping track of a frame budget. Calls without any budget are never counted.
+    pub fn run<T>(
+        &mut self,
+        f: impl FnOnce() -> T,
+        count: bool,
+    ) -> (T, BudgetOutcome, Option<usize>) {
+        let method = self.limits.method_instructions.map(|x| x as usize);
+        let grant = match (method, self.frame_left) {
+            (Some(method), Some(frame)) => method.min(frame),
+            (Some(method), None) => method,
+            (None, Some(frame)) => frame,
+            (None, None) => return (f(), BudgetOutcome::Finished, None),
+        };
+        let count = count || self.frame_left.is_some();
+        let (value, left) = budget::with(grant, || {
+            let value = f();
+            // rune doesn't expose the remaining budget, so it has to be counted by using it up.
+            // When nothing needs counting a single instruction tells whether there is any left.
+            let mut left = 0;
+            while budget::take() {
+                left += 1;
+                if !count {
+                    break;
+                }
+            }
+            (value, left)
+        })
+        .call();
+        let used = count.then_some(grant - left);
+        if let Some(frame) = &mut self.frame_left {
+            *frame -= grant - left;
+        }
+        if left > 0 {
+            (value, BudgetOutcome::Finished, used)
+        } else if Some(grant) == method {
+            (value, BudgetOutcome::MethodExhausted(grant), used)
+        } else {
+            self.frame_exhausted = true;
+            let limit = self.limits.frame_instructions.unwrap() as usize;
+            (value, BudgetOutcome::FrameExhausted(limit), used)
+        }
+    }
+
+    /// Records that a component ran out of its budget, returns true once it should be disabled
+    pub fn add_failure(&mut self, entity: u32, component: &str) -> bool {
+        let failures = self
+            .failures
+            .entry((entity, component.to_string()))
+            .or_insert(0);
+        *failures += 1;
+        *failures >= self.limits.max_failures
+    }
+
+    pub fn clear_failures(&mut self, entity: u32, component: &str) {
+        if !self.failures.is_empty() {
+            self.failures.remove(&(entity, component.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_budget() {
+        let mut state = BudgetState::new(ScriptBudget {
+            method_instructions: Some(10),
+            frame_instructions: Some(25),
+            max_failures: 2,
+        });
+        let use_budget = |n: usize| {
+            move || {
+                for _ in 0..n {
+                    if !budget::take() {
+                        return;
+                    }
+                }
+            }
+        };
+        state.begin_frame();
+        assert!(matches!(
+            state.run(use_budget(8), false).1,
+            BudgetOutcome::Finished
+        ));
+        assert!(matches!(
+            state.run(use_budget(20), false).1,
+            BudgetOutcome::MethodExhausted(10)
+        ));
+        // only 7 instructions are left for the frame
+        assert!(matches!(
+            state.run(use_budget(9), false).1,
+            BudgetOutcome::FrameExhausted(25)
+        ));
+        assert!(state.frame_exhausted());
+        state.begin_frame();
+        assert!(!state.frame_exhausted());
+        assert!(matches!(
+            state.run(use_budget(9), false).1,
+            BudgetOutcome::Finished
+        ));
+
+        // without a frame budget every call gets the whole method budget, and is only counted
+        // when asked to
+        state.limits.frame_instructions = None;
+        state.begin_frame();
+        assert_eq!(state.run(use_budget(9), true).2, Some(9));
+        assert_eq!(state.run(use_budget(9), false).2, None);
+        for _ in 0..5 {
+            assert!(matches!(
+                state.run(use_budget(9), false).1,
+                BudgetOutcome::Finished
+            ));
+        }
+        state.limits.method_instructions = None;
+        assert!(matches!(
+            state.run(use_budget(20), false).1,
+            BudgetOutcome::Finished
+        ));
+
+        assert!(!state.add_failure(1, "Player"));
+        state.clear_failures(1, "Player");
+        assert!(!state.add_failure(1, "Player"));
+        assert!(state.add_failure(1, "Player"));
+    }
+}
@@ -0,0 +1,63 @@
+use super::math::{Rect, Vec2};
+use crate::rendering::camera::{Bounds, Follow, CAMERA};
+use rune::runtime::VmResult;
+use rune::{ContextError, Module};
+
+/// The `camera` module, which moves the camera the game is rendered with
+pub fn module() -> Result<Module, ContextError> {
+    let mut m = Module::with_crate("camera")?;
+    m.function("position", || {
+        Vec2::from_tuple(CAMERA.lock().unwrap().position)
+    })
+    .build()?;
+    m.function("set_position", |position: &Vec2| {
+        CAMERA.lock().unwrap().position = (position.x, position.y)
+    })
+    .build()?;
+    m.function("zoom", || CAMERA.lock().unwrap().zoom).build()?;
+    m.function("set_zoom", |zoom: f32| {
+        if zoom <= 0.0 {
+            return VmResult::panic(format!("The zoom has to be positive, got {}", zoom));
+        }
+        CAMERA.lock().unwrap().zoom = zoom;
+        VmResult::Ok(())
+    })
+    .build()?;
+    m.function("rotation", || CAMERA.lock().unwrap().rotation)
+        .build()?;
+    m.function("set_rotation", |rotation: f32| {
+        CAMERA.lock().unwrap().rotation = rotation
+    })
+    .build()?;
+    m.function("follow", |entity: u32, smoothing: f32| {
+        CAMERA.lock().unwrap().follow = Some(Follow { entity, smoothing })
+    })
+    .build()?;
+    m.function("stop_following", || CAMERA.lock().unwrap().follow = None)
+        .build()?;
+    m.function("set_bounds", |bounds: &Rect| {
+        let (min, max) = (bounds.min(), bounds.max());
+        CAMERA.lock().unwrap().bounds = Some(Bounds {
+            min: (min.x, min.y),
+            max: (max.x, max.y),
+        })
+    })
+    .build()?;
+    m.function("clear_bounds", || CAMERA.lock().unwrap().bounds = None)
+        .build()?;
+    m.function("shake", |intensity: f32, duration: f32| {
+        CAMERA.lock().unwrap().shake(intensity, duration)
+    })
+    .build()?;
+    m.function("screen_to_world", |position: &Vec2| {
+        let camera = CAMERA.lock().unwrap();
+        Vec2::from_tuple(camera.screen_to_world((position.x, position.y)))
+    })
+    .build()?;
+    m.function("world_to_screen", |position: &Vec2| {
+        let camera = CAMERA.lock().unwrap();
+        Vec2::from_tuple(camera.world_to_screen((position.x, position.y)))
+    })
+    .build()?;
+    Ok(m)
+}
@@ -0,0 +1,691 @@
+use rune::alloc::fmt::TryWrite;
+use rune::runtime::{Value, VmResult};
+use rune::{Any, ContextError, Module};
+use std::fmt::{Display, Formatter};
+
+pub fn register(m: &mut Module) -> Result<(), ContextError> {
+    m.ty::<Vec2>()?;
+    m.ty::<Color>()?;
+    m.ty::<Rect>()?;
+
+    m.function_meta(Vec2::zero)?;
+    m.function_meta(Vec2::one)?;
+    m.function_meta(Vec2::up)?;
+    m.function_meta(Vec2::down)?;
+    m.function_meta(Vec2::left)?;
+    m.function_meta(Vec2::right)?;
+    m.function_meta(Vec2::from_angle)?;
+    m.function_meta(Vec2::string_display)?;
+    m.function_meta(Vec2::normalize)?;
+    m.function_meta(Vec2::dot__meta)?;
+    m.function_meta(Vec2::cross__meta)?;
+    m.function_meta(Vec2::length__meta)?;
+    m.function_meta(Vec2::length_squared__meta)?;
+    m.function_meta(Vec2::distance__meta)?;
+    m.function_meta(Vec2::lerp__meta)?;
+    m.function_meta(Vec2::rotate__meta)?;
+    m.function_meta(Vec2::angle__meta)?;
+    m.function_meta(Vec2::angle_to__meta)?;
+    m.function_meta(Vec2::clamp__meta)?;
+    m.function_meta(Vec2::clamp_length__meta)?;
+    m.function_meta(Vec2::min__meta)?;
+    m.function_meta(Vec2::max__meta)?;
+    m.function_meta(Vec2::eq)?;
+    m.function_meta(Vec2::add)?;
+    m.function_meta(Vec2::sub)?;
+    m.function_meta(Vec2::mul)?;
+    m.function_meta(Vec2::div)?;
+
+    m.function_meta(Color::hex)?;
+    m.function_meta(Color::try_hex)?;
+    m.function_meta(Color::rgba)?;
+    m.function_meta(Color::rgb)?;
+    m.function_meta(Color::hsv)?;
+    m.function_meta(Color::hsva)?;
+    m.function_meta(Color::hsl__meta)?;
+    m.function_meta(Color::black)?;
+    m.function_meta(Color::white)?;
+    m.function_meta(Color::transparent)?;
+    m.function_meta(Color::to_hsv__meta)?;
+    m.function_meta(Color::to_hsl__meta)?;
+    m.function_meta(Color::to_hex__meta)?;
+    m.function_meta(Color::lerp__meta)?;
+    m.function_meta(Color::with_alpha__meta)?;
+    m.function_meta(Color::fade__meta)?;
+    m.function_meta(Color::string_display)?;
+    m.function_meta(Color::eq)?;
+
+    m.function_meta(Rect::from_center)?;
+    m.function_meta(Rect::from_corners)?;
+    m.function_meta(Rect::position)?;
+    m.function_meta(Rect::size)?;
+    m.function_meta(Rect::center__meta)?;
+    m.function_meta(Rect::min__meta)?;
+    m.function_meta(Rect::max__meta)?;
+    m.function_meta(Rect::contains__meta)?;
+    m.function_meta(Rect::intersects__meta)?;
+    m.function_meta(Rect::intersection__meta)?;
+    m.function_meta(Rect::string_display)?;
+    m.function_meta(Rect::eq)?;
+    Ok(())
+}
+
+/// Reads a number or a `Vec2` from a script value, numbers apply to both components
+fn vec2_operand(value: Value) -> VmResult<Vec2> {
+    match value {
+        Value::Float(value) => VmResult::Ok(Vec2::new(value as f32, value as f32)),
+        Value::Integer(value) => VmResult::Ok(Vec2::new(value as f32, value as f32)),
+        Value::Any(value) => match value.downcast_borrow_ref::<Vec2>() {
+            Ok(value) => VmResult::Ok(value.clone()),
+            Err(_) => VmResult::panic("Expected a Vec2 or a number"),
+        },
+        _ => VmResult::panic("Expected a Vec2 or a number"),
+    }
+}
+
+pub(super) fn mul_vec2(a: &Vec2, b: Value) -> VmResult<Vec2> {
+    let b = rune::vm_try!(vec2_operand(b));
+    VmResult::Ok(Vec2::new(a.x * b.x, a.y * b.y))
+}
+
+#[derive(Debug, Clone, Any, PartialEq)]
+#[rune(constructor)]
+pub struct Vec2 {
+    #[rune(get, set, copy, add_assign, sub_assign, mul_assign, div_assign)]
+    pub x: f32,
+    #[rune(get, set, copy, add_assign, sub_assign, mul_assign, div_assign)]
+    pub y: f32,
+}
+
+// rune constants can only hold primitive values, which is why ZERO, ONE and friends are
+// functions instead
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_tuple(pos: (f32, f32)) -> Self {
+        Self { x: pos.0, y: pos.1 }
+    }
+
+    pub fn as_tuple(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    #[rune::function(path = Self::zero)]
+    fn zero() -> Self {
+        Self::new(0.0, 0.0)
+    }
+
+    #[rune::function(path = Self::one)]
+    fn one() -> Self {
+        Self::new(1.0, 1.0)
+    }
+
+    /// World space is y-up
+    #[rune::function(path = Self::up)]
+    fn up() -> Self {
+        Self::new(0.0, 1.0)
+    }
+
+    #[rune::function(path = Self::down)]
+    fn down() -> Self {
+        Self::new(0.0, -1.0)
+    }
+
+    #[rune::function(path = Self::left)]
+    fn left() -> Self {
+        Self::new(-1.0, 0.0)
+    }
+
+    #[rune::function(path = Self::right)]
+    fn right() -> Self {
+        Self::new(1.0, 0.0)
+    }
+
+    /// A unit vector pointing towards `angle`, in radians counter-clockwise from the x axis
+    #[rune::function(path = Self::from_angle)]
+    fn from_angle(angle: f32) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    #[rune::function(path = Self::normalize)]
+    pub fn normalize(&self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            return Self { x: 0.0, y: 0.0 };
+        }
+        Self {
+            x: self.x / length,
+            y: self.y / length,
+        }
+    }
+
+    #[rune::function(keep, path = Self::dot)]
+    pub fn dot(&self, other: &Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the 3D cross product
+    #[rune::function(keep, path = Self::cross)]
+    pub fn cross(&self, other: &Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    #[rune::function(keep, path = Self::length)]
+    pub fn length(&self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    #[rune::function(keep, path = Self::length_squared)]
+    pub fn length_squared(&self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    #[rune::function(keep, path = Self::distance)]
+    pub fn distance(&self, other: &Vec2) -> f32 {
+        Self::new(self.x - other.x, self.y - other.y).length()
+    }
+
+    #[rune::function(keep, path = Self::lerp)]
+    pub fn lerp(&self, other: &Vec2, t: f32) -> Self {
+        Self {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+
+    /// Rotates counter-clockwise by `angle` radians
+    #[rune::function(keep, path = Self::rotate)]
+    pub fn rotate(&self, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+
+    /// The angle from the x axis in radians, between -PI and PI
+    #[rune::function(keep, path = Self::angle)]
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// The signed angle needed to rotate this vector onto `other`, in radians
+    #[rune::function(keep, path = Self::angle_to)]
+    pub fn angle_to(&self, other: &Vec2) -> f32 {
+        self.cross(other).atan2(self.dot(other))
+    }
+
+    #[rune::function(keep, path = Self::clamp)]
+    pub fn clamp(&self, min: &Vec2, max: &Vec2) -> Self {
+        Self {
+            x: self.x.max(min.x).min(max.x),
+            y: self.y.max(min.y).min(max.y),
+        }
+    }
+
+    #[rune::function(keep, path = Self::clamp_length)]
+    pub fn clamp_length(&self, max: f32) -> Self {
+        let length = self.length();
+        if length <= max || length == 0.0 {
+            return self.clone();
+        }
+        Self {
+            x: self.x / length * max,
+            y: self.y / length * max,
+        }
+    }
+
+    #[rune::function(keep, path = Self::min)]
+    pub fn min(&self, other: &Vec2) -> Self {
+        Self::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    #[rune::function(keep, path = Self::max)]
+    pub fn max(&self, other: &Vec2) -> Self {
+        Self::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    #[rune::function(protocol = PARTIAL_EQ)]
+    fn eq(&self, other: &Vec2) -> bool {
+        self == other
+    }
+
+    #[rune::function(protocol = STRING_DISPLAY)]
+    fn string_display(&self, f: &mut rune::runtime::Formatter) {
+        write!(f, "({}, {})", self.x, self.y).unwrap();
+    }
+
+    #[rune::function(protocol = ADD)]
+    fn add(&self, other: &Vec2) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+
+    #[rune::function(protocol = SUB)]
+    fn sub(&self, other: &Vec2) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+
+    #[rune::function(protocol = MUL)]
+    fn mul(&self, other: Value) -> VmResult<Self> {
+        mul_vec2(self, other)
+    }
+
+    #[rune::function(protocol = DIV)]
+    fn div(&self, other: Value) -> VmResult<Self> {
+        let other = rune::vm_try!(vec2_operand(other));
+        VmResult::Ok(Self::new(self.x / other.x, self.y / other.y))
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Any, PartialEq)]
+#[rune(constructor)]
+pub struct Color {
+    #[rune(get, set, copy)]
+    pub r: f32,
+    #[rune(get, set, copy)]
+    pub g: f32,
+    #[rune(get, set, copy)]
+    pub b: f32,
+    #[rune(get, set, copy)]
+    pub a: f32,
+}
+
+impl Color {
+    #[rune::function(path = Self::black)]
+    fn black() -> Color {
+        Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    #[rune::function(path = Self::white)]
+    fn white() -> Color {
+        Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        }
+    }
+
+    #[rune::function(path = Self::transparent)]
+    fn transparent() -> Color {
+        Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        }
+    }
+
+    #[rune::function(path = Self::rgb)]
+    fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    #[rune::function(path = Self::rgba)]
+    fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// Hue is in degrees, saturation and value between 0 and 1
+    #[rune::function(path = Self::hsv)]
+    fn hsv(h: f32, s: f32, v: f32) -> Color {
+        Self::from_hsva(h, s, v, 1.0)
+    }
+
+    #[rune::function(path = Self::hsva)]
+    fn hsva(h: f32, s: f32, v: f32, a: f32) -> Color {
+        Self::from_hsva(h, s, v, a)
+    }
+
+    /// Hue is in degrees, saturation and lightness between 0 and 1
+    #[rune::function(keep, path = Self::hsl)]
+    fn hsl(h: f32, s: f32, l: f32) -> Color {
+        let v = l + s * l.min(1.0 - l);
+        let s = if v == 0.0 { 0.0 } else { 2.0 * (1.0 - l / v) };
+        Self::from_hsva(h, s, v, 1.0)
+    }
+
+    pub fn from_hsva(h: f32, s: f32, v: f32, a: f32) -> Color {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        Color {
+            r: r + m,
+            g: g + m,
+            b: b + m,
+            a,
+        }
+    }
+
+    /// Returns the hue in degrees along with the saturation and value
+    #[rune::function(keep, path = Self::to_hsv)]
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / delta + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / delta + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        (h, s, max)
+    }
+
+    /// Returns the hue in degrees along with the saturation and lightness
+    #[rune::function(keep, path = Self::to_hsl)]
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (h, s, v) = self.to_hsv();
+        let l = v * (1.0 - s / 2.0);
+        let s = if l == 0.0 || l == 1.0 {
+            0.0
+        } else {
+            (v - l) / l.min(1.0 - l)
+        };
+        (h, s, l)
+    }
+
+    /// Parses `#RGB`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.trim_start_matches('#');
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok()?;
+            Some(
+                match len {
+                    1 => value * 17,
+                    _ => value,
+                } as f32
+                    / 255.0,
+            )
+        };
+        let (len, channels) = match hex.len() {
+            3 => (1, 3),
+            6 => (2, 3),
+            8 => (2, 4),
+            _ => return None,
+        };
+        Some(Color {
+            r: channel(0, len)?,
+            g: channel(1, len)?,
+            b: channel(2, len)?,
+            a: if channels == 4 { channel(3, len)? } else { 1.0 },
+        })
+    }
+
+    /// Like `try_hex` but raises an error on an invalid color
+    #[rune::function(path = Self::hex)]
+    fn hex(hex: &str) -> VmResult<Color> {
+        match Self::from_hex(hex) {
+            Some(color) => VmResult::Ok(color),
+            None => VmResult::panic(format!("Invalid hex color '{}'", hex)),
+        }
+    }
+
+    #[rune::function(path = Self::try_hex)]
+    fn try_hex(hex: &str) -> Option<Color> {
+        Self::from_hex(hex)
+    }
+
+    /// Formats the color as `#RRGGBB`, or `#RRGGBBAA` if it isn't opaque
+    #[rune::function(keep, path = Self::to_hex)]
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let mut hex = format!(
+            "#{:02X}{:02X}{:02X}",
+            channel(self.r),
+            channel(self.g),
+            channel(self.b)
+        );
+        if channel(self.a) != 255 {
+            hex.push_str(&format!("{:02X}", channel(self.a)));
+        }
+        hex
+    }
+
+    #[rune::function(keep, path = Self::lerp)]
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        Color {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+
+    #[rune::function(keep, path = Self::with_alpha)]
+    pub fn with_alpha(&self, a: f32) -> Color {
+        Color { a, ..self.clone() }
+    }
+
+    /// Multiplies the alpha by `factor`
+    #[rune::function(keep, path = Self::fade)]
+    pub fn fade(&self, factor: f32) -> Color {
+        Color {
+            a: self.a * factor,
+            ..self.clone()
+        }
+    }
+
+    #[rune::function(protocol = PARTIAL_EQ)]
+    fn eq(&self, other: &Color) -> bool {
+        self == other
+    }
+
+    #[rune::function(protocol = STRING_DISPLAY)]
+    fn string_display(&self, f: &mut rune::runtime::Formatter) {
+        write!(f, "{}", self).unwrap();
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+impl From<&Color> for [f32; 4] {
+    fn from(color: &Color) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+/// An axis aligned rectangle, `x` and `y` being its bottom left corner
+#[derive(Debug, Clone, Any, PartialEq)]
+#[rune(constructor)]
+pub struct Rect {
+    #[rune(get, set, copy)]
+    pub x: f32,
+    #[rune(get, set, copy)]
+    pub y: f32,
+    #[rune(get, set, copy)]
+    pub width: f32,
+    #[rune(get, set, copy)]
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[rune::function(path = Self::from_center)]
+    fn from_center(center: &Vec2, size: &Vec2) -> Self {
+        Self::new(
+            center.x - size.x / 2.0,
+            center.y - size.y / 2.0,
+            size.x,
+            size.y,
+        )
+    }
+
+    #[rune::function(path = Self::from_corners)]
+    fn from_corners(a: &Vec2, b: &Vec2) -> Self {
+        let min = a.min(b);
+        let max = a.max(b);
+        Self::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    #[rune::function(path = Self::position)]
+    fn position(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    #[rune::function(path = Self::size)]
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    #[rune::function(keep, path = Self::center)]
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    #[rune::function(keep, path = Self::min)]
+    pub fn min(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    #[rune::function(keep, path = Self::max)]
+    pub fn max(&self) -> Vec2 {
+        Vec2::new(self.x + self.width, self.y + self.height)
+    }
+
+    /// Points on the edges count as inside
+    #[rune::function(keep, path = Self::contains)]
+    pub fn contains(&self, point: &Vec2) -> bool {
+        point.x >= self.x
+            && point.y >= self.y
+            && point.x <= self.x + self.width
+            && point.y <= self.y + self.height
+    }
+
+    /// Rects that only share an edge don't intersect
+    #[rune::function(keep, path = Self::intersects)]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    /// The overlapping area of both rects, if they intersect
+    #[rune::function(keep, path = Self::intersection)]
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let min = self.min().max(&other.min());
+        let max = self.max().min(&other.max());
+        Some(Self::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+
+    #[rune::function(protocol = PARTIAL_EQ)]
+    fn eq(&self, other: &Rect) -> bool {
+        self == other
+    }
+
+    #[rune::function(protocol = STRING_DISPLAY)]
+    fn string_display(&self, f: &mut rune::runtime::Formatter) {
+        write!(
+            f,
+            "Rect({}, {}, {}, {})",
+            self.x, self.y, self.width, self.height
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_vec2() {
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.dot(&Vec2::new(1.0, 0.0)), 3.0);
+        assert_eq!(Vec2::new(1.0, 0.0).cross(&Vec2::new(0.0, 1.0)), 1.0);
+        let r = Vec2::new(1.0, 0.0).rotate(std::f32::consts::FRAC_PI_2);
+        assert!(approx(r.x, 0.0) && approx(r.y, 1.0));
+        assert!(approx(r.angle(), std::f32::consts::FRAC_PI_2));
+        assert_eq!(v.clamp_length(1.0).length(), 1.0);
+        assert_eq!(
+            v.clamp(&Vec2::new(0.0, 0.0), &Vec2::new(2.0, 2.0)),
+            Vec2::new(2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let color = Color::from_hex("#ff8000").unwrap();
+        assert_eq!(color.to_hex(), "#FF8000");
+        assert_eq!(Color::from_hex("f80"), Color::from_hex("#ff8800"));
+        assert_eq!(Color::from_hex("#ff800080").unwrap().to_hex(), "#FF800080");
+        assert!(Color::from_hex("#ff80").is_none());
+        assert!(Color::from_hex("#gg0000").is_none());
+        assert!(Color::from_hex("#ää00").is_none());
+
+        let (h, s, v) = color.to_hsv();
+        assert!(approx(h, 60.0 * 128.0 / 255.0));
+        let back = Color::from_hsva(h, s, v, 1.0);
+        assert!(approx(back.r, color.r) && approx(back.g, color.g) && approx(back.b, color.b));
+        let (h, s, l) = color.to_hsl();
+        let back = Color::hsl(h, s, l);
+        assert!(approx(back.r, color.r) && approx(back.g, color.g) && approx(back.b, color.b));
+    }
+
+    #[test]
+    fn test_rect() {
+        let a = Rect::new(0.0, 0.0, 2.0, 2.0);
+        let b = Rect::new(1.0, 1.0, 2.0, 2.0);
+        assert!(a.contains(&Vec2::new(2.0, 1.0)));
+        assert!(!a.contains(&Vec2::new(2.5, 1.0)));
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&Rect::new(2.0, 0.0, 1.0, 1.0)));
+        assert_eq!(a.intersection(&b), Some(Rect::new(1.0, 1.0, 1.0, 1.0)));
+    }
+}
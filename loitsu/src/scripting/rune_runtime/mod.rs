@@ -0,0 +1,2246 @@
+use super::EntityUpdate;
+use crate::ecs::{ComponentFlags, RuntimeEntity, Transform, ECS};
+use crate::input::keyboard::{parse_key, KeyInput};
+use crate::input::mouse::{str_to_button, MouseState};
+use crate::input::{actions, gamepad, InputState};
+use crate::rendering::drawable::order::{DrawOrder, DEFAULT_LAYER};
+use crate::rendering::drawable::{DrawableProperty, DrawablePrototype};
+use crate::scene_management::{Component, Property};
+use crate::scripting::{
+    native, ScriptValue, ScriptingData, ScriptingError, ScriptingSource, SourceLocation,
+    ValidatedScripts,
+};
+use crate::{error, log_scripting as log, logging, ScriptingInstance};
+use lazy_static::lazy_static;
+use rune::ast::Spanned;
+use rune::compile::CompileVisitor;
+use rune::diagnostics::{Diagnostic, EmitError, FatalDiagnostic, FatalDiagnosticKind};
+use rune::runtime::{
+    AccessError, AnyObj, Args, Protocol, RuntimeContext, Shared, Stack, Struct, Value, VmError,
+    VmResult,
+};
+use rune::{
+    Any, BuildError, Context, ContextError, Diagnostics, Module, Source, Sources, ToValue, Unit, Vm,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use winit::event::MouseButton;
+mod budget;
+mod camera;
+mod math;
+mod profiler;
+mod random;
+mod storage;
+mod testing;
+mod unit_cache;
+
+pub use math::{Color, Rect, Vec2};
+#[cfg(not(target_arch = "wasm32"))]
+pub use testing::{run_tests, TestProject, TestResult};
+
+pub type Result<T> = std::result::Result<T, ScriptingError>;
+
+/// Identifies the engine and rune versions in `ValidatedScripts`, other versions may not compile
+/// the same scripts or load the units they serialized
+const VALIDATION_VERSION: &str = concat!(
+    "loitsu-",
+    env!("CARGO_PKG_VERSION"),
+    "/rune-",
+    env!("LOITSU_RUNE_VERSION")
+);
+
+#[cfg(feature = "scene_generation")]
+static mut REQUIRED_ASSETS: Vec<String> = Vec::new();
+
+pub struct RuneInstance {
+    virtual_machine: Option<Vm>,
+    // kept around so that the scripts can be recompiled when hot reloading
+    context: Option<Context>,
+    runtime: Option<Arc<RuntimeContext>>,
+    // indexed by rune source id, used to resolve error locations
+    sources: Vec<ScriptingSource>,
+    shared_entities: Arc<RwLock<HashMap<u32, SharedWrapper>>>,
+    unit: SharedUnit,
+    budget: budget::BudgetState,
+    // passed on to native components
+    input_state: Option<Arc<Mutex<InputState>>>,
+    // loaded by initialize instead of compiling the sources, if they match
+    validated: Option<ValidatedScripts>,
+    // compiled by another instance from the same sources and modules, used by initialize as is
+    compiled: Option<Arc<Unit>>,
+}
+
+/// The currently loaded unit, shared with native functions that need to call back into the
+/// scripts. The runtime context is weak as it owns those functions.
+type SharedUnit = Arc<RwLock<Option<(Weak<RuntimeContext>, Arc<Unit>)>>>;
+
+pub struct RuneComponent {
+    pub data: Option<Shared<Struct>>,
+    /// Set instead of `data` for native components
+    pub native: Option<Box<dyn native::Component>>,
+}
+
+#[derive(Debug, Clone, Any)]
+struct RuneTransform {
+    #[rune(get, set)]
+    position: Shared<AnyObj>,
+    #[rune(get, set, add_assign, sub_assign, mul_assign, div_assign)]
+    rotation: f32,
+    #[rune(get, set)]
+    scale: Shared<AnyObj>,
+}
+
+impl RuneTransform {
+    fn add_position(&mut self, other: &Vec2) {
+        let mut position = as_vec2(self.position.clone());
+        position.x += other.x;
+        position.y += other.y;
+        self.position = Shared::new(AnyObj::new(position).unwrap()).unwrap();
+    }
+    fn sub_position(&mut self, other: &Vec2) {
+        let mut position = as_vec2(self.position.clone());
+        position.x -= other.x;
+        position.y -= other.y;
+        self.position = Shared::new(AnyObj::new(position).unwrap()).unwrap();
+    }
+    fn mul_position(&mut self, other: Value) -> VmResult<()> {
+        let position = as_vec2(self.position.clone());
+        let position = rune::vm_try!(math::mul_vec2(&position, other));
+        self.position = Shared::new(AnyObj::new(position).unwrap()).unwrap();
+        VmResult::Ok(())
+    }
+    fn div_position(&mut self, other: f64) {
+        let mut position = as_vec2(self.position.clone());
+        position.x /= other as f32;
+        position.y /= other as f32;
+        self.position = Shared::new(AnyObj::new(position).unwrap()).unwrap();
+    }
+    fn add_scale(&mut self, other: &Vec2) {
+        let mut scale = as_vec2(self.scale.clone());
+        scale.x += other.x;
+        scale.y += other.y;
+        self.scale = Shared::new(AnyObj::new(scale).unwrap()).unwrap();
+    }
+    fn sub_scale(&mut self, other: &Vec2) {
+        let mut scale = as_vec2(self.scale.clone());
+        scale.x -= other.x;
+        scale.y -= other.y;
+        self.scale = Shared::new(AnyObj::new(scale).unwrap()).unwrap();
+    }
+    fn mul_scale(&mut self, other: Value) -> VmResult<()> {
+        let scale = as_vec2(self.scale.clone());
+        let scale = rune::vm_try!(math::mul_vec2(&scale, other));
+        self.scale = Shared::new(AnyObj::new(scale).unwrap()).unwrap();
+        VmResult::Ok(())
+    }
+    fn div_scale(&mut self, other: f64) {
+        let mut scale = as_vec2(self.scale.clone());
+        scale.x /= other as f32;
+        scale.y /= other as f32;
+        self.scale = Shared::new(AnyObj::new(scale).unwrap()).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, Any)]
+struct RuneEntity {
+    #[rune(get, set)]
+    pub name: String,
+    // the name and live data of each component on the entity
+    components: Vec<(String, Value)>,
+    // pub children: Vec<RuneEntity>, TODO: NOT IMPLEMENTED, should use a string id reference to
+    // avoid circular references
+    #[rune(get, set)]
+    pub transform: Shared<AnyObj>,
+    #[rune(get)]
+    pub id: u32,
+    drawables: Vec<(Drawable, u32)>,
+    remove_drawables: Vec<u32>,
+    property_updates: Vec<(u32, String, DrawableProperty)>,
+}
+
+impl RuneEntity {
+    /// Returns the data of the first component with the given name. The data is shared with the
+    /// component, so any changes are visible to it immediately.
+    #[rune::function]
+    fn get_component(&self, name: &str) -> Option<Value> {
+        self.components
+            .iter()
+            .find(|(component, _)| component == name)
+            .map(|(_, data)| data.clone())
+    }
+
+    #[rune::function]
+    fn register_drawable(&mut self, drawable: Drawable) -> u32 {
+        let id = crate::util::id::get_unique_id();
+        self.drawables.push((drawable, id));
+        id
+    }
+
+    #[rune::function]
+    fn unregister_drawable(&mut self, id: u32) {
+        self.remove_drawables.push(id);
+    }
+
+    #[rune::function]
+    fn set_drawable_color(&mut self, drawable: u32, color: Color) {
+        self.property_updates.push((
+            drawable,
+            "color".to_string(),
+            DrawableProperty::Color((&color).into()),
+        ));
+    }
+
+    #[rune::function]
+    fn set_drawable_sprite(&mut self, drawable: u32, sprite: &str) {
+        self.property_updates.push((
+            drawable,
+            "sprite".to_string(),
+            DrawableProperty::Sprite(sprite.to_string()),
+        ));
+    }
+
+    #[rune::function]
+    fn set_drawable_layer(&mut self, drawable: u32, layer: &str) {
+        self.property_updates.push((
+            drawable,
+            "layer".to_string(),
+            DrawableProperty::Layer(layer.to_string()),
+        ));
+    }
+
+    #[rune::function]
+    fn set_drawable_order(&mut self, drawable: u32, order: i64) {
+        self.property_updates.push((
+            drawable,
+            "order".to_string(),
+            DrawableProperty::Order(order.clamp(i32::MIN as i64, i32::MAX as i64) as i32),
+        ));
+    }
+}
+
+#[derive(Debug, Clone, Any)]
+struct Touch {
+    #[rune(get)]
+    id: u64,
+    #[rune(get)]
+    position: Vec2,
+    #[rune(get)]
+    world_position: Vec2,
+    /// One of "Started", "Moved", "Ended" or "Cancelled"
+    #[rune(get)]
+    phase: String,
+}
+
+impl From<&crate::input::touch::Touch> for Touch {
+    fn from(touch: &crate::input::touch::Touch) -> Self {
+        Touch {
+            id: touch.id,
+            position: Vec2::from_tuple(touch.position),
+            world_position: Vec2::from_tuple(touch.world_position),
+            phase: format!("{:?}", touch.phase),
+        }
+    }
+}
+
+/// Something an entity draws. The sorting layer and order can be set before the drawable is
+/// registered, e.g. `drawable.layer = "Foreground"`.
+#[derive(Debug, Clone, Any)]
+struct Drawable {
+    kind: DrawableKind,
+    #[rune(get, set)]
+    layer: String,
+    /// Higher is drawn on top within the layer
+    #[rune(get, set)]
+    order: i64,
+}
+
+#[derive(Debug, Clone)]
+enum DrawableKind {
+    Sprite(String, Color),
+}
+
+impl Drawable {
+    fn new(kind: DrawableKind) -> Self {
+        Drawable {
+            kind,
+            layer: DEFAULT_LAYER.to_string(),
+            order: 0,
+        }
+    }
+
+    #[rune::function(path = Self::sprite)]
+    fn sprite(sprite: &str, color: Color) -> Self {
+        Drawable::new(DrawableKind::Sprite(sprite.to_string(), color))
+    }
+
+    /// `Drawable::Sprite(sprite, color)`, kept from when drawables were an enum
+    #[rune::function(path = Self::Sprite)]
+    fn sprite_variant(sprite: &str, color: Color) -> Self {
+        Drawable::new(DrawableKind::Sprite(sprite.to_string(), color))
+    }
+}
+
+impl From<&(Drawable, u32)> for DrawablePrototype {
+    fn from(drawable: &(Drawable, u32)) -> Self {
+        let order = DrawOrder {
+            layer: drawable.0.layer.clone(),
+            order: drawable.0.order.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        };
+        match &drawable.0.kind {
+            DrawableKind::Sprite(sprite, color) => DrawablePrototype::Sprite {
+                sprite: sprite.to_string(),
+                color: [color.r, color.g, color.b, color.a],
+                order,
+                id: drawable.1,
+            },
+        }
+    }
+}
+
+impl From<Transform> for RuneTransform {
+    fn from(transform: Transform) -> Self {
+        match transform {
+            Transform::Transform2D {
+                position,
+                rotation,
+                scale,
+                ..
+            } => RuneTransform {
+                position: Shared::new(AnyObj::new(Vec2::from_tuple(position)).unwrap()).unwrap(),
+                rotation,
+                scale: Shared::new(AnyObj::new(Vec2::from_tuple(scale)).unwrap()).unwrap(),
+            },
+            Transform::RectTransform { position, .. } => RuneTransform {
+                position: Shared::new(AnyObj::new(Vec2::from_tuple(position)).unwrap()).unwrap(),
+                rotation: 0.0,
+                scale: Shared::new(AnyObj::new(Vec2::new(1.0, 1.0)).unwrap()).unwrap(),
+            },
+        }
+    }
+}
+
+fn as_vec2(vec: Shared<AnyObj>) -> Vec2 {
+    vec.take_downcast().unwrap()
+}
+
+impl From<RuneTransform> for Transform {
+    fn from(transform: RuneTransform) -> Self {
+        Transform::Transform2D {
+            position: as_vec2(transform.position).as_tuple(),
+            rotation: transform.rotation,
+            scale: as_vec2(transform.scale).as_tuple(),
+            r#static: false,
+        }
+    }
+}
+
+impl ScriptingData<RuneInstance> for RuneComponent {
+    fn from_component_proto(proto: Component, instance: &mut RuneInstance) -> Result<Self> {
+        if let Some(native) = native::create(&proto) {
+            return Ok(RuneComponent {
+                data: None,
+                native: Some(native),
+            });
+        }
+        // lets start by initializing a new struct in the runtime
+        let data = instance
+            .virtual_machine
+            .as_mut()
+            .unwrap()
+            .call([proto.name.as_str(), "new"], ())
+            .expect("Error when initializing component. Did you forget to include a 'new' method?");
+        let component_data = match data {
+            Value::Struct(data) => {
+                {
+                    let mut component_data = data.clone().into_mut().unwrap();
+                    let component_data_obj = component_data.data_mut();
+                    // lets assign all of our properties
+                    for (key, value) in proto.properties {
+                        component_data_obj
+                            .insert_value(rune::alloc::String::try_from(key)?, value)
+                            .unwrap();
+                    }
+                }
+                Some(data)
+            }
+            _ => None,
+        };
+
+        Ok(RuneComponent {
+            data: component_data,
+            native: None,
+        })
+    }
+
+    fn to_component_proto(&self, proto: &Component) -> Result<Component> {
+        let mut proto = proto.clone();
+        if let Some(native) = &self.native {
+            proto.properties.extend(native.properties());
+        }
+        if let Some(data) = &self.data {
+            let component_data = data.clone().into_mut().unwrap();
+            let component_data_obj = component_data.data();
+            for (key, value) in component_data_obj.iter() {
+                if key.to_string().starts_with("__") {
+                    continue;
+                }
+                proto
+                    .properties
+                    .insert(key.to_string(), value.clone().into());
+            }
+        }
+        Ok(proto)
+    }
+
+    fn set_property(&mut self, property: &str, value: Property) -> Result<()> {
+        if let Some(native) = &mut self.native {
+            return native.set_property(property, value);
+        }
+        if let Some(data) = &mut self.data {
+            let mut component_data = data.clone().into_mut().unwrap();
+            let component_data_obj = component_data.data_mut();
+            component_data_obj
+                .insert_value(rune::alloc::String::try_from(property)?, value)
+                .unwrap();
+        }
+        Ok(())
+    }
+
+    fn rebind(&mut self, proto: &Component, instance: &mut RuneInstance) -> Result<()> {
+        if self.native.is_some() {
+            return Ok(());
+        }
+        let data = instance.call_rune([proto.name.as_str(), "new"], ())?;
+        let component_data = match data {
+            Value::Struct(data) => {
+                if let Some(old_data) = &self.data {
+                    let old_data = old_data.borrow_ref()?;
+                    let mut component_data = data.clone().into_mut()?;
+                    let component_data_obj = component_data.data_mut();
+                    // fields that were removed from the script are dropped, new ones keep the
+                    // value assigned by 'new'
+                    for (key, value) in old_data.data().iter() {
+                        if component_data_obj.contains_key(key.as_str()) {
+                            component_data_obj.insert(
+                                rune::alloc::String::try_from(key.as_str())?,
+                                value.clone(),
+                            )?;
+                        }
+                    }
+                }
+                Some(data)
+            }
+            _ => None,
+        };
+        self.data = component_data;
+        Ok(())
+    }
+}
+
+impl From<Value> for Property {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(value) => Property::String(value.into_ref().unwrap().to_string()),
+            Value::Float(value) => Property::Number(value as f32),
+            Value::Integer(value) => Property::Number(value as f32),
+            Value::Bool(value) => Property::Boolean(value),
+            _ => Property::String("".to_string()),
+        }
+    }
+}
+
+impl ToValue for Property {
+    fn to_value(self) -> VmResult<Value> {
+        match self {
+            Property::String(value) => rune::alloc::String::try_from(value.clone())
+                .unwrap()
+                .to_value(),
+            Property::Number(value) => VmResult::Ok(Value::Float(value as f64)),
+            Property::Boolean(value) => VmResult::Ok(Value::Bool(value)),
+            Property::Array(value) => {
+                let mut vec = rune::runtime::Vec::new();
+                for item in value {
+                    let _ = vec.push_value(item.to_value()).into_result();
+                }
+                VmResult::Ok(Value::Vec(Shared::new(vec).unwrap()))
+            }
+            Property::EntityReference(value) => VmResult::Ok(Value::Integer(value.into())),
+            Property::ComponentReference(value) => VmResult::Ok(Value::Integer(value.into())),
+        }
+    }
+}
+
+impl ToValue for ScriptValue {
+    fn to_value(self) -> VmResult<Value> {
+        match self {
+            ScriptValue::Unit => VmResult::Ok(Value::EmptyTuple),
+            ScriptValue::Bool(value) => VmResult::Ok(Value::Bool(value)),
+            ScriptValue::Integer(value) => VmResult::Ok(Value::Integer(value)),
+            ScriptValue::Float(value) => VmResult::Ok(Value::Float(value)),
+            ScriptValue::String(value) => {
+                rune::vm_try!(rune::alloc::String::try_from(value)).to_value()
+            }
+            ScriptValue::Array(values) => {
+                let mut vec = rune::runtime::Vec::new();
+                for value in values {
+                    rune::vm_try!(vec.push(rune::vm_try!(value.to_value())));
+                }
+                VmResult::Ok(Value::Vec(rune::vm_try!(Shared::new(vec))))
+            }
+            ScriptValue::Object(fields) => {
+                let mut object = rune::runtime::Object::new();
+                for (name, value) in fields {
+                    rune::vm_try!(object.insert(
+                        rune::vm_try!(rune::alloc::String::try_from(name)),
+                        rune::vm_try!(value.to_value()),
+                    ));
+                }
+                VmResult::Ok(Value::Object(rune::vm_try!(Shared::new(object))))
+            }
+        }
+    }
+}
+
+/// Converts a value returned by the scripts. Structs become objects of their fields, and options
+/// their inner value or unit.
+fn to_script_value(value: &Value) -> Result<ScriptValue> {
+    Ok(match value {
+        Value::EmptyTuple => ScriptValue::Unit,
+        Value::Bool(value) => ScriptValue::Bool(*value),
+        Value::Byte(value) => ScriptValue::Integer((*value).into()),
+        Value::Integer(value) => ScriptValue::Integer(*value),
+        Value::Float(value) => ScriptValue::Float(*value),
+        Value::Char(value) => ScriptValue::String(value.to_string()),
+        Value::String(value) => ScriptValue::String(value.borrow_ref()?.to_string()),
+        Value::Vec(values) => ScriptValue::Array(
+            values
+                .borrow_ref()?
+                .iter()
+                .map(to_script_value)
+                .collect::<Result<_>>()?,
+        ),
+        Value::Tuple(values) => ScriptValue::Array(
+            values
+                .borrow_ref()?
+                .iter()
+                .map(to_script_value)
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(object) => to_script_object(&*object.borrow_ref()?)?,
+        Value::Struct(data) => to_script_object(data.borrow_ref()?.data())?,
+        Value::Option(value) => match &*value.borrow_ref()? {
+            Some(value) => to_script_value(value)?,
+            None => ScriptValue::Unit,
+        },
+        value => {
+            return Err(ScriptingError::new(&format!(
+                "A value of type {} can't be passed out of the scripts",
+                value.type_info().into_result()?
+            )))
+        }
+    })
+}
+
+fn to_script_object(object: &rune::runtime::Object) -> Result<ScriptValue> {
+    let mut fields = std::collections::BTreeMap::new();
+    for (name, value) in object.iter() {
+        fields.insert(name.to_string(), to_script_value(value)?);
+    }
+    Ok(ScriptValue::Object(fields))
+}
+
+impl From<rune::alloc::Error> for ScriptingError {
+    fn from(error: rune::alloc::Error) -> Self {
+        Self::new(&format!("Rune alloc error: {}", error))
+    }
+}
+
+impl From<ContextError> for ScriptingError {
+    fn from(error: ContextError) -> Self {
+        Self::new(&format!("Rune context error: {}", error))
+    }
+}
+
+impl From<BuildError> for ScriptingError {
+    fn from(error: BuildError) -> Self {
+        Self::new(&format!("Rune build error: {}", error))
+    }
+}
+
+impl From<EmitError> for ScriptingError {
+    fn from(error: EmitError) -> Self {
+        Self::new(&format!("Rune emit error: {}", error))
+    }
+}
+
+impl From<AccessError> for ScriptingError {
+    fn from(error: AccessError) -> Self {
+        Self::new(&format!("Rune access error: {}", error))
+    }
+}
+
+impl From<VmError> for ScriptingError {
+    fn from(error: VmError) -> Self {
+        Self::new(&format!("Rune vm error: {}", error))
+    }
+}
+
+impl ScriptingInstance for RuneInstance {
+    type Data = RuneComponent;
+
+    fn new_with_sources(
+        sources: Vec<ScriptingSource>,
+        ecs: Arc<RwLock<ECS<RuneInstance>>>,
+    ) -> Result<Self> {
+        let mut context = Context::new();
+        let shared_entities = Arc::new(RwLock::new(HashMap::new()));
+        let shared_unit = Arc::new(RwLock::new(None));
+        let core_modules = core_modules(None, ecs, shared_entities.clone(), shared_unit.clone())?;
+        for core_module in core_modules {
+            context.install(&core_module)?;
+        }
+        let runtime = Arc::new(context.runtime()?);
+        let (unit, sources) = compile(&context, sources)?;
+
+        let mut instance = Self {
+            virtual_machine: None,
+            context: Some(context),
+            runtime: Some(runtime),
+            sources: Vec::new(),
+            shared_entities,
+            unit: shared_unit,
+            budget: budget::BudgetState::default(),
+            input_state: None,
+            validated: None,
+            compiled: None,
+        };
+        instance.load_unit(unit, sources)?;
+        Ok(instance)
+    }
+
+    fn new_uninitialized() -> Result<Self> {
+        Ok(Self {
+            virtual_machine: None,
+            context: None,
+            runtime: None,
+            sources: Vec::new(),
+            shared_entities: Arc::new(RwLock::new(HashMap::new())),
+            unit: Arc::new(RwLock::new(None)),
+            budget: budget::BudgetState::default(),
+            input_state: None,
+            validated: None,
+            compiled: None,
+        })
+    }
+
+    fn initialize(
+        &mut self,
+        sources: Vec<ScriptingSource>,
+        input_state: Arc<Mutex<InputState>>,
+        ecs: Arc<RwLock<ECS<RuneInstance>>>,
+    ) -> Result<()> {
+        self.initialize_with_visitor(sources, input_state, ecs, None)
+    }
+
+    fn set_validated(&mut self, validated: ValidatedScripts) {
+        self.validated = Some(validated);
+    }
+
+    fn reload(&mut self, sources: Vec<ScriptingSource>) -> Result<()> {
+        let Some(context) = &self.context else {
+            return Err(ScriptingError::new(
+                "Can't reload scripts before scripting has been initialized",
+            ));
+        };
+        // the old vm is only replaced once the new unit has compiled successfully
+        let (unit, sources) = compile(context, sources)?;
+        self.load_unit(unit, sources)
+    }
+
+    fn call(&mut self, path: [&str; 2], args: Vec<ScriptValue>) -> Result<ScriptValue> {
+        let args = args
+            .into_iter()
+            .map(|arg| arg.to_value().into_result())
+            .collect::<std::result::Result<Vec<Value>, VmError>>()?;
+        to_script_value(&self.call_rune(path, args)?)
+    }
+
+    fn run_component_methods<RuneComponent>(
+        &mut self,
+        entities: &[Arc<Mutex<crate::ecs::RuntimeEntity<Self>>>],
+        lookup: HashMap<u32, Arc<Mutex<crate::ecs::RuntimeEntity<Self>>>>,
+        method: ComponentFlags,
+    ) -> Vec<(Arc<Mutex<crate::ecs::RuntimeTransform>>, Vec<EntityUpdate>)> {
+        let mut updates = Vec::new();
+        for entity in entities {
+            let mut entity_updates = Vec::new();
+            let mut entity = entity.lock().unwrap();
+            if entity.is_new {
+                if entity.component_flags & ComponentFlags::START == ComponentFlags::START {
+                    entity_updates.extend(self.run_component_methods_on_entity(
+                        &mut entity,
+                        &lookup,
+                        ComponentFlags::START,
+                    ));
+                }
+                entity.is_new = false;
+            }
+            if entity.component_flags & method == method {
+                entity_updates.extend(self.run_component_methods_on_entity(
+                    &mut entity,
+                    &lookup,
+                    method,
+                ));
+            }
+            for child in &mut entity.children {
+                let mut child = child.lock().unwrap();
+                let mut entity_updates = Vec::new();
+                if child.is_new {
+                    if child.component_flags & ComponentFlags::START == ComponentFlags::START {
+                        entity_updates.extend(self.run_component_methods_on_entity(
+                            &mut child,
+                            &lookup,
+                            ComponentFlags::START,
+                        ));
+                    }
+                    child.is_new = false;
+                }
+                if child.component_flags & method == method {
+                    entity_updates
+                        .extend(self.run_component_methods_on_entity(&mut child, &lookup, method));
+                }
+                updates.push((child.transform.clone(), entity_updates));
+            }
+            updates.push((entity.transform.clone(), entity_updates));
+        }
+        updates
+    }
+
+    fn get_component_flags(&self, component_name: &str) -> ComponentFlags {
+        if let Some(flags) = native::methods(component_name) {
+            return flags;
+        }
+        let mut flags = ComponentFlags::EMPTY;
+        if let Some(vm) = &self.virtual_machine {
+            let methods = vec![
+                ComponentFlags::BUILD,
+                ComponentFlags::FRAME,
+                ComponentFlags::LATE_FRAME,
+                ComponentFlags::TICK,
+                ComponentFlags::START,
+                ComponentFlags::DESTROY,
+                #[cfg(feature = "editor")]
+                ComponentFlags::EDITOR_START,
+                #[cfg(feature = "editor")]
+                ComponentFlags::EDITOR_DESTROY,
+                #[cfg(feature = "editor")]
+                ComponentFlags::EDITOR_UPDATE,
+            ];
+            for method in methods {
+                let result = vm.lookup_function([component_name, flags_to_method(method)]);
+                if result.is_ok() {
+                    flags |= method;
+                }
+            }
+        }
+        flags
+    }
+
+    fn set_budget(&mut self, budget: crate::scripting::ScriptBudget) {
+        self.budget = budget::BudgetState::new(budget);
+    }
+
+    fn begin_frame(&mut self) {
+        self.budget.begin_frame();
+    }
+}
+
+/// Compiles the given sources along with the builtin script, reporting every compile error. The
+/// returned sources are indexed by their rune source id.
+fn compile(
+    context: &Context,
+    sources: Vec<ScriptingSource>,
+) -> Result<(Arc<Unit>, Vec<ScriptingSource>)> {
+    let sources_by_id = with_builtin(sources);
+    let unit = build_unit(context, &sources_by_id, None)?;
+    Ok((unit, sources_by_id))
+}
+
+/// Prepends the builtin script, so that the sources are indexed by their rune source id
+fn with_builtin(sources: Vec<ScriptingSource>) -> Vec<ScriptingSource> {
+    let mut sources_by_id = vec![ScriptingSource {
+        name: "loitsu_builtin".to_string(),
+        source: include_str!("../scripts/builtin.rn").to_string(),
+    }];
+    sources_by_id.extend(sources);
+    sources_by_id
+}
+
+/// Loads the unit compiled at build time if it was built from the same sources by this version,
+/// otherwise logs why the sources have to be compiled
+fn load_validated(
+    validated: Option<ValidatedScripts>,
+    sources: &[ScriptingSource],
+) -> Option<Arc<Unit>> {
+    match validated {
+        Some(validated) if validated.matches(VALIDATION_VERSION, sources) => {
+            match unit_cache::deserialize(&validated.unit) {
+                Ok(unit) => return Some(Arc::new(unit)),
+                Err(e) => log!("{}, compiling them instead", e.message()),
+            }
+        }
+        Some(validated) => log!(
+            "The scripts were compiled by another build ({}), compiling them. Rebuild the project to skip this",
+            validated.version
+        ),
+        None => log!("The scripts weren't compiled at build time, compiling them"),
+    }
+    None
+}
+
+/// Compiles sources that already include the builtin script, the visitor is shown every item
+/// that gets compiled
+fn build_unit(
+    context: &Context,
+    sources: &[ScriptingSource],
+    visitor: Option<&mut dyn CompileVisitor>,
+) -> Result<Arc<Unit>> {
+    let mut rune_sources = Sources::new();
+    for source in sources {
+        rune_sources.insert(Source::new(&source.name, &source.source)?)?;
+    }
+    let mut diagnostics = Diagnostics::without_warnings();
+    let mut build = rune::prepare(&mut rune_sources)
+        .with_context(context)
+        .with_diagnostics(&mut diagnostics);
+    if let Some(visitor) = visitor {
+        build = build.with_visitor(visitor)?;
+    }
+    let result = build.build();
+
+    let mut error_count = 0;
+    for diagnostic in diagnostics.diagnostics() {
+        if let Diagnostic::Fatal(diagnostic) = diagnostic {
+            error_count += 1;
+            logging::script_error(&compile_error(diagnostic, sources));
+        }
+    }
+
+    match result {
+        Ok(unit) => Ok(Arc::new(unit)),
+        Err(_) if error_count > 0 => Err(ScriptingError::new(&format!(
+            "Scripts failed to compile with {} error(s)",
+            error_count
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn compile_error(diagnostic: &FatalDiagnostic, sources: &[ScriptingSource]) -> ScriptingError {
+    let error = ScriptingError::new(&diagnostic.to_string());
+    let span = match diagnostic.kind() {
+        FatalDiagnosticKind::CompileError(error) => Some(error.span()),
+        _ => None,
+    };
+    match (span, sources.get(diagnostic.source_id().into_index())) {
+        (Some(span), Some(source)) => {
+            error.with_location(SourceLocation::from_offset(source, span.start.0 as usize))
+        }
+        _ => error,
+    }
+}
+
+fn convert_entity(entity: &RuntimeEntity<RuneInstance>) -> RuneEntity {
+    RuneEntity {
+        name: entity.get_name().to_string(),
+        transform: Shared::new(
+            AnyObj::new(Into::<RuneTransform>::into(
+                entity.transform.lock().unwrap().transform.clone(),
+            ))
+            .unwrap(),
+        )
+        .unwrap(),
+        id: entity.get_id(),
+        components: entity
+            .components
+            .iter()
+            .map(|component| {
+                let data = match &component.data.data {
+                    Some(data) => Value::Struct(data.clone()),
+                    None => Value::EmptyTuple,
+                };
+                (component.component_proto.name.clone(), data)
+            })
+            .collect(),
+        drawables: Vec::new(),
+        remove_drawables: Vec::new(),
+        property_updates: Vec::new(),
+    }
+}
+
+impl RuneInstance {
+    /// Like `initialize`, the visitor is shown every item of the scripts as they are compiled.
+    /// Scripts that were compiled at build time aren't used when there is a visitor.
+    fn initialize_with_visitor(
+        &mut self,
+        sources: Vec<ScriptingSource>,
+        input_state: Arc<Mutex<InputState>>,
+        ecs: Arc<RwLock<ECS<RuneInstance>>>,
+        visitor: Option<&mut dyn CompileVisitor>,
+    ) -> Result<()> {
+        let mut context = Context::new();
+        let core_modules = core_modules(
+            Some(input_state.clone()),
+            ecs,
+            self.shared_entities.clone(),
+            self.unit.clone(),
+        )?;
+        for core_module in core_modules {
+            context.install(&core_module)?;
+        }
+        let runtime = Arc::new(context.runtime()?);
+        let sources = with_builtin(sources);
+        let unit = match (visitor, self.compiled.take()) {
+            (Some(visitor), _) => build_unit(&context, &sources, Some(visitor))?,
+            (None, Some(unit)) => unit,
+            (None, None) => match load_validated(self.validated.take(), &sources) {
+                Some(unit) => unit,
+                None => build_unit(&context, &sources, None)?,
+            },
+        };
+
+        self.context = Some(context);
+        self.runtime = Some(runtime);
+        self.input_state = Some(input_state);
+        self.load_unit(unit, sources)
+    }
+
+    fn call_rune<T: Args>(&mut self, path: [&str; 2], args: T) -> Result<Value> {
+        let Some(vm) = self.virtual_machine.as_mut() else {
+            return Err(ScriptingError::new(
+                "Can't call scripts before scripting has been initialized",
+            ));
+        };
+        let result = vm
+            .execute(path, args)
+            .and_then(|mut execution| execution.complete().into_result());
+        result.map_err(|e| self.vm_error(e))
+    }
+
+    /// Serializes the loaded unit along with what it was compiled from, see
+    /// `ScriptingInstance::set_validated`
+    pub fn validated(&self) -> Result<ValidatedScripts> {
+        let Some((_, unit)) = &*self.unit.read().unwrap() else {
+            return Err(ScriptingError::new(
+                "Can't validate scripts before they have been compiled",
+            ));
+        };
+        Ok(ValidatedScripts {
+            version: VALIDATION_VERSION.to_string(),
+            source_hash: ValidatedScripts::hash_sources(&self.sources),
+            unit: unit_cache::serialize(unit)?,
+        })
+    }
+
+    fn load_unit(&mut self, unit: Arc<Unit>, sources: Vec<ScriptingSource>) -> Result<()> {
+        let runtime = self.runtime.clone().unwrap();
+        let vm = Vm::new(runtime.clone(), unit.clone());
+        // native components are created instead of scripted ones by the same name, which would
+        // otherwise never run
+        for name in native::names() {
+            if vm.lookup_function([name.as_str(), "new"]).is_ok() {
+                return Err(ScriptingError::new(&format!(
+                    "The scripts define a component '{}', but a native component is registered by that name",
+                    name
+                )));
+            }
+        }
+        *self.unit.write().unwrap() = Some((Arc::downgrade(&runtime), unit));
+        self.virtual_machine = Some(vm);
+        self.sources = sources;
+        Ok(())
+    }
+
+    /// Converts a vm error into a scripting error pointing at the instruction that failed
+    fn vm_error(&self, error: VmError) -> ScriptingError {
+        let mut e = ScriptingError::new(&error.to_string());
+        let Some(location) = error.first_location() else {
+            return e;
+        };
+        let Some(debug_info) = location.unit.debug_info() else {
+            return e;
+        };
+        if let Some(inst) = debug_info.instruction_at(location.ip) {
+            if let Some(source) = self.sources.get(inst.source_id.into_index()) {
+                e = e.with_location(SourceLocation::from_offset(
+                    source,
+                    inst.span.start.0 as usize,
+                ));
+            }
+        }
+        let ips = std::iter::once(location.ip).chain(location.frames.iter().rev().map(|f| f.ip));
+        for ip in ips {
+            // functions are only indexed by their first instruction
+            let start = debug_info
+                .functions_rev
+                .keys()
+                .filter(|start| **start <= ip)
+                .max();
+            if let Some((_, signature)) = start.and_then(|start| debug_info.function_at(*start)) {
+                let path = signature.path.to_string();
+                let (component, method) = path.rsplit_once("::").unwrap_or(("", path.as_str()));
+                e = e.with_frame(component, method);
+            }
+        }
+        e
+    }
+
+    fn run_component_methods_on_entity(
+        &mut self,
+        entity: &mut crate::ecs::RuntimeEntity<Self>,
+        entity_lookup: &HashMap<u32, Arc<Mutex<crate::ecs::RuntimeEntity<Self>>>>,
+        c_flags: ComponentFlags,
+    ) -> Vec<EntityUpdate> {
+        #[cfg(feature = "disable_common_ecs_methods")]
+        {
+            if c_flags == ComponentFlags::START || c_flags == ComponentFlags::DESTROY {
+                return Vec::new();
+            }
+        }
+        let mut entity_obj = convert_entity(entity);
+        let (shared, _guard) = unsafe { Shared::from_mut(&mut entity_obj).unwrap() };
+        self.shared_entities.write().unwrap().insert(
+            entity.get_id(),
+            SharedWrapper {
+                shared: shared.clone(),
+            },
+        );
+        let method = flags_to_method(c_flags);
+        let entity_id = entity.get_id();
+        let profiling = crate::scripting::profiler::PROFILER
+            .lock()
+            .unwrap()
+            .is_enabled();
+        for component in entity.components.iter_mut() {
+            if component.flags & c_flags != c_flags
+                || component.data.native.is_some()
+                || self.budget.frame_exhausted()
+            {
+                continue;
+            }
+            let data = match &component.data.data {
+                Some(data) => Value::Struct(data.clone()),
+                None => Value::EmptyTuple,
+            };
+            let name = component.component_proto.name.as_str();
+            let vm = self.virtual_machine.as_mut().unwrap();
+            let ((r, duration), outcome, instructions) = self.budget.run(
+                || {
+                    let start = instant::Instant::now();
+                    let r = vm.call([name, method], (data, shared.clone()));
+                    (r, start.elapsed())
+                },
+                profiling,
+            );
+            if profiling {
+                crate::scripting::profiler::PROFILER.lock().unwrap().record(
+                    name,
+                    method,
+                    duration,
+                    instructions,
+                );
+            }
+            if let Err(e) = r {
+                let e = outcome.explain(self.vm_error(e));
+                // the component method is the outermost frame, unless the script function frames
+                // already ended with it
+                let e = match e.stack().last() {
+                    Some(frame)
+                        if frame.component == component.component_proto.name
+                            && frame.method == method =>
+                    {
+                        e
+                    }
+                    _ => e.with_frame(&component.component_proto.name, method),
+                };
+                logging::script_error(&e);
+                if let budget::BudgetOutcome::MethodExhausted(_) = outcome {
+                    if self.budget.add_failure(entity_id, name) {
+                        error!(
+                            "Disabled component {} of entity {} after it ran out of instructions {} times in a row",
+                            name,
+                            entity_id,
+                            self.budget.limits.max_failures
+                        );
+                        component.flags = ComponentFlags::EMPTY;
+                    }
+                }
+            } else {
+                self.budget.clear_failures(entity_id, name);
+            }
+        }
+        for (id, shared) in self.shared_entities.write().unwrap().drain() {
+            let entity_obj = shared.shared.downcast_borrow_ref::<RuneEntity>().unwrap();
+            if id != entity.get_id() {
+                let mut entity = entity_lookup.get(&id).unwrap().lock().unwrap();
+                process_entity_update(entity_obj.clone(), &mut entity);
+            } else {
+                process_entity_update(entity_obj.clone(), entity);
+            }
+        }
+        let mut updates = Vec::new();
+        for drawable in &entity_obj.drawables {
+            updates.push(EntityUpdate::AddDrawable(Into::<DrawablePrototype>::into(
+                drawable,
+            )));
+        }
+        for drawable in &entity_obj.remove_drawables {
+            updates.push(EntityUpdate::RemoveDrawable(drawable.clone()));
+        }
+        for property_update in &entity_obj.property_updates {
+            updates.push(EntityUpdate::SetDrawableProperty(
+                property_update.0.clone(),
+                property_update.1.clone(),
+                property_update.2.clone(),
+            ));
+        }
+        // native components run after the scripted ones, seeing the changes they made
+        updates.extend(native::run_methods(
+            entity,
+            c_flags,
+            self.input_state.as_ref(),
+            |data| data.native.as_deref_mut(),
+        ));
+        updates
+    }
+}
+
+fn process_entity_update(entity_obj: RuneEntity, entity: &mut RuntimeEntity<RuneInstance>) {
+    if entity_obj.name != entity.get_name() {
+        entity.set_name(entity_obj.name.clone());
+    }
+    let rune_transform: RuneTransform = entity_obj.clone().transform.take_downcast().unwrap();
+    let new_transform: Transform = rune_transform.into();
+    let mut rtransform = entity.transform.lock().unwrap();
+    if rtransform.transform != new_transform {
+        rtransform.has_changed = true;
+        rtransform.transform = new_transform;
+    }
+}
+
+fn flags_to_method(flags: ComponentFlags) -> &'static str {
+    match flags {
+        ComponentFlags::BUILD => "build",
+        ComponentFlags::FRAME => "frame",
+        ComponentFlags::LATE_FRAME => "late_frame",
+        ComponentFlags::TICK => "tick",
+        ComponentFlags::START => "start",
+        ComponentFlags::DESTROY => "destroy",
+        #[cfg(feature = "editor")]
+        ComponentFlags::EDITOR_START => "editor_start",
+        #[cfg(feature = "editor")]
+        ComponentFlags::EDITOR_DESTROY => "editor_destroy",
+        #[cfg(feature = "editor")]
+        ComponentFlags::EDITOR_UPDATE => "editor_update",
+        _ => panic!("Invalid component flags"),
+    }
+}
+
+#[cfg(feature = "scene_generation")]
+pub unsafe fn get_required_assets() -> Vec<String> {
+    REQUIRED_ASSETS.clone()
+}
+
+#[cfg(feature = "scene_generation")]
+pub unsafe fn clear_required_assets() {
+    REQUIRED_ASSETS.clear();
+}
+
+// im really sorry for what im about to do...
+struct SharedWrapper {
+    shared: Shared<AnyObj>,
+}
+unsafe impl Send for RuneComponent {}
+unsafe impl Send for SharedWrapper {}
+unsafe impl Sync for SharedWrapper {}
+#[cfg(feature = "editor")]
+unsafe impl<T: ScriptingInstance> Send for crate::editor::EventHandler<T> {}
+// lets just ignore that that ever happened......
+
+fn core_modules(
+    input_state: Option<Arc<Mutex<InputState>>>,
+    ecs: Arc<RwLock<ECS<RuneInstance>>>,
+    shared_entities: Arc<RwLock<HashMap<u32, SharedWrapper>>>,
+    unit: SharedUnit,
+) -> Result<Vec<Module>> {
+    let mut m = Module::new();
+
+    // Types
+    m.ty::<RuneEntity>()?;
+    m.ty::<RuneTransform>()?;
+    m.ty::<Drawable>()?;
+    m.ty::<Touch>()?;
+    math::register(&mut m)?;
+    testing::register(&mut m)?;
+    m.function_meta(RuneEntity::get_component)?;
+    m.raw_function("call", move |stack: &mut Stack, args: usize| {
+        entity_call(stack, args, &unit)
+    })
+    .build_associated::<RuneEntity>()?;
+    m.function_meta(RuneEntity::register_drawable)?;
+    m.function_meta(RuneEntity::unregister_drawable)?;
+    m.function_meta(RuneEntity::set_drawable_color)?;
+    m.function_meta(RuneEntity::set_drawable_sprite)?;
+    m.function_meta(RuneEntity::set_drawable_layer)?;
+    m.function_meta(RuneEntity::set_drawable_order)?;
+    m.function_meta(Drawable::sprite)?;
+    m.function_meta(Drawable::sprite_variant)?;
+
+    m.field_function(
+        Protocol::ADD_ASSIGN,
+        "position",
+        RuneTransform::add_position,
+    )?;
+    m.field_function(
+        Protocol::SUB_ASSIGN,
+        "position",
+        RuneTransform::sub_position,
+    )?;
+    m.field_function(
+        Protocol::MUL_ASSIGN,
+        "position",
+        RuneTransform::mul_position,
+    )?;
+    m.field_function(
+        Protocol::DIV_ASSIGN,
+        "position",
+        RuneTransform::div_position,
+    )?;
+    m.field_function(Protocol::ADD_ASSIGN, "scale", RuneTransform::add_scale)?;
+    m.field_function(Protocol::SUB_ASSIGN, "scale", RuneTransform::sub_scale)?;
+    m.field_function(Protocol::MUL_ASSIGN, "scale", RuneTransform::mul_scale)?;
+    m.field_function(Protocol::DIV_ASSIGN, "scale", RuneTransform::div_scale)?;
+
+    m.function("print", |log: &str| log!("[RUNE] {}", log))
+        .build()?;
+    m.function("error", |log: &str| error!("[RUNE] {}", log))
+        .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_time", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().time
+        } else {
+            0.0
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_delta_time", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().delta_time
+        } else {
+            0.0
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_key", move |key: &str| {
+        query_key(&input_state_clone, key, InputState::get_key)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_key_down", move |key: &str| {
+        query_key(&input_state_clone, key, InputState::get_key_down)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_key_up", move |key: &str| {
+        query_key(&input_state_clone, key, InputState::get_key_up)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_text_input", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().get_text()
+        } else {
+            String::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_text_composition", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().composition.clone()
+        } else {
+            String::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("set_text_input_enabled", move |enabled: bool| {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().text_input_enabled = enabled;
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("action_pressed", move |name: &str| {
+        query_action(&input_state_clone, name, InputState::action_pressed)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("action_down", move |name: &str| {
+        query_action(&input_state_clone, name, InputState::action_down)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("action_up", move |name: &str| {
+        query_action(&input_state_clone, name, InputState::action_up)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("axis", move |name: &str| {
+        if let Some(input_state) = &input_state_clone {
+            let input_state = input_state.lock().unwrap();
+            if !input_state.actions.has_axis(name) {
+                return VmResult::panic(format!("Unknown input axis '{}'", name));
+            }
+            VmResult::Ok(input_state.axis(name))
+        } else {
+            VmResult::Ok(0.0)
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_action_bindings", move |name: &str| {
+        if let Some(input_state) = &input_state_clone {
+            let input_state = input_state.lock().unwrap();
+            input_state
+                .actions
+                .action_bindings(name)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("rebind_action", move |name: &str, bindings: Vec<String>| {
+        if let Some(input_state) = &input_state_clone {
+            let mut input_state = input_state.lock().unwrap();
+            if let Err(e) = input_state.actions.rebind_action(name, bindings) {
+                return VmResult::panic(e);
+            }
+            actions::save_overrides(input_state.actions.overrides());
+        }
+        VmResult::Ok(())
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function(
+        "rebind_axis",
+        move |name: &str, negative: Vec<String>, positive: Vec<String>| {
+            if let Some(input_state) = &input_state_clone {
+                let mut input_state = input_state.lock().unwrap();
+                // the gamepad axis isn't something players usually remap, so we keep it
+                let gamepad = input_state
+                    .actions
+                    .axis_bindings(name)
+                    .and_then(|axis| axis.gamepad.clone());
+                let bindings = actions::AxisPreferences {
+                    negative,
+                    positive,
+                    gamepad,
+                };
+                if let Err(e) = input_state.actions.rebind_axis(name, bindings) {
+                    return VmResult::panic(e);
+                }
+                actions::save_overrides(input_state.actions.overrides());
+            }
+            VmResult::Ok(())
+        },
+    )
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("reset_bindings", move || {
+        if let Some(input_state) = &input_state_clone {
+            let mut input_state = input_state.lock().unwrap();
+            input_state.actions.reset();
+            actions::save_overrides(input_state.actions.overrides());
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_position", move || {
+        if let Some(input_state) = &input_state_clone {
+            Vec2::from_tuple(input_state.lock().unwrap().mouse.position)
+        } else {
+            Vec2::new(0.0, 0.0)
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_world_position", move || {
+        if let Some(input_state) = &input_state_clone {
+            Vec2::from_tuple(input_state.lock().unwrap().mouse.world_position)
+        } else {
+            Vec2::new(0.0, 0.0)
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_button", move |button: &str| {
+        query_mouse_button(&input_state_clone, button, MouseState::get_button)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_button_down", move |button: &str| {
+        query_mouse_button(&input_state_clone, button, MouseState::get_button_down)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_button_up", move |button: &str| {
+        query_mouse_button(&input_state_clone, button, MouseState::get_button_up)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_mouse_wheel", move || {
+        if let Some(input_state) = &input_state_clone {
+            Vec2::from_tuple(input_state.lock().unwrap().mouse.wheel_delta)
+        } else {
+            Vec2::new(0.0, 0.0)
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_touches", move || {
+        if let Some(input_state) = &input_state_clone {
+            let input_state = input_state.lock().unwrap();
+            input_state.touches.iter().map(Touch::from).collect()
+        } else {
+            Vec::<Touch>::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_gamepads", move || {
+        if let Some(input_state) = &input_state_clone {
+            let input_state = input_state.lock().unwrap();
+            input_state
+                .gamepads
+                .iter()
+                .map(|gamepad| gamepad.id)
+                .collect()
+        } else {
+            Vec::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_gamepads_connected", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().connected_gamepads.clone()
+        } else {
+            Vec::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_gamepads_disconnected", move || {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().disconnected_gamepads.clone()
+        } else {
+            Vec::new()
+        }
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_button", move |id: u32, button: &str| {
+        query_gamepad_button(
+            &input_state_clone,
+            id,
+            button,
+            gamepad::GamepadState::get_button,
+        )
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_button_down", move |id: u32, button: &str| {
+        query_gamepad_button(
+            &input_state_clone,
+            id,
+            button,
+            gamepad::GamepadState::get_button_down,
+        )
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_button_up", move |id: u32, button: &str| {
+        query_gamepad_button(
+            &input_state_clone,
+            id,
+            button,
+            gamepad::GamepadState::get_button_up,
+        )
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("get_axis", move |id: u32, axis: &str| {
+        let Some(axis) = gamepad::str_to_axis(axis) else {
+            return VmResult::panic(format!("Unknown gamepad axis '{}'", axis));
+        };
+        if let Some(input_state) = &input_state_clone {
+            let input_state = input_state.lock().unwrap();
+            if let Some(gamepad) = input_state.get_gamepad(id) {
+                return VmResult::Ok(gamepad.get_axis(axis, input_state.dead_zone));
+            }
+        }
+        VmResult::Ok(0.0)
+    })
+    .build()?;
+    let input_state_clone = input_state.clone();
+    m.function("set_dead_zone", move |dead_zone: f32| {
+        if let Some(input_state) = &input_state_clone {
+            input_state.lock().unwrap().dead_zone = dead_zone.clamp(0.0, 0.99);
+        }
+    })
+    .build()?;
+    m.function("e", move |id: u32| {
+        if shared_entities.read().unwrap().contains_key(&id) {
+            return Some(
+                shared_entities
+                    .read()
+                    .unwrap()
+                    .get(&id)
+                    .unwrap()
+                    .shared
+                    .clone(),
+            );
+        }
+        let e = ecs.read().unwrap().get_entity(id);
+        if let Some(e) = e {
+            let entity = e.try_lock();
+            if let Ok(entity) = entity {
+                let converted = convert_entity(&entity);
+                let shared = Shared::new(AnyObj::new(converted).unwrap()).unwrap();
+                shared_entities.write().unwrap().insert(
+                    id,
+                    SharedWrapper {
+                        shared: shared.clone(),
+                    },
+                );
+                Some(shared)
+            } else {
+                log!(
+                    "Couldn't get entity. Are you trying to access the currently executing entity?"
+                );
+                None
+            }
+        } else {
+            None
+        }
+    })
+    .build()?;
+    // Math Constants
+    m.constant("PI", std::f64::consts::PI).build()?;
+    m.constant("E", std::f64::consts::E).build()?;
+
+    // Platform Constants
+    #[cfg(target_arch = "wasm32")]
+    {
+        m.constant("PLATFORM", "WEB").build()?;
+        m.constant("IS_WEB", true).build()?;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        m.constant("PLATFORM", "DESKTOP").build()?;
+        m.constant("IS_WEB", false).build()?;
+    }
+    m.constant("LOITSU_VERSION", env!("CARGO_PKG_VERSION"))
+        .build()?;
+
+    #[cfg(feature = "scene_generation")]
+    {
+        m.function("require_asset", |asset: &str| {
+            unsafe {
+                REQUIRED_ASSETS.push(asset.to_string());
+            }
+            Ok::<(), ()>(())
+        })
+        .build()?;
+    }
+    #[cfg(not(feature = "scene_generation"))]
+    {
+        m.function("require_asset", |_asset: &str| Ok::<(), ()>(()))
+            .build()?;
+    }
+    m.raw_function("dbg", dbg_impl).build()?;
+
+    // scene build steps have no input, they get fixed seeds so that builds are reproducible
+    let random_state = input_state.unwrap_or_else(|| Arc::new(Mutex::new(InputState::new())));
+    Ok(vec![
+        rune::modules::core::module()?,
+        rune::modules::option::module()?,
+        rune::modules::result::module()?,
+        rune::modules::iter::module()?,
+        rune::modules::collections::module()?,
+        rune::modules::ops::module()?,
+        random::module(random_state)?,
+        profiler::module()?,
+        storage::module()?,
+        camera::module()?,
+        testing::module()?,
+        m,
+    ]
+    .into_iter()
+    .chain(custom_modules()?)
+    .collect())
+}
+
+pub(crate) type ModuleFactory =
+    Box<dyn Fn() -> std::result::Result<Module, ContextError> + Send + Sync>;
+
+lazy_static! {
+    static ref CUSTOM_MODULES: RwLock<Vec<ModuleFactory>> = RwLock::new(Vec::new());
+}
+
+/// Installs a module into every script context of the process from now on, next to the modules
+/// of the engine. This is how games expose their own functions and types to scripts. Modules have
+/// to be registered before the scripts are compiled, which happens when scenes are built, when
+/// the engine starts and when the editor loads a scene, so the CLI and editor only know of them
+/// when they are run from a binary of the game (see `loitsu_cli::run` and `loitsu_editor::start`).
+pub fn register_module<F>(module: F)
+where
+    F: Fn() -> std::result::Result<Module, ContextError> + Send + Sync + 'static,
+{
+    CUSTOM_MODULES.write().unwrap().push(Box::new(module));
+}
+
+fn custom_modules() -> Result<Vec<Module>> {
+    let factories = CUSTOM_MODULES.read().unwrap();
+    let mut modules = Vec::with_capacity(factories.len());
+    for factory in factories.iter() {
+        modules.push(factory()?);
+    }
+    Ok(modules)
+}
+
+fn query_key(
+    input_state: &Option<Arc<Mutex<InputState>>>,
+    key: &str,
+    query: fn(&InputState, &KeyInput) -> bool,
+) -> VmResult<bool> {
+    let Some(key) = parse_key(key) else {
+        return VmResult::panic(format!("Unknown key '{}'", key));
+    };
+    if let Some(input_state) = input_state {
+        VmResult::Ok(query(&input_state.lock().unwrap(), &key))
+    } else {
+        VmResult::Ok(false)
+    }
+}
+
+fn query_mouse_button(
+    input_state: &Option<Arc<Mutex<InputState>>>,
+    button: &str,
+    query: fn(&MouseState, MouseButton) -> bool,
+) -> VmResult<bool> {
+    let Some(button) = str_to_button(button) else {
+        return VmResult::panic(format!("Unknown mouse button '{}'", button));
+    };
+    if let Some(input_state) = input_state {
+        VmResult::Ok(query(&input_state.lock().unwrap().mouse, button))
+    } else {
+        VmResult::Ok(false)
+    }
+}
+
+fn query_gamepad_button(
+    input_state: &Option<Arc<Mutex<InputState>>>,
+    id: u32,
+    button: &str,
+    query: fn(&gamepad::GamepadState, gamepad::GamepadButton) -> bool,
+) -> VmResult<bool> {
+    let Some(button) = gamepad::str_to_button(button) else {
+        return VmResult::panic(format!("Unknown gamepad button '{}'", button));
+    };
+    if let Some(input_state) = input_state {
+        let input_state = input_state.lock().unwrap();
+        if let Some(gamepad) = input_state.get_gamepad(id) {
+            return VmResult::Ok(query(gamepad, button));
+        }
+    }
+    VmResult::Ok(false)
+}
+
+fn query_action(
+    input_state: &Option<Arc<Mutex<InputState>>>,
+    name: &str,
+    query: fn(&InputState, &str) -> bool,
+) -> VmResult<bool> {
+    if let Some(input_state) = input_state {
+        let input_state = input_state.lock().unwrap();
+        if !input_state.actions.has_action(name) {
+            return VmResult::panic(format!("Unknown input action '{}'", name));
+        }
+        VmResult::Ok(query(&input_state, name))
+    } else {
+        VmResult::Ok(false)
+    }
+}
+
+/// `entity.call(component, method, args...)` runs a method of one of the entity's components.
+/// The method is run in a separate vm, as the calling method is still running in the main one.
+fn entity_call(stack: &mut Stack, args: usize, unit: &SharedUnit) -> VmResult<()> {
+    if args < 3 {
+        return VmResult::panic("entity.call expects a component and a method name");
+    }
+    let mut values = rune::vm_try!(stack.drain(args))
+        .collect::<Vec<_>>()
+        .into_iter();
+    let entity = values.next().unwrap();
+    let component: String = rune::vm_try!(rune::from_value(values.next().unwrap()));
+    let method: String = rune::vm_try!(rune::from_value(values.next().unwrap()));
+    let data = {
+        let Value::Any(shared) = &entity else {
+            return VmResult::panic("entity.call must be called on an entity");
+        };
+        // the borrow has to end before the method is called, as it will borrow the entity again
+        let entity_obj = rune::vm_try!(shared.downcast_borrow_ref::<RuneEntity>());
+        match entity_obj
+            .components
+            .iter()
+            .find(|(name, _)| *name == component)
+        {
+            Some((_, data)) => data.clone(),
+            None => {
+                return VmResult::panic(format!(
+                    "Entity '{}' has no component '{}'",
+                    entity_obj.name, component
+                ))
+            }
+        }
+    };
+    let Some((runtime, unit)) = unit.read().unwrap().clone() else {
+        return VmResult::panic("Scripting hasn't been initialized");
+    };
+    let Some(runtime) = runtime.upgrade() else {
+        return VmResult::panic("Scripting hasn't been initialized");
+    };
+    let mut call_args = vec![data, entity];
+    call_args.extend(values);
+    let mut vm = Vm::new(runtime, unit);
+    let mut execution = rune::vm_try!(vm.execute([component.as_str(), method.as_str()], call_args));
+    let result = rune::vm_try!(execution.complete());
+    rune::vm_try!(stack.push(result));
+    VmResult::Ok(())
+}
+
+fn dbg_impl(stack: &mut rune::runtime::Stack, args: usize) -> VmResult<()> {
+    for value in rune::vm_try!(stack.drain(args)) {
+        log!("{:?}", value);
+    }
+
+    rune::vm_try!(stack.push(Value::EmptyTuple));
+    VmResult::Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripting::conformance::{self, ConformanceBackend};
+
+    impl ConformanceBackend for RuneInstance {
+        fn conformance_sources() -> Vec<ScriptingSource> {
+            let source = r#"
+                pub struct Echo;
+                impl Echo {
+                    pub fn echo(value) { value }
+                }
+
+                pub struct Math;
+                impl Math {
+                    pub fn add(a, b) { a + b }
+                }
+
+                pub struct Counter { count, label }
+                impl Counter {
+                    pub fn new() { Counter { count: 0.0, label: "" } }
+                    pub fn frame(self, entity) { self.count += 1.0; }
+                }
+
+                pub struct Broken;
+                impl Broken {
+                    pub fn new() { Broken {} }
+                    pub fn frame(self, entity) { self.missing(); }
+                }
+            "#;
+            vec![ScriptingSource {
+                name: "conformance.rn".to_string(),
+                source: source.to_string(),
+            }]
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        conformance::run::<RuneInstance>();
+    }
+
+    #[derive(Any)]
+    #[rune(item = ::game)]
+    struct Score(i64);
+
+    /// Removes the modules registered by a test once it's done, so that they don't end up in
+    /// the contexts of other tests
+    struct RegisteredModules;
+
+    impl Drop for RegisteredModules {
+        fn drop(&mut self) {
+            CUSTOM_MODULES.write().unwrap().clear();
+        }
+    }
+
+    fn input_instance(source: &str) -> (RuneInstance, Arc<Mutex<InputState>>) {
+        let sources = vec![ScriptingSource {
+            name: "game.rn".to_string(),
+            source: source.to_string(),
+        }];
+        #[cfg(not(feature = "editor"))]
+        let ecs = Arc::new(RwLock::new(ECS::new()));
+        #[cfg(feature = "editor")]
+        let ecs = Arc::new(RwLock::new(ECS::new(Arc::new(Mutex::new(
+            crate::editor::EventHandler::new(),
+        )))));
+        let input_state = Arc::new(Mutex::new(InputState::new()));
+        let mut instance = RuneInstance::new_uninitialized().unwrap();
+        instance
+            .initialize(sources, input_state.clone(), ecs)
+            .unwrap();
+        (instance, input_state)
+    }
+
+    #[test]
+    fn test_mouse_buttons() {
+        let (mut instance, input_state) = input_instance(
+            r#"pub struct Game;
+            impl Game {
+                pub fn left() { get_mouse_button_down("Left") }
+                pub fn thumb() { get_mouse_button("Thumb") }
+            }"#,
+        );
+        input_state
+            .lock()
+            .unwrap()
+            .process_event(crate::input::InputEvent::MouseButton(
+                MouseButton::Left,
+                true,
+            ));
+        assert_eq!(
+            instance.call(["Game", "left"], Vec::new()).unwrap(),
+            ScriptValue::Bool(true)
+        );
+        let error = instance.call(["Game", "thumb"], Vec::new()).unwrap_err();
+        assert!(error.message().contains("Unknown mouse button 'Thumb'"));
+    }
+
+    #[test]
+    fn test_gamepads() {
+        let (mut instance, input_state) = input_instance(
+            r#"pub struct Game;
+            impl Game {
+                pub fn jump() { get_button_down(0, "A") }
+                pub fn stick() { get_axis(0, "LeftStickX") }
+                pub fn missing() { get_button(1, "South") }
+                pub fn unknown_button() { get_button(0, "Turbo") }
+                pub fn unknown_axis() { get_axis(0, "Wheel") }
+            }"#,
+        );
+        {
+            let mut input_state = input_state.lock().unwrap();
+            input_state.process_event(crate::input::InputEvent::Gamepad(
+                gamepad::GamepadEvent::ButtonPressed(0, gamepad::GamepadButton::South),
+            ));
+            input_state.process_event(crate::input::InputEvent::Gamepad(
+                gamepad::GamepadEvent::AxisChanged(0, gamepad::GamepadAxis::LeftStickX, 1.0),
+            ));
+        }
+        assert_eq!(
+            instance.call(["Game", "jump"], Vec::new()).unwrap(),
+            ScriptValue::Bool(true)
+        );
+        assert_eq!(
+            instance.call(["Game", "stick"], Vec::new()).unwrap(),
+            ScriptValue::Float(1.0)
+        );
+        // gamepads that aren't connected just aren't pressing anything
+        assert_eq!(
+            instance.call(["Game", "missing"], Vec::new()).unwrap(),
+            ScriptValue::Bool(false)
+        );
+        let error = instance
+            .call(["Game", "unknown_button"], Vec::new())
+            .unwrap_err();
+        assert!(error.message().contains("Unknown gamepad button 'Turbo'"));
+        let error = instance
+            .call(["Game", "unknown_axis"], Vec::new())
+            .unwrap_err();
+        assert!(error.message().contains("Unknown gamepad axis 'Wheel'"));
+    }
+
+    #[test]
+    fn test_drawables() {
+        let sources = vec![ScriptingSource {
+            name: "game.rn".to_string(),
+            source: r#"pub struct Game;
+                impl Game {
+                    pub fn layers() {
+                        let old = Drawable::Sprite("a.png", Color::white());
+                        let new = Drawable::sprite("a.png", Color::white());
+                        new.layer = "Foreground";
+                        [old.layer, new.layer]
+                    }
+                }"#
+            .to_string(),
+        }];
+        #[cfg(not(feature = "editor"))]
+        let ecs = Arc::new(RwLock::new(ECS::new()));
+        #[cfg(feature = "editor")]
+        let ecs = Arc::new(RwLock::new(ECS::new(Arc::new(Mutex::new(
+            crate::editor::EventHandler::new(),
+        )))));
+        let mut instance = RuneInstance::new_with_sources(sources, ecs).unwrap();
+        assert_eq!(
+            instance.call(["Game", "layers"], Vec::new()).unwrap(),
+            ScriptValue::Array(vec![
+                ScriptValue::String(DEFAULT_LAYER.to_string()),
+                ScriptValue::String("Foreground".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_custom_modules() {
+        let _modules = RegisteredModules;
+        register_module(|| {
+            let mut m = Module::with_crate("game")?;
+            m.ty::<Score>()?;
+            m.function("score", Score).build()?;
+            m.function("value", |score: &Score| score.0)
+                .build_associated::<Score>()?;
+            Ok(m)
+        });
+        let sources = vec![ScriptingSource {
+            name: "game.rn".to_string(),
+            source: "pub struct Game;
+                impl Game {
+                    pub fn doubled(x) { game::score(x * 2).value() }
+                }"
+            .to_string(),
+        }];
+        // the context used by scene builds
+        #[cfg(not(feature = "editor"))]
+        let ecs = Arc::new(RwLock::new(ECS::new()));
+        #[cfg(feature = "editor")]
+        let ecs = Arc::new(RwLock::new(ECS::new(Arc::new(Mutex::new(
+            crate::editor::EventHandler::new(),
+        )))));
+        let mut instance = RuneInstance::new_with_sources(sources.clone(), ecs.clone()).unwrap();
+        assert_eq!(
+            instance
+                .call(["Game", "doubled"], vec![ScriptValue::Integer(21)])
+                .unwrap(),
+            ScriptValue::Integer(42)
+        );
+        // and the one used at runtime
+        let mut instance = RuneInstance::new_uninitialized().unwrap();
+        instance
+            .initialize(sources, Arc::new(Mutex::new(InputState::new())), ecs)
+            .unwrap();
+        assert_eq!(
+            instance
+                .call(["Game", "doubled"], vec![ScriptValue::Integer(2)])
+                .unwrap(),
+            ScriptValue::Integer(4)
+        );
+    }
+
+    #[test]
+    fn test_validated() {
+        let sources = vec![ScriptingSource {
+            name: "game.rn".to_string(),
+            source: "pub struct Game; impl Game { pub fn value() { 1 } pub fn fail() { [][0] } }"
+                .to_string(),
+        }];
+        #[cfg(not(feature = "editor"))]
+        let ecs = Arc::new(RwLock::new(ECS::new()));
+        #[cfg(feature = "editor")]
+        let ecs = Arc::new(RwLock::new(ECS::new(Arc::new(Mutex::new(
+            crate::editor::EventHandler::new(),
+        )))));
+        let validated = RuneInstance::new_with_sources(sources.clone(), ecs.clone())
+            .unwrap()
+            .validated()
+            .unwrap();
+        assert!(validated.matches(VALIDATION_VERSION, &with_builtin(sources.clone())));
+        let mut changed = sources.clone();
+        changed[0].source = changed[0].source.replace('1', "2");
+        assert!(!validated.matches(VALIDATION_VERSION, &with_builtin(changed.clone())));
+        assert!(!validated.matches("loitsu-0.0.0/rune-0.0.0", &with_builtin(sources.clone())));
+        assert!(RuneInstance::new_uninitialized()
+            .unwrap()
+            .validated()
+            .is_err());
+
+        let initialize = |validated: ValidatedScripts| {
+            let mut instance = RuneInstance::new_uninitialized().unwrap();
+            instance.set_validated(validated);
+            instance
+                .initialize(
+                    sources.clone(),
+                    Arc::new(Mutex::new(InputState::new())),
+                    ecs.clone(),
+                )
+                .unwrap();
+            instance.call(["Game", "value"], vec![]).unwrap()
+        };
+        assert_eq!(initialize(validated.clone()), ScriptValue::Integer(1));
+        // a matching unit is loaded instead of compiling the sources
+        let other_unit = RuneInstance::new_with_sources(changed, ecs.clone())
+            .unwrap()
+            .validated()
+            .unwrap()
+            .unit;
+        assert_eq!(
+            initialize(ValidatedScripts {
+                unit: other_unit,
+                ..validated.clone()
+            }),
+            ScriptValue::Integer(2)
+        );
+        // errors of a loaded unit still point at the function and source that failed
+        let mut instance = RuneInstance::new_uninitialized().unwrap();
+        instance.set_validated(validated.clone());
+        instance
+            .initialize(
+                sources.clone(),
+                Arc::new(Mutex::new(InputState::new())),
+                ecs.clone(),
+            )
+            .unwrap();
+        let e = instance.call(["Game", "fail"], vec![]).unwrap_err();
+        assert_eq!(e.stack()[0].to_string(), "Game::fail");
+        assert_eq!(e.location().unwrap().source, "game.rn");
+        // and the sources are compiled if it can't be loaded
+        assert_eq!(
+            initialize(ValidatedScripts {
+                unit: b"not a unit".to_vec(),
+                ..validated
+            }),
+            ScriptValue::Integer(1)
+        );
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        use crate::headless::HeadlessRunner;
+        use crate::scene_management::{Entity, Scene};
+        use std::collections::BTreeMap;
+
+        let counter = |source: &str| ScriptingSource {
+            name: "counter.rn".to_string(),
+            source: source.to_string(),
+        };
+        let first = r#"pub struct Counter { count }
+            impl Counter {
+                pub fn new() { Counter { count: 0 } }
+                pub fn frame(self, entity) { self.count += 1; }
+            }"#;
+        let second = r#"pub struct Counter { count, label }
+            impl Counter {
+                pub fn new() { Counter { count: 0, label: "new" } }
+                pub fn frame(self, entity) { self.count += 10; }
+            }"#;
+        let mut scene = Scene::new("main".to_string());
+        let mut entity = Entity::new("counter".to_string(), 1);
+        entity.add_component(Component::new("Counter".to_string(), 2));
+        scene.add_entity(entity);
+        scene.id_space = 3;
+        let mut runner = HeadlessRunner::<RuneInstance>::new(vec![counter(first)], scene).unwrap();
+        let data = |runner: &HeadlessRunner<RuneInstance>| {
+            let entity = runner.ecs.read().unwrap().get_runtime_entities()[0].clone();
+            let entity = entity.lock().unwrap();
+            let data = entity.components[0].data.data.clone().unwrap();
+            to_script_value(&Value::Struct(data)).unwrap()
+        };
+        runner.step(0.016);
+        runner.step(0.016);
+
+        // the count survives the reload, the new field gets the value from 'new'
+        let reload = runner
+            .ecs
+            .read()
+            .unwrap()
+            .reload_scripts(&mut runner.scripting, vec![counter(second)]);
+        reload.unwrap();
+        runner.step(0.016);
+        assert_eq!(
+            data(&runner),
+            ScriptValue::Object(BTreeMap::from([
+                ("count".to_string(), ScriptValue::Integer(12)),
+                ("label".to_string(), ScriptValue::String("new".to_string())),
+            ]))
+        );
+
+        // scripts that don't compile leave the running ones in place
+        let reload = runner
+            .ecs
+            .read()
+            .unwrap()
+            .reload_scripts(&mut runner.scripting, vec![counter("pub struct Counter {")]);
+        assert!(reload.is_err());
+        runner.step(0.016);
+        assert_eq!(
+            data(&runner),
+            ScriptValue::Object(BTreeMap::from([
+                ("count".to_string(), ScriptValue::Integer(22)),
+                ("label".to_string(), ScriptValue::String("new".to_string())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_native_name_conflict() {
+        use crate::headless::HeadlessRunner;
+        use crate::scene_management::Scene;
+
+        // the builtin camera is a native component, so scripts can't have their own by its name
+        let camera = ScriptingSource {
+            name: "camera.rn".to_string(),
+            source: r#"pub struct Camera { zoom }
+                impl Camera {
+                    pub fn new() { Camera { zoom: 1.0 } }
+                }"#
+            .to_string(),
+        };
+        let result =
+            HeadlessRunner::<RuneInstance>::new(vec![camera], Scene::new("main".to_string()));
+        let Err(e) = result else {
+            panic!("the scripted Camera component should conflict with the native one");
+        };
+        assert!(e.message().contains("'Camera'"));
+    }
+
+    #[test]
+    fn test_entity_call() {
+        use crate::headless::HeadlessRunner;
+        use crate::scene_management::{Entity, Scene};
+
+        let source = r#"
+            pub struct Health { hp }
+            impl Health {
+                pub fn new() { Health { hp: 10 } }
+                pub fn damage(self, entity, amount) {
+                    self.hp -= amount;
+                    self.hp
+                }
+            }
+
+            pub struct Attacker { target, left }
+            impl Attacker {
+                pub fn new() { Attacker { target: 0, left: 0 } }
+                pub fn frame(self, entity) {
+                    self.left = e(self.target).unwrap().call("Health", "damage", 3);
+                }
+            }
+        "#;
+        let mut scene = Scene::new("main".to_string());
+        let mut player = Entity::new("player".to_string(), 1);
+        player.add_component(Component::new("Health".to_string(), 2));
+        scene.add_entity(player);
+        let mut enemy = Entity::new("enemy".to_string(), 3);
+        let mut attacker = Component::new("Attacker".to_string(), 4);
+        attacker.add_property("target".to_string(), Property::EntityReference(1));
+        enemy.add_component(attacker);
+        scene.add_entity(enemy);
+        scene.id_space = 5;
+        let scripts = vec![ScriptingSource {
+            name: "combat.rn".to_string(),
+            source: source.to_string(),
+        }];
+        let mut runner = HeadlessRunner::<RuneInstance>::new(scripts, scene).unwrap();
+        runner.step(0.016);
+        runner.step(0.016);
+        let field = |index: usize, field: &str| {
+            let entity = runner.ecs.read().unwrap().get_runtime_entities()[index].clone();
+            let entity = entity.lock().unwrap();
+            let data = entity.components[0].data.data.clone().unwrap();
+            let data = data.borrow_ref().unwrap();
+            to_script_value(data.get(field).unwrap()).unwrap()
+        };
+        // the attacker sees the value returned by the player's component
+        assert_eq!(field(0, "hp"), ScriptValue::Integer(4));
+        assert_eq!(field(1, "left"), ScriptValue::Integer(4));
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        use crate::headless::HeadlessRunner;
+        use crate::scene_management::{Entity, Scene};
+
+        let source = r#"
+            pub struct Cheap { frames }
+            impl Cheap {
+                pub fn new() { Cheap { frames: 0 } }
+                pub fn frame(self, entity) { self.frames += 1; }
+            }
+        "#;
+        let mut scene = Scene::new("main".to_string());
+        for i in 0..50 {
+            let mut entity = Entity::new(format!("cheap {}", i), i * 2 + 1);
+            entity.add_component(Component::new("Cheap".to_string(), i * 2 + 2));
+            scene.add_entity(entity);
+        }
+        scene.id_space = 101;
+        let scripts = vec![ScriptingSource {
+            name: "cheap.rn".to_string(),
+            source: source.to_string(),
+        }];
+        let mut runner = HeadlessRunner::<RuneInstance>::new(scripts, scene).unwrap();
+        // every method stays well within its own budget, but not all of them fit in the frame
+        runner.scripting.set_budget(crate::scripting::ScriptBudget {
+            method_instructions: Some(1000),
+            frame_instructions: Some(200),
+            max_failures: 3,
+        });
+        let frames = |runner: &HeadlessRunner<RuneInstance>| {
+            runner
+                .ecs
+                .read()
+                .unwrap()
+                .get_runtime_entities()
+                .iter()
+                .map(|entity| {
+                    let entity = entity.lock().unwrap();
+                    let data = entity.components[0].data.data.clone().unwrap();
+                    let data = data.borrow_ref().unwrap();
+                    match to_script_value(data.get("frames").unwrap()).unwrap() {
+                        ScriptValue::Integer(frames) => frames,
+                        value => panic!("unexpected frames {:?}", value),
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        runner.step(0.016);
+        let first = frames(&runner);
+        let ran = first.iter().filter(|frames| **frames == 1).count();
+        assert!(ran > 0 && ran < 50, "{} of the components ran", ran);
+        // the components that ran are the first ones, the rest were skipped
+        assert!(first[..ran].iter().all(|frames| *frames == 1));
+        assert!(first[ran..].iter().all(|frames| *frames == 0));
+        // and the budget is reset on the next frame
+        runner.step(0.016);
+        assert_eq!(frames(&runner)[..ran], vec![2; ran]);
+    }
+}
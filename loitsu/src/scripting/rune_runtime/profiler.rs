@@ -0,0 +1,68 @@
+use crate::scripting::profiler::{self, MethodProfile, PROFILER};
+use rune::{Any, ContextError, Module};
+
+/// The `profiler` module, for inspecting how long component methods take from scripts
+pub fn module() -> Result<Module, ContextError> {
+    let mut m = Module::with_crate("profiler")?;
+    m.ty::<RuneMethodProfile>()?;
+    m.function("enable", || PROFILER.lock().unwrap().set_enabled(true))
+        .build()?;
+    m.function("disable", || PROFILER.lock().unwrap().set_enabled(false))
+        .build()?;
+    m.function("is_enabled", || PROFILER.lock().unwrap().is_enabled())
+        .build()?;
+    m.function("reset", || PROFILER.lock().unwrap().reset())
+        .build()?;
+    m.function("show_overlay", |show: bool| {
+        PROFILER.lock().unwrap().set_overlay(show)
+    })
+    .build()?;
+    m.function("report", || {
+        PROFILER
+            .lock()
+            .unwrap()
+            .report()
+            .into_iter()
+            .map(RuneMethodProfile::from)
+            .collect::<Vec<_>>()
+    })
+    .build()?;
+    m.function("dump", || {
+        profiler::format_report(&PROFILER.lock().unwrap().report())
+    })
+    .build()?;
+    Ok(m)
+}
+
+#[derive(Any, Clone)]
+#[rune(item = ::profiler, name = MethodProfile)]
+pub struct RuneMethodProfile {
+    #[rune(get)]
+    component: String,
+    #[rune(get)]
+    method: String,
+    #[rune(get, copy)]
+    calls: i64,
+    #[rune(get, copy)]
+    mean_ms: f64,
+    #[rune(get, copy)]
+    p95_ms: f64,
+    #[rune(get, copy)]
+    max_ms: f64,
+    #[rune(get)]
+    instructions: Option<f64>,
+}
+
+impl From<MethodProfile> for RuneMethodProfile {
+    fn from(profile: MethodProfile) -> Self {
+        RuneMethodProfile {
+            component: profile.component,
+            method: profile.method,
+            calls: profile.calls as i64,
+            mean_ms: profile.mean_ms,
+            p95_ms: profile.p95_ms,
+            max_ms: profile.max_ms,
+            instructions: profile.instructions,
+        }
+    }
+}
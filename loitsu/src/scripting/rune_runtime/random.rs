@@ -0,0 +1,147 @@
+use crate::input::InputState;
+use crate::util::random::DEFAULT_STREAM;
+use rune::runtime::{Value, VmResult};
+use rune::{Any, ContextError, Module};
+use std::sync::{Arc, Mutex};
+
+/// The `rand` module. Numbers come from seeded streams stored in the input state, so a run can be
+/// reproduced by reusing its seed.
+pub fn module(input_state: Arc<Mutex<InputState>>) -> Result<Module, ContextError> {
+    let mut m = Module::with_crate("rand")?;
+    m.ty::<RandomStream>()?;
+    m.function_meta(RandomStream::random)?;
+    m.function_meta(RandomStream::range)?;
+    m.function_meta(RandomStream::choose)?;
+    m.function_meta(RandomStream::shuffle)?;
+    m.function_meta(RandomStream::seed)?;
+
+    let default = RandomStream::new(DEFAULT_STREAM, input_state.clone());
+    {
+        let default = default.clone();
+        m.function("random", move || default.next_f64()).build()?;
+    }
+    {
+        let default = default.clone();
+        m.function("range", move |min: Value, max: Value| {
+            default.next_range(min, max)
+        })
+        .build()?;
+    }
+    {
+        let default = default.clone();
+        m.function("choose", move |items: &rune::runtime::Vec| {
+            default.choose_from(items)
+        })
+        .build()?;
+    }
+    m.function("shuffle", move |items: &mut rune::runtime::Vec| {
+        default.shuffle_items(items)
+    })
+    .build()?;
+    {
+        let input_state = input_state.clone();
+        m.function("seed", move |seed: i64| {
+            input_state.lock().unwrap().random.reseed(seed as u64)
+        })
+        .build()?;
+    }
+    {
+        let input_state = input_state.clone();
+        m.function("get_seed", move || {
+            input_state.lock().unwrap().random.seed() as i64
+        })
+        .build()?;
+    }
+    m.function("stream", move |name: &str| {
+        RandomStream::new(name, input_state.clone())
+    })
+    .build()?;
+    Ok(m)
+}
+
+/// A named, independent stream of random numbers
+#[derive(Any, Clone)]
+#[rune(item = ::rand)]
+pub struct RandomStream {
+    name: String,
+    input_state: Arc<Mutex<InputState>>,
+}
+
+impl RandomStream {
+    fn new(name: &str, input_state: Arc<Mutex<InputState>>) -> RandomStream {
+        RandomStream {
+            name: name.to_string(),
+            input_state,
+        }
+    }
+
+    fn next_f64(&self) -> f64 {
+        let mut input_state = self.input_state.lock().unwrap();
+        input_state.random.stream(&self.name).next_f64()
+    }
+
+    /// Integers give an integer in `min..max`, anything else a float
+    fn next_range(&self, min: Value, max: Value) -> VmResult<Value> {
+        let mut input_state = self.input_state.lock().unwrap();
+        let rng = input_state.random.stream(&self.name);
+        match (min, max) {
+            (Value::Integer(min), Value::Integer(max)) => {
+                VmResult::Ok(Value::Integer(rng.range_i64(min, max)))
+            }
+            (min, max) => {
+                let min = rune::vm_try!(as_f64(min));
+                let max = rune::vm_try!(as_f64(max));
+                VmResult::Ok(Value::Float(rng.range_f64(min, max)))
+            }
+        }
+    }
+
+    fn choose_from(&self, items: &[Value]) -> Option<Value> {
+        if items.is_empty() {
+            return None;
+        }
+        let mut input_state = self.input_state.lock().unwrap();
+        let index = input_state.random.stream(&self.name).index(items.len());
+        Some(items[index].clone())
+    }
+
+    fn shuffle_items(&self, items: &mut [Value]) {
+        let mut input_state = self.input_state.lock().unwrap();
+        input_state.random.stream(&self.name).shuffle(items);
+    }
+
+    #[rune::function(path = Self::random)]
+    fn random(&self) -> f64 {
+        self.next_f64()
+    }
+
+    #[rune::function(path = Self::range)]
+    fn range(&self, min: Value, max: Value) -> VmResult<Value> {
+        self.next_range(min, max)
+    }
+
+    #[rune::function(path = Self::choose)]
+    fn choose(&self, items: &rune::runtime::Vec) -> Option<Value> {
+        self.choose_from(items)
+    }
+
+    #[rune::function(path = Self::shuffle)]
+    fn shuffle(&self, items: &mut rune::runtime::Vec) {
+        self.shuffle_items(items)
+    }
+
+    /// Restarts only this stream from the given seed
+    #[rune::function(path = Self::seed)]
+    fn seed(&self, seed: i64) {
+        let mut input_state = self.input_state.lock().unwrap();
+        input_state.random.seed_stream(&self.name, seed as u64);
+    }
+}
+
+fn as_f64(value: Value) -> VmResult<f64> {
+    match value {
+        Value::Float(value) => VmResult::Ok(value),
+        Value::Integer(value) => VmResult::Ok(value as f64),
+        _ => VmResult::panic("Expected a number"),
+    }
+}
@@ -0,0 +1,50 @@
+use crate::scene_management::Property;
+use crate::storage::with_storage;
+use rune::runtime::{ToValue, Value, VmResult};
+use rune::{ContextError, Module};
+
+/// The `storage` module, values persist between sessions. Strings, numbers, booleans and arrays of
+/// them can be stored, numbers always come back as floats.
+pub fn module() -> Result<Module, ContextError> {
+    let mut m = Module::with_crate("storage")?;
+    m.function("set", |key: &str, value: Value| {
+        let value = rune::vm_try!(to_property(value));
+        with_storage(|storage| storage.set(key, value));
+        VmResult::Ok(())
+    })
+    .build()?;
+    m.function("get", |key: &str| {
+        match with_storage(|storage| storage.get(key)) {
+            Some(value) => VmResult::Ok(Some(rune::vm_try!(value.to_value()))),
+            None => VmResult::Ok(None),
+        }
+    })
+    .build()?;
+    m.function("remove", |key: &str| {
+        with_storage(|storage| storage.remove(key))
+    })
+    .build()?;
+    m.function("keys", || with_storage(|storage| storage.keys()))
+        .build()?;
+    Ok(m)
+}
+
+fn to_property(value: Value) -> VmResult<Property> {
+    match value {
+        Value::String(value) => VmResult::Ok(Property::String(
+            rune::vm_try!(value.borrow_ref()).to_string(),
+        )),
+        Value::Float(value) => VmResult::Ok(Property::Number(value as f32)),
+        Value::Integer(value) => VmResult::Ok(Property::Number(value as f32)),
+        Value::Bool(value) => VmResult::Ok(Property::Boolean(value)),
+        Value::Vec(items) => {
+            let items = rune::vm_try!(items.borrow_ref());
+            let mut array = Vec::with_capacity(items.len());
+            for item in items.iter() {
+                array.push(rune::vm_try!(to_property(item.clone())));
+            }
+            VmResult::Ok(Property::Array(array))
+        }
+        _ => VmResult::panic("Only strings, numbers, booleans and arrays of them can be stored"),
+    }
+}
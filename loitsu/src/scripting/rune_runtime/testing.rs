@@ -0,0 +1,544 @@
+use rune::ast;
+use rune::compile;
+use rune::macros::{quote, MacroContext, ToTokens, TokenStream};
+use rune::parse::Parser;
+use rune::runtime::{Value, VmResult};
+use rune::{ContextError, Module};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use runner::{run_tests, TestProject, TestResult};
+
+/// Registers the `assert!`, `assert_eq!` and `assert_ne!` macros. They are available everywhere,
+/// not just in tests.
+pub fn register(m: &mut Module) -> Result<(), ContextError> {
+    m.macro_meta(assert)?;
+    m.macro_meta(assert_eq)?;
+    m.macro_meta(assert_ne)?;
+    Ok(())
+}
+
+/// The `testing` module, used by `#[test]` functions run with `loitsu test`
+pub fn module() -> Result<Module, ContextError> {
+    let mut m = Module::with_crate("testing")?;
+    m.function("fail", |message: &str| {
+        VmResult::<()>::panic(message.to_string())
+    })
+    .build()?;
+    m.function("assert_failed", assert_failed).build()?;
+    #[cfg(not(target_arch = "wasm32"))]
+    runner::register(&mut m)?;
+    #[cfg(target_arch = "wasm32")]
+    m.function("load_scene", |_: &str| {
+        VmResult::<()>::panic("Scenes can only be loaded in tests run with `loitsu test`")
+    })
+    .build()?;
+    Ok(m)
+}
+
+/// Parses the optional message that follows the asserted expressions, an empty string if there
+/// is none
+fn parse_message(
+    cx: &mut MacroContext<'_, '_, '_>,
+    p: &mut Parser<'_>,
+) -> compile::Result<TokenStream> {
+    let mut message = TokenStream::new();
+    if p.parse::<Option<ast::Comma>>()?.is_some() {
+        let expr = p.parse::<ast::Expr>()?;
+        quote!(#expr).to_tokens(cx, &mut message)?;
+    } else {
+        let empty = cx.lit("")?;
+        quote!(#empty).to_tokens(cx, &mut message)?;
+    }
+    p.eof()?;
+    Ok(message)
+}
+
+#[rune::macro_]
+fn assert(cx: &mut MacroContext<'_, '_, '_>, stream: &TokenStream) -> compile::Result<TokenStream> {
+    let mut p = Parser::from_token_stream(stream, cx.input_span());
+    let expr = p.parse::<ast::Expr>()?;
+    let message = parse_message(cx, &mut p)?;
+    // the failing expression can be seen in the location of the error
+    let condition = cx.lit("")?;
+    let output = quote!(if !(#expr) {
+        ::testing::assert_failed(#condition, (), (), #message);
+    });
+    Ok(output.into_token_stream(cx)?)
+}
+
+#[rune::macro_]
+fn assert_eq(
+    cx: &mut MacroContext<'_, '_, '_>,
+    stream: &TokenStream,
+) -> compile::Result<TokenStream> {
+    let (left, right, message) = parse_comparison(cx, stream)?;
+    let condition = cx.lit("left == right")?;
+    let output = quote! {{
+        let left = #left;
+        let right = #right;
+        if !(left == right) {
+            ::testing::assert_failed(#condition, left, right, #message);
+        }
+    }};
+    Ok(output.into_token_stream(cx)?)
+}
+
+#[rune::macro_]
+fn assert_ne(
+    cx: &mut MacroContext<'_, '_, '_>,
+    stream: &TokenStream,
+) -> compile::Result<TokenStream> {
+    let (left, right, message) = parse_comparison(cx, stream)?;
+    let condition = cx.lit("left != right")?;
+    let output = quote! {{
+        let left = #left;
+        let right = #right;
+        if left == right {
+            ::testing::assert_failed(#condition, left, right, #message);
+        }
+    }};
+    Ok(output.into_token_stream(cx)?)
+}
+
+fn parse_comparison(
+    cx: &mut MacroContext<'_, '_, '_>,
+    stream: &TokenStream,
+) -> compile::Result<(ast::Expr, ast::Expr, TokenStream)> {
+    let mut p = Parser::from_token_stream(stream, cx.input_span());
+    let left = p.parse::<ast::Expr>()?;
+    p.parse::<ast::Comma>()?;
+    let right = p.parse::<ast::Expr>()?;
+    let message = parse_message(cx, &mut p)?;
+    Ok((left, right, message))
+}
+
+fn assert_failed(condition: &str, left: Value, right: Value, message: &str) -> VmResult<()> {
+    let mut text = "Assertion failed".to_string();
+    for part in [condition, message] {
+        if !part.is_empty() {
+            text.push_str(&format!(": {}", part));
+        }
+    }
+    // plain asserts don't have any values to show
+    if !matches!(left, Value::EmptyTuple) {
+        text.push_str(&format!(
+            "\n  left: {}\n right: {}",
+            describe(&left),
+            describe(&right)
+        ));
+    }
+    VmResult::panic(text)
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(value) => match value.borrow_ref() {
+            Ok(value) => format!("{:?}", value.as_str()),
+            Err(_) => "<string>".to_string(),
+        },
+        Value::Float(value) => format!("{:?}", value),
+        Value::Integer(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::EmptyTuple => "()".to_string(),
+        Value::Vec(items) => match items.borrow_ref() {
+            Ok(items) => format!(
+                "[{}]",
+                items.iter().map(describe).collect::<Vec<_>>().join(", ")
+            ),
+            Err(_) => "<vec>".to_string(),
+        },
+        Value::Option(option) => match option.borrow_ref() {
+            Ok(option) => match &*option {
+                Some(value) => format!("Some({})", describe(value)),
+                None => "None".to_string(),
+            },
+            Err(_) => "<option>".to_string(),
+        },
+        value => format!("{:?}", value),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod runner {
+    use super::super::{convert_entity, with_builtin, RuneEntity, RuneInstance};
+    use crate::headless::HeadlessRunner;
+    use crate::input::keyboard::{parse_key, KeyInput};
+    use crate::input::{actions::ActionMap, InputEvent, InputState};
+    use crate::scene_management::Scene;
+    use crate::scripting::{
+        Result, ScriptingError, ScriptingInstance, ScriptingSource, SourceLocation,
+    };
+    use crate::storage::{set_storage, MemoryStorage};
+    use crate::Preferences;
+    use lazy_static::lazy_static;
+    use rune::compile::{meta, CompileVisitor, MetaError, MetaRef};
+    use rune::runtime::{Value, VmResult};
+    use rune::{Any, ContextError, Hash, Module, Unit};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use winit::keyboard::{Key, NativeKey, NativeKeyCode, PhysicalKey};
+
+    lazy_static! {
+        // the project the tests are run in, scenes are loaded from it
+        static ref PROJECT: Mutex<Option<LoadedProject>> = Mutex::new(None);
+        // held while tests run, as they share the project and the storage
+        static ref RUNNING: Mutex<()> = Mutex::new(());
+    }
+
+    /// Sets the project for as long as it lives
+    struct ProjectGuard {
+        _running: MutexGuard<'static, ()>,
+    }
+
+    impl ProjectGuard {
+        fn new(project: TestProject, unit: Arc<Unit>) -> ProjectGuard {
+            let running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+            *PROJECT.lock().unwrap() = Some(LoadedProject { project, unit });
+            ProjectGuard { _running: running }
+        }
+    }
+
+    impl Drop for ProjectGuard {
+        fn drop(&mut self) {
+            *PROJECT.lock().unwrap() = None;
+        }
+    }
+
+    /// The project along with its compiled scripts, which every loaded scene shares instead of
+    /// compiling them again
+    struct LoadedProject {
+        project: TestProject,
+        unit: Arc<Unit>,
+    }
+
+    /// Everything tests need to load the scenes of a project
+    pub struct TestProject {
+        pub scripts: Vec<ScriptingSource>,
+        /// Built scenes that tests can load by name
+        pub scenes: Vec<Scene>,
+        pub preferences: Preferences,
+    }
+
+    pub struct TestResult {
+        pub name: String,
+        pub location: Option<SourceLocation>,
+        /// Why the test failed, `None` if it passed
+        pub error: Option<ScriptingError>,
+    }
+
+    pub(super) fn register(m: &mut Module) -> std::result::Result<(), ContextError> {
+        m.ty::<World>()?;
+        m.function("load_scene", World::load).build()?;
+        m.function_meta(World::step)?;
+        m.function_meta(World::step_frames)?;
+        m.function_meta(World::entity)?;
+        m.function_meta(World::frame_count)?;
+        m.function_meta(World::press_key)?;
+        m.function_meta(World::release_key)?;
+        Ok(())
+    }
+
+    /// A scene running in its own headless ECS, stepped manually by a test
+    #[derive(Any)]
+    #[rune(item = ::testing)]
+    struct World {
+        runner: HeadlessRunner<RuneInstance>,
+    }
+
+    impl World {
+        fn load(name: &str) -> VmResult<World> {
+            let project = PROJECT.lock().unwrap();
+            let Some(LoadedProject { project, unit }) = project.as_ref() else {
+                return VmResult::panic(
+                    "Scenes can only be loaded in tests run with `loitsu test`",
+                );
+            };
+            let Some(scene) = project.scenes.iter().find(|scene| scene.name == name) else {
+                return VmResult::panic(format!("Couldn't find scene '{}'", name));
+            };
+            let mut input_state = InputState::new();
+            input_state.actions = ActionMap::new(project.preferences.input.clone());
+            let mut runner = match HeadlessRunner::<RuneInstance>::with_initializer(
+                scene.clone(),
+                input_state,
+                |scripting, input_state, ecs| {
+                    scripting.compiled = Some(unit.clone());
+                    scripting.initialize(project.scripts.clone(), input_state, ecs)
+                },
+            ) {
+                Ok(runner) => runner,
+                Err(e) => return VmResult::panic(format!("Couldn't load scene '{}': {}", name, e)),
+            };
+            runner
+                .scripting
+                .set_budget(project.preferences.script_budget.clone());
+            VmResult::Ok(World { runner })
+        }
+
+        /// Runs a single frame that took `delta` seconds
+        #[rune::function(path = Self::step)]
+        fn step(&mut self, delta: f64) {
+            self.runner.step(delta as f32);
+        }
+
+        #[rune::function(path = Self::step_frames)]
+        fn step_frames(&mut self, frames: i64, delta: f64) {
+            for _ in 0..frames {
+                self.runner.step(delta as f32);
+            }
+        }
+
+        /// Returns the first entity with the given name
+        #[rune::function(path = Self::entity)]
+        fn entity(&self, name: &str) -> Option<RuneEntity> {
+            let ecs = self.runner.ecs.read().unwrap();
+            let entities = ecs.get_all_runtime_entities_flat();
+            let entity = entities
+                .iter()
+                .find(|entity| entity.lock().unwrap().get_name() == name)?;
+            let entity = entity.lock().unwrap();
+            Some(convert_entity(&entity))
+        }
+
+        #[rune::function(path = Self::frame_count)]
+        fn frame_count(&self) -> i64 {
+            self.runner.frame_count as i64
+        }
+
+        /// Holds a key down from the next frame on, until it is released
+        #[rune::function(path = Self::press_key)]
+        fn press_key(&mut self, key: &str) -> VmResult<()> {
+            self.send_key(key, true)
+        }
+
+        #[rune::function(path = Self::release_key)]
+        fn release_key(&mut self, key: &str) -> VmResult<()> {
+            self.send_key(key, false)
+        }
+
+        fn send_key(&mut self, key: &str, pressed: bool) -> VmResult<()> {
+            let (logical_key, physical_key) = match parse_key(key) {
+                Some(KeyInput::Logical(key)) => {
+                    (key, PhysicalKey::Unidentified(NativeKeyCode::Unidentified))
+                }
+                Some(KeyInput::Physical(code)) => (
+                    Key::Unidentified(NativeKey::Unidentified),
+                    PhysicalKey::Code(code),
+                ),
+                None => return VmResult::panic(format!("Unknown key '{}'", key)),
+            };
+            self.runner
+                .input_state
+                .lock()
+                .unwrap()
+                .process_event(InputEvent::Key {
+                    logical_key,
+                    physical_key,
+                    pressed,
+                    text: None,
+                });
+            VmResult::Ok(())
+        }
+    }
+
+    struct TestFunction {
+        hash: Hash,
+        name: String,
+        location: Option<SourceLocation>,
+        // the source id and offset, tests are run in the order they appear in
+        order: (usize, usize),
+    }
+
+    /// Collects the functions marked with `#[test]`
+    struct TestVisitor<'a> {
+        sources: &'a [ScriptingSource],
+        tests: Vec<TestFunction>,
+    }
+
+    impl CompileVisitor for TestVisitor<'_> {
+        fn register_meta(&mut self, meta: MetaRef<'_>) -> std::result::Result<(), MetaError> {
+            let meta::Kind::Function { is_test: true, .. } = meta.kind else {
+                return Ok(());
+            };
+            if self.tests.iter().any(|test| test.hash == meta.hash) {
+                return Ok(());
+            }
+            let order = meta.source.map_or((usize::MAX, 0), |source| {
+                (
+                    source.location.source_id.into_index(),
+                    source.location.span.start.0 as usize,
+                )
+            });
+            let location = self
+                .sources
+                .get(order.0)
+                .map(|script| SourceLocation::from_offset(script, order.1));
+            self.tests.push(TestFunction {
+                hash: meta.hash,
+                name: meta.item.to_string(),
+                location,
+                order,
+            });
+            Ok(())
+        }
+    }
+
+    /// Compiles the scripts of the project and runs every `#[test]` function whose name contains
+    /// `filter`. Each test gets an empty storage, so tests can't touch the saves of the player.
+    pub fn run_tests(project: TestProject, filter: Option<&str>) -> Result<Vec<TestResult>> {
+        let scripts = project.scripts.clone();
+        let budget = project.preferences.script_budget.clone();
+        // the tests themselves run in an empty scene
+        let empty = Scene {
+            name: "tests".to_string(),
+            entities: Vec::new(),
+            required_assets: Vec::new(),
+            shards: Vec::new(),
+            id_space: 0,
+        };
+        // the visitor sees the sources with the builtin script, like the compiler
+        let sources = with_builtin(scripts.clone());
+        let mut visitor = TestVisitor {
+            sources: &sources,
+            tests: Vec::new(),
+        };
+        let mut host = HeadlessRunner::<RuneInstance>::with_initializer(
+            empty,
+            InputState::new(),
+            |scripting, input_state, ecs| {
+                scripting.initialize_with_visitor(scripts, input_state, ecs, Some(&mut visitor))
+            },
+        )?;
+        host.scripting.set_budget(budget);
+        // the scenes the tests load reuse the unit compiled here
+        let unit = match &*host.scripting.unit.read().unwrap() {
+            Some((_, unit)) => unit.clone(),
+            None => unreachable!("the host has compiled the scripts"),
+        };
+        let _project = ProjectGuard::new(project, unit);
+        let scripting = &mut host.scripting;
+
+        let mut tests = visitor.tests;
+        tests.sort_by_key(|test| test.order);
+        tests.retain(|test| filter.is_none_or(|filter| test.name.contains(filter)));
+
+        let mut results = Vec::new();
+        for test in tests {
+            set_storage(Box::new(MemoryStorage::new()));
+            let vm = scripting.virtual_machine.as_mut().unwrap();
+            let (result, outcome, _) = scripting.budget.run(|| vm.call(test.hash, ()), false);
+            let error = match result {
+                Ok(Value::Result(result)) => match &*result.borrow_ref()? {
+                    Ok(_) => None,
+                    Err(e) => Some(ScriptingError::new(&format!(
+                        "Returned an error: {}",
+                        super::describe(e)
+                    ))),
+                },
+                Ok(_) => None,
+                Err(e) => Some(outcome.explain(scripting.vm_error(e))),
+            };
+            results.push(TestResult {
+                name: test.name,
+                location: test.location,
+                error,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::scripting::ScriptingSource;
+
+    #[test]
+    fn test_run_tests() {
+        let source = r#"
+            fn double(x) { x * 2 }
+
+            #[test]
+            fn doubles() { assert_eq!(double(2), 4); }
+
+            #[test]
+            fn fails() { assert_ne!(double(1), 2, "doubling"); }
+
+            #[test]
+            fn no_scenes() { testing::load_scene("main"); }
+        "#;
+        let project = TestProject {
+            scripts: vec![ScriptingSource {
+                name: "math.rn".to_string(),
+                source: source.to_string(),
+            }],
+            scenes: Vec::new(),
+            preferences: crate::Preferences {
+                default_scene: "main".to_string(),
+                ..Default::default()
+            },
+        };
+        let results = run_tests(project, None).unwrap();
+        let names: Vec<&str> = results.iter().map(|result| result.name.as_str()).collect();
+        assert_eq!(names, vec!["doubles", "fails", "no_scenes"]);
+        assert!(results[0].error.is_none());
+        assert_eq!(results[0].location.as_ref().unwrap().line, 4);
+        let error = results[1].error.as_ref().unwrap();
+        assert!(error.message().contains("left != right: doubling"));
+        assert_eq!(error.location().unwrap().line, 8);
+        assert!(results[2].error.is_some());
+    }
+
+    #[test]
+    fn test_scene_tests() {
+        use crate::scene_management::{Component, Entity, Scene};
+
+        let source = r#"
+            pub struct Jumper { jumps }
+
+            impl Jumper {
+                pub fn new() { Jumper { jumps: 0 } }
+                pub fn frame(self, entity) {
+                    if get_key_down("Space") { self.jumps += 1; }
+                }
+            }
+
+            fn jumps(world) {
+                world.entity("player").unwrap().get_component("Jumper").unwrap().jumps
+            }
+
+            #[test]
+            fn jumps_once() {
+                let world = testing::load_scene("main");
+                world.step(0.016);
+                assert_eq!(jumps(world), 0);
+                world.press_key("Space");
+                world.step_frames(3, 0.016);
+                assert_eq!(jumps(world), 1);
+                assert_eq!(world.frame_count(), 4);
+            }
+
+            #[test]
+            fn missing_scene() { testing::load_scene("missing"); }
+        "#;
+        let mut scene = Scene::new("main".to_string());
+        let mut player = Entity::new("player".to_string(), 1);
+        player.add_component(Component::new("Jumper".to_string(), 2));
+        scene.add_entity(player);
+        scene.id_space = 3;
+        let project = TestProject {
+            scripts: vec![ScriptingSource {
+                name: "jumper.rn".to_string(),
+                source: source.to_string(),
+            }],
+            scenes: vec![scene],
+            preferences: crate::Preferences {
+                default_scene: "main".to_string(),
+                ..Default::default()
+            },
+        };
+        let results = run_tests(project, None).unwrap();
+        assert!(results[0].error.is_none(), "{:?}", results[0].error);
+        let error = results[1].error.as_ref().unwrap();
+        assert!(error.message().contains("Couldn't find scene 'missing'"));
+    }
+}
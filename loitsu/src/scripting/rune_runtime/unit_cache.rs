@@ -0,0 +1,83 @@
+use crate::scripting::ScriptingError;
+use rune::compile::{ComponentRef, ItemBuf};
+use rune::runtime::unit::Logic;
+use rune::runtime::DebugInfo;
+use rune::{Hash, Unit};
+use serde_json::Value;
+
+type Result<T> = std::result::Result<T, ScriptingError>;
+
+/// Serializes a compiled unit so that it can be shipped in the static shard
+pub fn serialize(unit: &Unit) -> Result<Vec<u8>> {
+    serde_json::to_vec(unit).map_err(|e| cache_error("serialize", e))
+}
+
+/// Loads a unit written by `serialize`. Rune 0.13 writes item paths in a form it can't read back,
+/// so they are cleared before deserializing and the function paths of the debug info are put
+/// back afterwards. The type names used when formatting values are left empty.
+pub fn deserialize(bytes: &[u8]) -> Result<Unit> {
+    let mut unit: Value = serde_json::from_slice(bytes).map_err(|e| cache_error("read", e))?;
+    let Some(fields) = unit.as_object_mut() else {
+        return Err(ScriptingError::new("The compiled scripts aren't a unit"));
+    };
+    let debug = fields.remove("debug").unwrap_or(Value::Null);
+    for rtti in ["rtti", "variant_rtti"] {
+        if let Some(Value::Object(types)) = fields.get_mut(rtti) {
+            for ty in types.values_mut() {
+                ty["item"] = Value::Array(Vec::new());
+            }
+        }
+    }
+    let logic: Logic = serde_json::from_value(unit).map_err(|e| cache_error("read", e))?;
+    let debug = match debug {
+        Value::Null => None,
+        debug => Some(deserialize_debug(debug)?),
+    };
+    Unit::from_parts(logic, debug).map_err(|e| e.into())
+}
+
+fn deserialize_debug(mut debug: Value) -> Result<DebugInfo> {
+    let mut paths = Vec::new();
+    if let Some(Value::Object(functions)) = debug.get_mut("functions") {
+        for (hash, signature) in functions.iter_mut() {
+            paths.push((hash.clone(), signature["path"].take()));
+            signature["path"] = Value::Array(Vec::new());
+        }
+    }
+    let mut debug: DebugInfo = serde_json::from_value(debug).map_err(|e| cache_error("read", e))?;
+    for (hash, path) in paths {
+        let hash: Hash = serde_json::from_str(&hash).map_err(|e| cache_error("read", e))?;
+        if let Some(signature) = debug.functions.get_mut(&hash) {
+            signature.path = item_path(&path)?;
+        }
+    }
+    Ok(debug)
+}
+
+/// Reads an item path in the form rune writes it, `[{"Str": "Game"}, {"Str": "frame"}]`
+fn item_path(path: &Value) -> Result<ItemBuf> {
+    let invalid = || ScriptingError::new("Couldn't read an item path of the compiled scripts");
+    let mut components = Vec::new();
+    for component in path.as_array().ok_or_else(invalid)? {
+        let (kind, value) = component
+            .as_object()
+            .and_then(|c| c.iter().next())
+            .ok_or_else(invalid)?;
+        components.push(match (kind.as_str(), value) {
+            ("Crate", Value::String(name)) => ComponentRef::Crate(name),
+            ("Str", Value::String(name)) => ComponentRef::Str(name),
+            ("Id", Value::Number(id)) => {
+                ComponentRef::Id(id.as_u64().ok_or_else(invalid)? as usize)
+            }
+            _ => return Err(invalid()),
+        });
+    }
+    Ok(ItemBuf::with_item(components)?)
+}
+
+fn cache_error(action: &str, error: serde_json::Error) -> ScriptingError {
+    ScriptingError::new(&format!(
+        "Couldn't {} the compiled scripts: {}",
+        action, error
+    ))
+}
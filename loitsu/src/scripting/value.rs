@@ -0,0 +1,154 @@
+use crate::scene_management::Property;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A value passed between the engine and a scripting backend. Backends convert these to and from
+/// their own value types, so nothing outside of a backend has to know which language is used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptValue {
+    Unit,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<ScriptValue>),
+    /// A struct or map, by field name
+    Object(BTreeMap<String, ScriptValue>),
+}
+
+impl ScriptValue {
+    /// Returns the value as a float if it is a number
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ScriptValue::Integer(value) => Some(*value as f64),
+            ScriptValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to a component property. Units and objects have no property
+    /// counterpart, so `None` is returned for them.
+    pub fn to_property(&self) -> Option<Property> {
+        match self {
+            ScriptValue::Bool(value) => Some(Property::Boolean(*value)),
+            ScriptValue::Integer(value) => Some(Property::Number(*value as f32)),
+            ScriptValue::Float(value) => Some(Property::Number(*value as f32)),
+            ScriptValue::String(value) => Some(Property::String(value.clone())),
+            ScriptValue::Array(values) => values
+                .iter()
+                .map(ScriptValue::to_property)
+                .collect::<Option<Vec<Property>>>()
+                .map(Property::Array),
+            ScriptValue::Unit | ScriptValue::Object(_) => None,
+        }
+    }
+}
+
+impl From<Property> for ScriptValue {
+    fn from(property: Property) -> Self {
+        match property {
+            Property::String(value) => ScriptValue::String(value),
+            Property::Number(value) => ScriptValue::Float(value as f64),
+            Property::Boolean(value) => ScriptValue::Bool(value),
+            Property::Array(values) => {
+                ScriptValue::Array(values.into_iter().map(ScriptValue::from).collect())
+            }
+            Property::EntityReference(id) => ScriptValue::Integer(id.into()),
+            Property::ComponentReference(id) => ScriptValue::Integer(id.into()),
+        }
+    }
+}
+
+impl From<()> for ScriptValue {
+    fn from(_: ()) -> Self {
+        ScriptValue::Unit
+    }
+}
+
+impl From<bool> for ScriptValue {
+    fn from(value: bool) -> Self {
+        ScriptValue::Bool(value)
+    }
+}
+
+impl From<i64> for ScriptValue {
+    fn from(value: i64) -> Self {
+        ScriptValue::Integer(value)
+    }
+}
+
+impl From<f64> for ScriptValue {
+    fn from(value: f64) -> Self {
+        ScriptValue::Float(value)
+    }
+}
+
+impl From<&str> for ScriptValue {
+    fn from(value: &str) -> Self {
+        ScriptValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ScriptValue {
+    fn from(value: String) -> Self {
+        ScriptValue::String(value)
+    }
+}
+
+impl fmt::Display for ScriptValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptValue::Unit => write!(f, "()"),
+            ScriptValue::Bool(value) => write!(f, "{}", value),
+            ScriptValue::Integer(value) => write!(f, "{}", value),
+            ScriptValue::Float(value) => write!(f, "{}", value),
+            ScriptValue::String(value) => write!(f, "{:?}", value),
+            ScriptValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            ScriptValue::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_conversion() {
+        let property = Property::Array(vec![
+            Property::Number(1.5),
+            Property::String("a".to_string()),
+            Property::Boolean(true),
+        ]);
+        let value = ScriptValue::from(property.clone());
+        assert_eq!(
+            value,
+            ScriptValue::Array(vec![1.5.into(), "a".into(), true.into()])
+        );
+        assert_eq!(value.to_property(), Some(property));
+        assert_eq!(
+            ScriptValue::Integer(3).to_property(),
+            Some(Property::Number(3.0))
+        );
+        assert_eq!(ScriptValue::Object(BTreeMap::new()).to_property(), None);
+        assert_eq!(value.to_string(), "[1.5, \"a\", true]");
+    }
+}
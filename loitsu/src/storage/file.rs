@@ -0,0 +1,97 @@
+use super::Storage;
+use crate::scene_management::Property;
+use crate::warn;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub(super) const STORAGE_FILE: &str = "storage.bin";
+
+/// Keeps every value in a single bitcode encoded file, which is rewritten whenever something
+/// changes
+pub struct FileStorage {
+    path: PathBuf,
+    values: BTreeMap<String, Property>,
+}
+
+impl FileStorage {
+    /// Loads the values stored at `path`, starting out empty if there are none
+    pub fn open(path: PathBuf) -> FileStorage {
+        let values = match std::fs::read(&path) {
+            Ok(data) => bitcode::decode(&data).unwrap_or_else(|e| {
+                warn!("Couldn't read the stored values: {}", e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        FileStorage { path, values }
+    }
+
+    fn save(&self) {
+        let data = match bitcode::encode(&self.values) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Couldn't save the stored values: {}", e);
+                return;
+            }
+        };
+        // written to a temporary file first so that a crash can't leave a half written file behind
+        let temp = self.path.with_extension("tmp");
+        let result = match self.path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+        .and_then(|_| std::fs::write(&temp, data))
+        .and_then(|_| std::fs::rename(&temp, &self.path));
+        if let Err(e) = result {
+            warn!("Couldn't save the stored values: {}", e);
+        }
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> Option<Property> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: Property) {
+        self.values.insert(key.to_string(), value);
+        self.save();
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        let removed = self.values.remove(key).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_storage() {
+        let path = std::env::temp_dir().join(format!("loitsu_storage_{}.bin", std::process::id()));
+        let mut storage = FileStorage::open(path.clone());
+        storage.set("volume", Property::Number(0.5));
+        storage.set(
+            "unlocked",
+            Property::Array(vec![Property::String("forest".to_string())]),
+        );
+        storage.set("fullscreen", Property::Boolean(true));
+        assert!(storage.remove("fullscreen"));
+        assert!(!storage.remove("fullscreen"));
+
+        let storage = FileStorage::open(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(storage.keys(), vec!["unlocked", "volume"]);
+        assert_eq!(storage.get("volume"), Some(Property::Number(0.5)));
+        assert_eq!(storage.get("fullscreen"), None);
+    }
+}
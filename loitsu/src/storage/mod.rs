@@ -0,0 +1,87 @@
+use crate::scene_management::Property;
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file;
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod web;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileStorage;
+#[cfg(target_arch = "wasm32")]
+pub use web::LocalStorage;
+
+lazy_static! {
+    static ref STORAGE: Mutex<Box<dyn Storage>> = Mutex::new(default_storage());
+}
+
+/// Persistent key-value storage for settings and small saves
+pub trait Storage: Send {
+    fn get(&self, key: &str) -> Option<Property>;
+    fn set(&mut self, key: &str, value: Property);
+    /// Returns whether the key was stored
+    fn remove(&mut self, key: &str) -> bool;
+    /// Every stored key, sorted
+    fn keys(&self) -> Vec<String>;
+}
+
+/// Storage that only lasts as long as the process, used in tests and when nothing else is
+/// available
+#[derive(Default)]
+pub struct MemoryStorage {
+    values: BTreeMap<String, Property>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<Property> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: Property) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        self.values.remove(key).is_some()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_storage() -> Box<dyn Storage> {
+    if cfg!(test) {
+        return Box::new(MemoryStorage::new());
+    }
+    match crate::util::paths::user_data_dir() {
+        Some(dir) => Box::new(FileStorage::open(dir.join(file::STORAGE_FILE))),
+        None => {
+            crate::warn!("No user data directory, stored values won't persist");
+            Box::new(MemoryStorage::new())
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_storage() -> Box<dyn Storage> {
+    Box::new(LocalStorage)
+}
+
+/// Replaces the storage used by scripts, for example with a [`MemoryStorage`] in tests
+pub fn set_storage(storage: Box<dyn Storage>) {
+    *STORAGE.lock().unwrap() = storage;
+}
+
+pub fn with_storage<T>(f: impl FnOnce(&mut dyn Storage) -> T) -> T {
+    f(STORAGE.lock().unwrap().as_mut())
+}
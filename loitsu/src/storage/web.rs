@@ -0,0 +1,82 @@
+use super::Storage;
+use crate::scene_management::Property;
+use crate::warn;
+
+/// Stored keys are prefixed so that they don't collide with anything else on the page
+const KEY_PREFIX: &str = "loitsu_storage:";
+
+/// Stores each value as its own `localStorage` item
+pub struct LocalStorage;
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<Property> {
+        let hex = local_storage()?
+            .get_item(&format!("{}{}", KEY_PREFIX, key))
+            .ok()??;
+        match bitcode::decode(&from_hex(&hex)?) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Couldn't read the stored value '{}': {}", key, e);
+                None
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: Property) {
+        let data = match bitcode::encode(&value) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Couldn't store '{}': {}", key, e);
+                return;
+            }
+        };
+        if local_storage()
+            .and_then(|storage| {
+                storage
+                    .set_item(&format!("{}{}", KEY_PREFIX, key), &to_hex(&data))
+                    .ok()
+            })
+            .is_none()
+        {
+            warn!("Couldn't store '{}': local storage isn't available", key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        let Some(storage) = local_storage() else {
+            return false;
+        };
+        let key = format!("{}{}", KEY_PREFIX, key);
+        let stored = matches!(storage.get_item(&key), Ok(Some(_)));
+        stored && storage.remove_item(&key).is_ok()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Some(storage) = local_storage() else {
+            return Vec::new();
+        };
+        let length = storage.length().unwrap_or(0);
+        let mut keys: Vec<String> = (0..length)
+            .filter_map(|i| storage.key(i).ok()?)
+            .filter_map(|key| Some(key.strip_prefix(KEY_PREFIX)?.to_string()))
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// `localStorage` can only hold strings, so binary data is stored as hex
+pub(crate) fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
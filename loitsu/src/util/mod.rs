@@ -1,2 +1,5 @@
 pub mod id;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod paths;
+pub mod random;
 pub mod scaling;
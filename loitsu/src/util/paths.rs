@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+/// Returns the directory where per-user data such as settings should be stored. Each game gets
+/// its own directory, named after the executable.
+pub fn user_data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match std::env::var_os("XDG_DATA_HOME") {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+        }
+    };
+    let executable = std::env::current_exe().ok()?;
+    Some(base.join(executable.file_stem()?))
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+/// A xoshiro256** generator. It only uses integer math, so a seed produces the same numbers on
+/// desktop and web.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        let mut seed = seed;
+        let mut state = [0; 4];
+        for value in &mut state {
+            *value = splitmix64(&mut seed);
+        }
+        Rng { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// A number between 0 (inclusive) and 1 (exclusive)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number between `min` (inclusive) and `max` (exclusive)
+    pub fn range_f64(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// An integer between `min` (inclusive) and `max` (exclusive), `min` if the range is empty
+    pub fn range_i64(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        let span = max.wrapping_sub(min) as u64;
+        min.wrapping_add(self.below(span) as i64)
+    }
+
+    /// An index into a collection of `len` items, `len` must not be 0
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i + 1);
+            items.swap(i, j);
+        }
+    }
+
+    // rejection sampling keeps the result unbiased
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// Named random streams that are all derived from a single seed. Each stream only advances when
+/// it is used, so adding randomness in one place doesn't change the numbers of another.
+#[derive(Debug, Clone)]
+pub struct Random {
+    seed: u64,
+    streams: HashMap<String, Rng>,
+}
+
+/// The stream used when scripts don't ask for a specific one
+pub const DEFAULT_STREAM: &str = "default";
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Resets every stream to start from the new seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    pub fn stream(&mut self, name: &str) -> &mut Rng {
+        let seed = self.seed;
+        self.streams
+            .entry(name.to_string())
+            .or_insert_with(|| Rng::new(stream_seed(seed, name)))
+    }
+
+    /// Restarts a single stream from its own seed, independent of the global one
+    pub fn seed_stream(&mut self, name: &str, seed: u64) {
+        self.streams.insert(name.to_string(), Rng::new(seed));
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Random::new(0)
+    }
+}
+
+/// A seed based on the current time, used when the project doesn't specify one
+pub fn time_seed() -> u64 {
+    instant::SystemTime::now()
+        .duration_since(instant::SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// FNV-1a, the std hasher isn't guaranteed to stay the same between releases
+fn stream_seed(seed: u64, name: &str) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    let mut seed = seed;
+    splitmix64(&mut seed) ^ hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_streams() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        let first: Vec<u64> = (0..4).map(|_| a.stream("loot").next_u64()).collect();
+        // using another stream doesn't affect the loot stream
+        b.stream(DEFAULT_STREAM).next_u64();
+        let second: Vec<u64> = (0..4).map(|_| b.stream("loot").next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(a.stream("loot").next_u64(), a.stream("enemies").next_u64());
+
+        // the output must not change between platforms or versions
+        assert_eq!(Rng::new(0).next_u64(), 0x99EC_5F36_CB75_F2B4);
+
+        let mut rng = Rng::new(7);
+        for _ in 0..100 {
+            let value = rng.range_i64(-3, 3);
+            assert!((-3..3).contains(&value));
+            let value = rng.range_f64(1.0, 2.0);
+            assert!((1.0..2.0).contains(&value));
+        }
+        let mut items: Vec<u32> = (0..10).collect();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+    }
+}
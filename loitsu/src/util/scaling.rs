@@ -3,7 +3,7 @@ use crate::rendering::core::CameraState;
 /// Expects a vector, representing a scale, in screen space and returns a vector in world space
 /// This is the inverse of `as_screen_scale`
 pub fn as_world_scale(camera: &CameraState, pos: (f32, f32)) -> (f32, f32) {
-    let (x, y) = (pos.0, pos.1);
+    let (x, y) = (pos.0 / camera.viewport[2], pos.1 / camera.viewport[3]); // Relative to the viewport
     let (x, y) = (x / camera.scale, y / camera.scale); // Scale the mouse position by the camera zoom
     let (x, y) = (x / camera.aspect.1, y / camera.aspect.0);
     (x * 2.0, y * 2.0)
@@ -14,34 +14,53 @@ pub fn as_world_scale(camera: &CameraState, pos: (f32, f32)) -> (f32, f32) {
 pub fn as_screen_scale(camera: &CameraState, pos: (f32, f32)) -> (f32, f32) {
     let (x, y) = (pos.0 * camera.aspect.1, pos.1 * camera.aspect.0);
     let (x, y) = (x * camera.scale, y * camera.scale);
+    let (x, y) = (x * camera.viewport[2], y * camera.viewport[3]); // Relative to the screen
     (x / 2.0, y / 2.0)
 }
 
 /// Expects a vector, representing a position, in screen space and returns a vector in world space
-/// Here, (0, 0) represents the bottom-left corner of the screen.
+/// Here, (0, 0) represents the top-left corner of the screen.
 /// This is the inverse of `as_screen_pos`
 pub fn as_world_pos(camera: &CameraState, pos: (f32, f32)) -> (f32, f32) {
-    let aspect = camera.aspect;
-    let (x, y) = (pos.0 * 2.0, pos.1 * 2.0); // Scale the mouse position to the range -1.0 to 1.0
+    let (aspect, viewport) = (camera.aspect, camera.viewport);
+    let (x, y) = (
+        (pos.0 - viewport[0]) / viewport[2],
+        (pos.1 - viewport[1]) / viewport[3],
+    ); // Relative to the viewport
+    let (x, y) = (x * 2.0, y * 2.0); // Scale the mouse position to the range -1.0 to 1.0
     let (x, y) = (x - 1.0, -y + 1.0); // Center the mouse position
     let (x, y) = (x / camera.scale, y / camera.scale); // Scale the mouse position by the camera zoom
-    let (x, y) = (x + camera.position.x, y + camera.position.y); // Move the mouse position by the camera position
-    (x / aspect.1, y / aspect.0)
+    let (x, y) = rotate((x / aspect.1, y / aspect.0), camera.rotation); // Turn the mouse position with the camera
+    let (x, y) = (
+        x + camera.position.x / aspect.1,
+        y + camera.position.y / aspect.0,
+    ); // Move the mouse position by the camera position
+    (x, y)
 }
 
 /// Expects a vector, representing a position, in world space and returns a vector in screen space
-/// Here, (0, 0) represents the bottom-left corner of the screen.
+/// Here, (0, 0) represents the top-left corner of the screen.
 /// This is the inverse of `as_world_pos`
 pub fn as_screen_pos(camera: &CameraState, pos: (f32, f32)) -> (f32, f32) {
-    let aspect = camera.aspect;
+    let (aspect, viewport) = (camera.aspect, camera.viewport);
     let (x, y) = (
-        pos.0 * aspect.1 - camera.position.x,
-        pos.1 * aspect.0 - camera.position.y,
+        pos.0 - camera.position.x / aspect.1,
+        pos.1 - camera.position.y / aspect.0,
     ); // Move the mouse position by the camera position
+    let (x, y) = rotate((x, y), -camera.rotation); // Turn the mouse position with the camera
+    let (x, y) = (x * aspect.1, y * aspect.0);
     let (x, y) = (x * camera.scale, y * camera.scale); // Scale the mouse position by the camera zoom
     let (x, y) = (x, y); // Scale the mouse position to the range -1.0 to 1.0
     let (x, y) = (x + 1.0, -y + 1.0); // Center the mouse position
-    (x / 2.0, y / 2.0)
+    let (x, y) = (x / 2.0, y / 2.0);
+    let (x, y) = (x * viewport[2] + viewport[0], y * viewport[3] + viewport[1]); // Relative to the screen
+    (x, y)
+}
+
+/// Rotates a vector counter-clockwise by `angle` radians
+fn rotate(pos: (f32, f32), angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    (pos.0 * cos - pos.1 * sin, pos.0 * sin + pos.1 * cos)
 }
 
 #[cfg(test)]
@@ -53,7 +72,9 @@ mod tests {
         let camera = CameraState {
             position: (0.0, 0.0).into(),
             scale: 2.0,
+            rotation: 0.0,
             aspect: (1.0, 2.0),
+            viewport: [0.0, 0.0, 1.0, 1.0],
             dirty: false,
             view: [[0.0; 4]; 4],
         };
@@ -77,7 +98,9 @@ mod tests {
         let camera = CameraState {
             position: (0.0, 0.0).into(),
             scale: 3.0,
+            rotation: 0.0,
             aspect: (1.0, 2.0),
+            viewport: [0.0, 0.0, 1.0, 1.0],
             dirty: false,
             view: [
                 [1.0, 0.0, 0.0, 0.0],
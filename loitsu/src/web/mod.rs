@@ -3,6 +3,8 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 extern "C" {
     fn set_status(status: u32);
+    #[wasm_bindgen(js_name = set_profiler_overlay)]
+    fn set_profiler_overlay_text(text: String);
     #[cfg(feature = "editor")]
     fn add_loading_task(task: String);
     #[cfg(feature = "editor")]
@@ -28,3 +30,8 @@ pub fn notify(r#type: u32, title: &str, message: &str) {
 pub fn update_loading_status(status: u32) {
     set_status(status);
 }
+
+/// Shows the profiler overlay with the given text, an empty text hides it
+pub fn set_profiler_overlay(text: &str) {
+    set_profiler_overlay_text(text.to_string());
+}